@@ -50,10 +50,61 @@ fn generate_strk_bind(name: &str, abi_file: &str, bind_out: PathBuf) {
         String::from("openzeppelin::upgrades::upgradeable::UpgradeableComponent::Event"),
         String::from("UpgradeableCptEvent"),
     );
+    aliases.insert(
+        String::from("openzeppelin::security::pausable::PausableComponent::Event"),
+        String::from("PausableCptEvent"),
+    );
     aliases.insert(
         String::from("contracts::client::mailboxclient_component::MailboxclientComponent::Event"),
         String::from("MailboxclientEvent"),
     );
+    aliases.insert(
+        String::from("contracts::client::router_component::RouterComponent::Event"),
+        String::from("RouterCptEvent"),
+    );
+    aliases.insert(
+        String::from("contracts::client::gas_router_component::GasRouterComponent::Event"),
+        String::from("GasRouterCptEvent"),
+    );
+    aliases.insert(
+        String::from("token::components::token_router::TokenRouterComponent::Event"),
+        String::from("TokenRouterCptEvent"),
+    );
+    aliases.insert(
+        String::from(
+            "token::components::hyp_erc20_collateral_component::HypErc20CollateralComponent::Event",
+        ),
+        String::from("HypErc20CollateralCptEvent"),
+    );
+    aliases.insert(
+        String::from("openzeppelin::token::erc20::erc20::ERC20Component::Event"),
+        String::from("ERC20CptEvent"),
+    );
+    // xerc20 package (openzeppelin v0.20 split crates)
+    aliases.insert(
+        String::from("openzeppelin_access::ownable::ownable::OwnableComponent::Event"),
+        String::from("OwnableCptEvent"),
+    );
+    aliases.insert(
+        String::from("openzeppelin_upgrades::upgradeable::UpgradeableComponent::Event"),
+        String::from("UpgradeableCptEvent"),
+    );
+    aliases.insert(
+        String::from("openzeppelin_token::erc20::erc20::ERC20Component::Event"),
+        String::from("ERC20CptEvent"),
+    );
+    aliases.insert(
+        String::from("openzeppelin_utils::cryptography::nonces::NoncesComponent::Event"),
+        String::from("NoncesCptEvent"),
+    );
+    aliases.insert(
+        String::from("xerc20::xerc20::component::XERC20Component::Event"),
+        String::from("XERC20CptEvent"),
+    );
+    aliases.insert(
+        String::from("xerc20::lockbox::component::XERC20LockboxComponent::Event"),
+        String::from("XERC20LockboxCptEvent"),
+    );
 
     let abigen = cainome::rs::Abigen::new(name, abi_file).with_types_aliases(aliases);
 
@@ -69,7 +120,7 @@ fn main() {
     let eth_abi_base = current_dir().unwrap().join("abis");
     let eth_bind_base = current_dir()
         .unwrap()
-        .join("tests")
+        .join("src")
         .join("contracts/eth/bind");
 
     // Check if the Ethereum ABI directory exists
@@ -80,6 +131,10 @@ fn main() {
         ("Mailbox", "mailbox"),
         ("FastHypERC20", "fast_hyp_erc20"),
         ("FastHypERC20Collateral", "fast_hyp_erc20_collateral"),
+        ("IInterchainSecurityModule", "ism"),
+        ("IAggregationIsm", "aggregation_ism"),
+        ("IMultisigIsm", "multisig_ism"),
+        ("IRoutingIsm", "routing_ism"),
         ("TestMultisigIsm", "test_mock_ism"),
         ("TestRecipient", "test_mock_msg_receiver"),
         ("TestMerkleTreeHook", "test_merkle_tree_hook"),
@@ -97,35 +152,59 @@ fn main() {
     }
 
     // Generate Starknet bindings
-    let strk_abi_base = current_dir()
+    let strk_project_base = current_dir()
         .unwrap()
         .parent() // Move one directory up to source directory
         .unwrap()
-        .join("cairo")
-        .join("target")
-        .join("dev");
+        .to_path_buf();
     let strk_bind_base = current_dir()
         .unwrap()
-        .join("tests")
+        .join("src")
         .join("contracts/strk/bind");
 
-    // Check if the Starknet ABI directory exists
-    check_path_exists(&strk_abi_base);
     check_path_exists(&strk_bind_base);
 
+    // (scarb project, package, contract, bind_out)
     let strk_deployments = [
-        ("mailbox", "mailbox"),
-        ("domain_routing_ism", "routing"),
-        ("ism", "ism"),
-        ("messageid_multisig_ism", "multisig_ism"),
-        ("validator_announce", "validator_announce"),
+        ("cairo", "contracts", "mailbox", "mailbox"),
+        ("cairo", "contracts", "domain_routing_ism", "routing"),
+        ("cairo", "contracts", "ism", "ism"),
+        ("cairo", "contracts", "hook", "hook"),
+        ("cairo", "contracts", "merkle_tree_hook", "merkle_tree_hook"),
+        ("cairo", "contracts", "domain_routing_hook", "routing_hook"),
+        ("cairo", "contracts", "protocol_fee", "protocol_fee"),
+        ("cairo", "contracts", "aggregation", "aggregation"),
+        ("cairo", "contracts", "pausable_ism", "pausable_ism"),
+        (
+            "cairo",
+            "contracts",
+            "messageid_multisig_ism",
+            "multisig_ism",
+        ),
+        (
+            "cairo",
+            "contracts",
+            "validator_announce",
+            "validator_announce",
+        ),
+        ("cairo", "contracts", "TestERC20", "test_erc20"),
+        ("cairo", "token", "HypXERC20", "hyp_xerc20"),
+        ("cairo", "token", "HypXERC20Lockbox", "hyp_xerc20_lockbox"),
+        ("xerc20", "xerc20", "XERC20Factory", "xerc20_factory"),
+        ("xerc20", "xerc20", "XERC20", "xerc20_token"),
+        ("xerc20", "xerc20", "XERC20Lockbox", "xerc20_lockbox"),
     ];
 
-    for (abi_file, bind_out) in strk_deployments {
+    for (project, package, contract, bind_out) in strk_deployments {
+        let strk_abi_base = strk_project_base.join(project).join("target").join("dev");
+
+        // Check if the Starknet ABI directory exists
+        check_path_exists(&strk_abi_base);
+
         generate_strk_bind(
-            abi_file,
+            contract,
             strk_abi_base
-                .join(format!("contracts_{abi_file}.contract_class.json"))
+                .join(format!("{package}_{contract}.contract_class.json"))
                 .to_str()
                 .unwrap(),
             strk_bind_base.join(format!("{bind_out}.rs")),
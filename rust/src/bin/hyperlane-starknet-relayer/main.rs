@@ -0,0 +1,135 @@
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+use clap::Parser;
+use hyperlane_starknet::{
+    config::{Config, Protocol},
+    indexer::MerkleTreeIndexer,
+    relayer::{
+        AnnouncedCheckpoints, Chain, IndexedMerkleTrees, MetadataBuilders, RelayPolicy, Relayer,
+        RelayerSettings, RelayerStore,
+    },
+    validator::MerkleTreeHook,
+};
+
+/// Relay the messages dispatched between the mailboxes of Starknet and EVM chains.
+#[derive(Parser)]
+#[command(name = "hyperlane-starknet-relayer", version)]
+struct Cli {
+    /// Chain configuration file.
+    #[arg(
+        long,
+        short,
+        env = "HYPERLANE_STARKNET_CONFIG",
+        default_value = "hyperlane.json"
+    )]
+    config: PathBuf,
+
+    /// Chains to relay between, all the configured chains when omitted.
+    #[arg(long, env = "HYPERLANE_STARKNET_CHAINS", value_delimiter = ',')]
+    chain: Vec<String>,
+
+    /// Database keeping the indexing progress and the delivery state of the messages.
+    #[arg(long, env = "HYPERLANE_RELAYER_DB", default_value = "relayer.db")]
+    db: PathBuf,
+
+    /// JSON file of the relay policy, filtering the relayed messages. All messages are relayed
+    /// by default.
+    #[arg(long, env = "HYPERLANE_RELAYER_POLICY")]
+    policy: Option<PathBuf>,
+
+    /// S3 compatible endpoint the S3 requests are sent to instead of AWS, when reading the
+    /// checkpoints of the validators.
+    #[arg(long, env = "HYPERLANE_S3_ENDPOINT")]
+    s3_endpoint: Option<String>,
+
+    /// Delay between two rounds of indexing and delivery, in seconds.
+    #[arg(long, default_value_t = 5)]
+    poll_interval: u64,
+
+    /// Maximum number of blocks indexed per round and chain.
+    #[arg(long, default_value_t = 1000)]
+    max_block_range: u64,
+
+    /// Failed delivery attempts after which a message is given up, unlimited by default.
+    #[arg(long)]
+    max_attempts: Option<u32>,
+
+    /// Maximum number of messages processed in a single Starknet transaction.
+    #[arg(long, default_value_t = 16)]
+    max_batch_size: usize,
+}
+
+#[tokio::main]
+async fn main() -> eyre::Result<()> {
+    let cli = Cli::parse();
+
+    if cli.max_block_range == 0 {
+        eyre::bail!("--max-block-range must be positive");
+    }
+    if cli.max_batch_size == 0 {
+        eyre::bail!("--max-batch-size must be positive");
+    }
+
+    let config = Config::load(&cli.config)?;
+    let names: Vec<&str> = match cli.chain.is_empty() {
+        true => config.chains.keys().map(String::as_str).collect(),
+        false => cli.chain.iter().map(String::as_str).collect(),
+    };
+
+    let mut chains = vec![];
+    let mut checkpoints = AnnouncedCheckpoints::new();
+    let mut trees = IndexedMerkleTrees::new();
+    for name in names {
+        let chain_config = config.chain(Some(name))?;
+        let chain = Chain::from_config(chain_config).await?;
+        println!("Relaying domain {} ({name})", chain.domain());
+        chains.push(chain);
+
+        // multisig metadata of the messages from Starknet origins with validators
+        let addresses = &chain_config.addresses;
+        if chain_config.protocol == Protocol::Starknet
+            && addresses.contains_key("merkle_tree_hook")
+            && addresses.contains_key("validator_announce")
+        {
+            checkpoints = checkpoints.with_validator_announce(
+                chain_config.domain,
+                chain_config.provider()?,
+                chain_config.address("validator_announce")?,
+            );
+            let hook = MerkleTreeHook::new(
+                chain_config.provider()?,
+                chain_config.address("merkle_tree_hook")?,
+                chain_config.domain,
+            );
+            trees = trees.with_indexer(
+                MerkleTreeIndexer::new(hook, chain_config.start_block.unwrap_or(0))
+                    .with_max_block_range(cli.max_block_range),
+            );
+        }
+    }
+    if let Some(endpoint) = &cli.s3_endpoint {
+        checkpoints = checkpoints.with_s3_endpoint(endpoint);
+    }
+
+    let settings = RelayerSettings {
+        poll_interval: Duration::from_secs(cli.poll_interval),
+        max_block_range: cli.max_block_range,
+        max_attempts: cli.max_attempts,
+        max_batch_size: cli.max_batch_size,
+        ..Default::default()
+    };
+    let policy = match &cli.policy {
+        Some(path) => RelayPolicy::load(path)?,
+        None => RelayPolicy::default(),
+    };
+    let store = RelayerStore::open(&cli.db)?;
+    Relayer::new(chains, store, settings)
+        .with_policy(policy)
+        .with_metadata_builders(
+            MetadataBuilders::new().with_multisig(Arc::new(checkpoints), Arc::new(trees)),
+        )
+        .run()
+        .await;
+
+    Ok(())
+}
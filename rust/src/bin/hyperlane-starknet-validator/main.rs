@@ -0,0 +1,144 @@
+use std::{path::PathBuf, time::Duration};
+
+use clap::Parser;
+use hyperlane_starknet::{
+    checkpoint_syncer::syncer_from_storage,
+    config::{Config, ValidatorSignerArgs},
+    validator::{MerkleTreeHook, SigningLedger, Validator, ValidatorSettings},
+};
+
+/// Sign the checkpoints of the merkle tree hook of a Starknet origin mailbox.
+#[derive(Parser)]
+#[command(name = "hyperlane-starknet-validator", version)]
+struct Cli {
+    /// Chain configuration file.
+    #[arg(
+        long,
+        short,
+        env = "HYPERLANE_STARKNET_CONFIG",
+        default_value = "hyperlane.json"
+    )]
+    config: PathBuf,
+
+    /// Origin chain to validate. Can be omitted when the configuration holds a single chain.
+    #[arg(long, env = "HYPERLANE_STARKNET_CHAIN")]
+    chain: Option<String>,
+
+    #[command(flatten)]
+    signer: ValidatorSignerArgs,
+
+    /// Storage the signed checkpoints are written to: a local directory, or an
+    /// `s3://{bucket}/{region}[/{folder}]` location.
+    #[arg(
+        long,
+        env = "HYPERLANE_VALIDATOR_CHECKPOINTS",
+        default_value = "checkpoints"
+    )]
+    checkpoints: String,
+
+    /// S3 compatible endpoint the S3 requests are sent to instead of AWS.
+    #[arg(long, env = "HYPERLANE_S3_ENDPOINT")]
+    s3_endpoint: Option<String>,
+
+    /// Delay between two rounds of indexing and signing, in seconds.
+    #[arg(long, default_value_t = 5)]
+    poll_interval: u64,
+
+    /// Maximum number of blocks indexed per query.
+    #[arg(long, default_value_t = 1000)]
+    max_block_range: u64,
+
+    /// Sqlite database recording every checkpoint signed, guarding against equivocation.
+    #[arg(
+        long,
+        env = "HYPERLANE_VALIDATOR_LEDGER",
+        default_value = "validator.db"
+    )]
+    ledger: PathBuf,
+
+    /// Print the checkpoints recorded in the ledger and whether the validator halted, then exit.
+    #[arg(long, conflicts_with = "resume")]
+    audit: bool,
+
+    /// Lift the halt of the validator, once the origin chain was checked, then exit.
+    #[arg(long)]
+    resume: bool,
+}
+
+#[tokio::main]
+async fn main() -> eyre::Result<()> {
+    let cli = Cli::parse();
+
+    if cli.max_block_range == 0 {
+        eyre::bail!("--max-block-range must be positive");
+    }
+
+    let config = Config::load(&cli.config)?;
+    let chain = config.chain(cli.chain.as_deref())?;
+    let hook = MerkleTreeHook::new(
+        chain.provider()?,
+        chain.address("merkle_tree_hook")?,
+        chain.domain,
+    );
+    let ledger = SigningLedger::open(&cli.ledger)?;
+
+    if cli.audit {
+        return audit(&ledger, chain.domain, &hook.address.to_bytes_be());
+    }
+    if cli.resume {
+        match ledger.halted()? {
+            Some(halted) => println!("Resuming the validator, halted: {}", halted.reason),
+            None => println!("The validator is not halted"),
+        }
+        return ledger.resume();
+    }
+
+    let signer = cli.signer.evm_signer().await?;
+    let syncer = syncer_from_storage(&cli.checkpoints, cli.s3_endpoint.as_deref())?;
+
+    let settings = ValidatorSettings {
+        poll_interval: Duration::from_secs(cli.poll_interval),
+        max_block_range: cli.max_block_range,
+        start_block: chain.start_block.unwrap_or(0),
+    };
+    let mut validator = Validator::new(hook, signer, syncer.clone(), ledger, settings);
+    println!(
+        "Validating domain {} as {:?}, publishing to {}",
+        chain.domain,
+        validator.address(),
+        syncer.announcement_location()
+    );
+    validator.run().await
+}
+
+/// Prints the checkpoints signed for the merkle tree hook of `origin`, one per line.
+fn audit(ledger: &SigningLedger, origin: u32, merkle_tree_hook: &[u8; 32]) -> eyre::Result<()> {
+    const PAGE: u32 = 1000;
+
+    match ledger.halted()? {
+        Some(halted) => println!("{halted}"),
+        None => println!("validator not halted"),
+    }
+
+    let mut from = 0;
+    loop {
+        let entries = ledger.entries(origin, merkle_tree_hook, from, PAGE)?;
+        for entry in &entries {
+            let value = &entry.checkpoint.value;
+            println!(
+                "index {} root 0x{} message 0x{} signed at {} signature 0x{}",
+                value.checkpoint.index,
+                hex::encode(value.checkpoint.root),
+                hex::encode(value.message_id),
+                entry.signed_at,
+                hex::encode(&entry.checkpoint.signature)
+            );
+        }
+        match entries.last() {
+            Some(last) if entries.len() == PAGE as usize => {
+                from = last.checkpoint.value.checkpoint.index + 1
+            }
+            _ => return Ok(()),
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use cainome::cairo_serde::{ClassHash, ContractAddress};
+use clap::Subcommand;
+use hyperlane_starknet::{
+    config::ChainConfig, contracts::strk::mailbox::mailbox, tx_watcher::TxWatcher,
+};
+use starknet::{accounts::ConnectedAccount, core::types::FieldElement};
+
+#[derive(Subcommand)]
+pub enum AdminCommand {
+    /// Set the ISM used by the mailbox for recipients not specifying one.
+    SetDefaultIsm { ism: FieldElement },
+    /// Set the hook called on dispatches not specifying one.
+    SetDefaultHook { hook: FieldElement },
+    /// Set the hook called on every dispatch.
+    SetRequiredHook { hook: FieldElement },
+    /// Transfer the ownership of a contract. Defaults to the mailbox.
+    TransferOwnership {
+        new_owner: FieldElement,
+        #[arg(long)]
+        contract: Option<FieldElement>,
+    },
+    /// Upgrade a contract to a declared class. Defaults to the mailbox.
+    Upgrade {
+        class_hash: FieldElement,
+        #[arg(long)]
+        contract: Option<FieldElement>,
+    },
+}
+
+pub async fn run(chain: &ChainConfig, command: AdminCommand) -> eyre::Result<()> {
+    let account = chain.account().await?;
+    let mailbox_address = chain.mailbox()?;
+
+    // the hyperlane contracts share the openzeppelin ownable and upgradeable entrypoints exposed
+    // by the mailbox binding
+    let res = match command {
+        AdminCommand::SetDefaultIsm { ism } => {
+            mailbox::new(mailbox_address, &account)
+                .set_default_ism(&ContractAddress(ism))
+                .send()
+                .await?
+        }
+        AdminCommand::SetDefaultHook { hook } => {
+            mailbox::new(mailbox_address, &account)
+                .set_default_hook(&ContractAddress(hook))
+                .send()
+                .await?
+        }
+        AdminCommand::SetRequiredHook { hook } => {
+            mailbox::new(mailbox_address, &account)
+                .set_required_hook(&ContractAddress(hook))
+                .send()
+                .await?
+        }
+        AdminCommand::TransferOwnership {
+            new_owner,
+            contract,
+        } => {
+            mailbox::new(contract.unwrap_or(mailbox_address), &account)
+                .transfer_ownership(&ContractAddress(new_owner))
+                .send()
+                .await?
+        }
+        AdminCommand::Upgrade {
+            class_hash,
+            contract,
+        } => {
+            mailbox::new(contract.unwrap_or(mailbox_address), &account)
+                .upgrade(&ClassHash(class_hash))
+                .send()
+                .await?
+        }
+    };
+
+    TxWatcher::new(account.provider())
+        .wait(res.transaction_hash)
+        .await?;
+    println!("Transaction {:#064x} accepted", res.transaction_hash);
+
+    Ok(())
+}
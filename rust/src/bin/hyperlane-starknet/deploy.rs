@@ -0,0 +1,43 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use hyperlane_starknet::{
+    config::ChainConfig,
+    contracts::strk::{declare_contract_from, deploy_contract_with_salt},
+};
+use starknet::core::types::FieldElement;
+
+#[derive(Args)]
+pub struct DeployArgs {
+    /// Contract to deploy, e.g. `mailbox` or `merkle_tree_hook`.
+    contract: String,
+
+    /// Scarb build directory holding the contract artifacts.
+    #[arg(long, default_value = "../cairo/target/dev")]
+    artifacts: PathBuf,
+
+    /// Scarb package the contract belongs to.
+    #[arg(long, default_value = "contracts")]
+    package: String,
+
+    /// Constructor calldata, as a list of felts.
+    #[arg(long, num_args = 0..)]
+    calldata: Vec<FieldElement>,
+
+    /// Deployment salt.
+    #[arg(long, default_value = "0x0")]
+    salt: FieldElement,
+}
+
+pub async fn run(chain: &ChainConfig, args: DeployArgs) -> eyre::Result<()> {
+    let account = chain.account().await?;
+
+    let build_path_prefix = format!("{}/{}_", args.artifacts.display(), args.package);
+    let class_hash = declare_contract_from(&account, &build_path_prefix, &args.contract).await?;
+
+    let (address, _) =
+        deploy_contract_with_salt(class_hash, args.calldata, args.salt, &account).await?;
+    println!("Deployed {} at {:#064x}", args.contract, address);
+
+    Ok(())
+}
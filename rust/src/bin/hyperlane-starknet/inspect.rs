@@ -0,0 +1,142 @@
+use cainome::cairo_serde::U256;
+use clap::Args;
+use hyperlane_starknet::{
+    config::ChainConfig,
+    contracts::strk::{hook, mailbox::mailboxReader, to_strk_message_bytes},
+    hook::{build_dispatch_message, inspect_dispatch_hooks, StarknetHookSource},
+    ism::{inspect_ism, inspect_recipient_ism, StarknetIsmSource},
+};
+use starknet::core::types::FieldElement;
+
+use crate::utils::{parse_hex, parse_u256};
+
+#[derive(Args)]
+pub struct IsmArgs {
+    /// ISM address. Defaults to the mailbox default ISM.
+    #[arg(conflicts_with = "recipient")]
+    address: Option<FieldElement>,
+
+    /// Inspect the ISM of this recipient, hex encoded.
+    #[arg(long, value_parser = parse_u256)]
+    recipient: Option<U256>,
+
+    /// Only follow the routes of this origin domain.
+    #[arg(long)]
+    origin: Option<u32>,
+
+    /// Print the ISM tree as JSON.
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Args)]
+pub struct HookArgs {
+    /// Hook to use instead of the mailbox default hook.
+    address: Option<FieldElement>,
+
+    /// Destination domain of the quoted message.
+    #[arg(long)]
+    destination: u32,
+
+    /// Recipient of the quoted message, hex encoded. Defaults to zero.
+    #[arg(long, value_parser = parse_u256)]
+    recipient: Option<U256>,
+
+    /// Body of the quoted message, hex encoded. Defaults to an empty body.
+    #[arg(long)]
+    body: Option<String>,
+
+    /// Metadata passed to the hooks, hex encoded.
+    #[arg(long)]
+    hook_metadata: Option<String>,
+
+    /// Print the hook graph as JSON.
+    #[arg(long)]
+    json: bool,
+}
+
+pub async fn run_ism(chain: &ChainConfig, args: IsmArgs) -> eyre::Result<()> {
+    let provider = chain.provider()?;
+
+    let ism = match (args.address, &args.recipient) {
+        (Some(address), _) => {
+            inspect_ism(&StarknetIsmSource::new(&provider), address, args.origin).await?
+        }
+        (None, Some(recipient)) => {
+            inspect_recipient_ism(&provider, chain.mailbox()?, recipient, args.origin).await?
+        }
+        (None, None) => {
+            let address = mailboxReader::new(chain.mailbox()?, &provider)
+                .get_default_ism()
+                .call()
+                .await?;
+            inspect_ism(&StarknetIsmSource::new(&provider), address.0, args.origin).await?
+        }
+    };
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&ism)?);
+    } else {
+        print!("{ism}");
+    }
+
+    Ok(())
+}
+
+pub async fn run_hook(chain: &ChainConfig, args: HookArgs) -> eyre::Result<()> {
+    let provider = chain.provider()?;
+    let mailbox = chain.mailbox()?;
+
+    // the configured account sends the quoted message, when there is one
+    let sender = chain
+        .account
+        .as_ref()
+        .map_or(FieldElement::ZERO, |account| account.address);
+    let recipient = args.recipient.unwrap_or(U256 { low: 0, high: 0 });
+    let body = args
+        .body
+        .as_deref()
+        .map(parse_hex)
+        .transpose()
+        .map_err(|e| eyre::eyre!(e))?
+        .unwrap_or_default();
+    let metadata = args
+        .hook_metadata
+        .as_deref()
+        .map(parse_hex)
+        .transpose()
+        .map_err(|e| eyre::eyre!(e))?
+        .unwrap_or_default();
+    let message = build_dispatch_message(
+        &provider,
+        mailbox,
+        sender,
+        args.destination,
+        &recipient,
+        &body,
+    )
+    .await?;
+
+    let metadata = to_strk_message_bytes(&metadata);
+    let metadata = hook::Bytes {
+        size: metadata.size,
+        data: metadata.data,
+    };
+
+    let hooks = inspect_dispatch_hooks(
+        &StarknetHookSource::new(&provider),
+        mailbox,
+        &metadata,
+        &message,
+        args.address,
+    )
+    .await?;
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&hooks)?);
+    } else {
+        print!("{hooks}");
+    }
+
+    Ok(())
+}
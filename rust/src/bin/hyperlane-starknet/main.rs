@@ -0,0 +1,76 @@
+mod admin;
+mod deploy;
+mod inspect;
+mod process;
+mod send;
+mod status;
+mod utils;
+mod validator;
+
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+use hyperlane_starknet::config::Config;
+
+/// Operate the hyperlane contracts deployed on Starknet.
+#[derive(Parser)]
+#[command(name = "hyperlane-starknet", version)]
+struct Cli {
+    /// Chain configuration file.
+    #[arg(
+        long,
+        short,
+        global = true,
+        env = "HYPERLANE_STARKNET_CONFIG",
+        default_value = "hyperlane.json"
+    )]
+    config: PathBuf,
+
+    /// Chain to operate on. Can be omitted when the configuration holds a single chain.
+    #[arg(long, global = true, env = "HYPERLANE_STARKNET_CHAIN")]
+    chain: Option<String>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Dispatch a message through the mailbox.
+    Send(send::SendArgs),
+    /// Deliver a message to the mailbox, simulating it first to estimate its fee.
+    Process(process::ProcessArgs),
+    /// Query the dispatch and delivery status of a message, on Starknet and EVM chains.
+    Status(status::StatusArgs),
+    /// Inspect an interchain security module.
+    Ism(inspect::IsmArgs),
+    /// Inspect the post dispatch hooks of a destination and the fee each of them charges.
+    Hook(inspect::HookArgs),
+    /// Declare and deploy a contract from its build artifacts.
+    Deploy(deploy::DeployArgs),
+    /// Administer the deployed contracts.
+    #[command(subcommand)]
+    Admin(admin::AdminCommand),
+    /// Operate a validator of the chain.
+    #[command(subcommand)]
+    Validator(validator::ValidatorCommand),
+}
+
+#[tokio::main]
+async fn main() -> eyre::Result<()> {
+    let cli = Cli::parse();
+
+    let config = Config::load(&cli.config)?;
+    let chain = cli.chain.as_deref();
+
+    match cli.command {
+        Command::Send(args) => send::run(config.chain(chain)?, args).await,
+        Command::Process(args) => process::run(config.chain(chain)?, args).await,
+        Command::Status(args) => status::run(&config, chain, args).await,
+        Command::Ism(args) => inspect::run_ism(config.chain(chain)?, args).await,
+        Command::Hook(args) => inspect::run_hook(config.chain(chain)?, args).await,
+        Command::Deploy(args) => deploy::run(config.chain(chain)?, args).await,
+        Command::Admin(command) => admin::run(config.chain(chain)?, command).await,
+        Command::Validator(command) => validator::run(config.chain(chain)?, command).await,
+    }
+}
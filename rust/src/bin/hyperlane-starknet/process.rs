@@ -0,0 +1,75 @@
+use clap::Args;
+use hyperlane_starknet::{
+    config::ChainConfig,
+    contracts::strk::mailbox::Message,
+    message::HyperlaneMessage,
+    process::{estimate_process, parse_fee_multiplier, process, ProcessError},
+};
+
+use crate::utils::{format_bytes, parse_hex};
+
+#[derive(Args)]
+pub struct ProcessArgs {
+    /// Encoded message, hex encoded.
+    #[arg(long)]
+    message: String,
+
+    /// Metadata verified by the recipient ISM, hex encoded.
+    #[arg(long, default_value = "0x")]
+    metadata: String,
+
+    /// Factor applied to the estimated fee, overriding the chain fee policy.
+    #[arg(long, value_parser = parse_fee_multiplier)]
+    fee_multiplier: Option<f64>,
+
+    /// Upper bound of the max fee in wei, overriding the chain fee policy.
+    #[arg(long)]
+    max_fee: Option<u128>,
+
+    /// Only simulate the delivery.
+    #[arg(long)]
+    dry_run: bool,
+}
+
+pub async fn run(chain: &ChainConfig, args: ProcessArgs) -> eyre::Result<()> {
+    let account = chain.account().await?;
+    let mailbox = chain.mailbox()?;
+
+    let message =
+        HyperlaneMessage::from_bytes(&parse_hex(&args.message).map_err(|e| eyre::eyre!(e))?)?;
+    let metadata = parse_hex(&args.metadata).map_err(|e| eyre::eyre!(e))?;
+    let mut fee = chain.fee.clone();
+    if let Some(multiplier) = args.fee_multiplier {
+        fee.multiplier = multiplier;
+    }
+    if args.max_fee.is_some() {
+        fee.max_fee = args.max_fee;
+    }
+
+    println!("Message id: {}", format_bytes(&message.id()));
+    let strk_message = Message::from(&message);
+    let estimate = estimate_process(&account, mailbox, &metadata, &strk_message, &fee)
+        .await
+        .map_err(report)?;
+    println!("Estimated fee: {}", estimate.overall_fee);
+    println!("Max fee: {}", estimate.max_fee);
+
+    if args.dry_run {
+        return Ok(());
+    }
+
+    let hash = process(&account, mailbox, &metadata, &strk_message, &estimate)
+        .await
+        .map_err(report)?;
+    println!("Process transaction: {hash:#064x}");
+
+    Ok(())
+}
+
+/// Tells whether retrying the delivery can succeed along with the failure.
+fn report(error: ProcessError) -> eyre::Report {
+    match error.is_permanent() {
+        true => eyre::eyre!("permanent failure: {error}"),
+        false => eyre::eyre!("transient failure: {error}"),
+    }
+}
@@ -0,0 +1,151 @@
+use std::path::PathBuf;
+
+use cainome::cairo_serde::{ContractAddress, U256};
+use clap::Args;
+use hyperlane_starknet::{
+    config::ChainConfig,
+    contracts::strk::{
+        mailbox::{mailbox, mailboxReader, Event as MailboxEvent},
+        test_erc20::TestERC20,
+        to_strk_message_bytes, MAILBOX_FEE_TOKEN,
+    },
+    tx_watcher::TxWatcher,
+};
+use starknet::{
+    accounts::{Account, ConnectedAccount},
+    core::types::FieldElement,
+};
+
+use crate::utils::{format_u256, parse_h256, parse_hex, u256_lt};
+
+#[derive(Args)]
+pub struct SendArgs {
+    /// Destination domain.
+    #[arg(long)]
+    to_domain: u32,
+
+    /// Recipient address, hex encoded and left padded to 32 bytes.
+    #[arg(long, value_parser = parse_h256)]
+    recipient: [u8; 32],
+
+    #[command(flatten)]
+    body: BodyArgs,
+
+    /// Metadata passed to the hooks, hex encoded.
+    #[arg(long)]
+    hook_metadata: Option<String>,
+
+    /// Hook to use instead of the mailbox default hook.
+    #[arg(long)]
+    hook: Option<FieldElement>,
+}
+
+/// Message body, given in exactly one of the supported encodings.
+#[derive(Args)]
+#[group(required = true, multiple = false)]
+struct BodyArgs {
+    /// Message body, as an utf8 string.
+    #[arg(long)]
+    body: Option<String>,
+
+    /// Message body, hex encoded.
+    #[arg(long)]
+    body_hex: Option<String>,
+
+    /// File holding the raw message body.
+    #[arg(long)]
+    body_file: Option<PathBuf>,
+}
+
+impl BodyArgs {
+    fn bytes(&self) -> eyre::Result<Vec<u8>> {
+        match (&self.body, &self.body_hex, &self.body_file) {
+            (Some(body), _, _) => Ok(body.as_bytes().to_vec()),
+            (_, Some(body), _) => parse_hex(body).map_err(|e| eyre::eyre!(e)),
+            (_, _, Some(path)) => std::fs::read(path)
+                .map_err(|e| eyre::eyre!("failed to read {}: {e}", path.display())),
+            _ => unreachable!("clap requires one of the body arguments"),
+        }
+    }
+}
+
+pub async fn run(chain: &ChainConfig, args: SendArgs) -> eyre::Result<()> {
+    let account = chain.account().await?;
+    let mailbox_address = chain.mailbox()?;
+
+    let recipient = U256::from_bytes_be(&args.recipient);
+    let body = to_strk_message_bytes(&args.body.bytes()?);
+    let hook_metadata = args
+        .hook_metadata
+        .as_deref()
+        .map(parse_hex)
+        .transpose()
+        .map_err(|e| eyre::eyre!(e))?
+        .map(|metadata| to_strk_message_bytes(&metadata));
+    let hook = args.hook.map(ContractAddress);
+
+    // quote the fees charged by the required and default (or custom) hooks
+    let fee = mailboxReader::new(mailbox_address, account.provider())
+        .quote_dispatch(&args.to_domain, &recipient, &body, &hook_metadata, &hook)
+        .call()
+        .await?;
+    println!("Quoted fee: {}", format_amount(&fee));
+
+    // the mailbox pulls the fees from the sender
+    let fee_token = TestERC20::new(MAILBOX_FEE_TOKEN, &account);
+    let allowance = fee_token
+        .allowance(
+            &ContractAddress(account.address()),
+            &ContractAddress(mailbox_address),
+        )
+        .call()
+        .await?;
+    if u256_lt(&allowance, &fee) {
+        let res = fee_token
+            .approve(&ContractAddress(mailbox_address), &fee)
+            .send()
+            .await?;
+        TxWatcher::new(account.provider())
+            .wait(res.transaction_hash)
+            .await?;
+        println!("Approved fee token: {:#064x}", res.transaction_hash);
+    }
+
+    let res = mailbox::new(mailbox_address, &account)
+        .dispatch(
+            &args.to_domain,
+            &recipient,
+            &body,
+            &fee,
+            &hook_metadata,
+            &hook,
+        )
+        .send()
+        .await?;
+    println!("Dispatch transaction: {:#064x}", res.transaction_hash);
+
+    let outcome = TxWatcher::new(account.provider())
+        .wait(res.transaction_hash)
+        .await?;
+
+    for event in outcome.events_from::<MailboxEvent>(mailbox_address) {
+        match event {
+            MailboxEvent::Dispatch(dispatch) => println!("Nonce: {}", dispatch.message.nonce),
+            MailboxEvent::DispatchId(dispatch_id) => {
+                println!("Message id: {}", format_u256(&dispatch_id.id))
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Formats a token amount in its smallest unit.
+fn format_amount(amount: &U256) -> String {
+    if amount.high == 0 {
+        amount.low.to_string()
+    } else {
+        format_u256(amount)
+    }
+}
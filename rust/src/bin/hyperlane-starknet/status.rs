@@ -0,0 +1,269 @@
+use std::{sync::Arc, time::Duration};
+
+use cainome::cairo_serde::U256;
+use clap::Args;
+use ethers::{
+    contract::parse_log,
+    providers::Middleware,
+    types::{Address, H256},
+};
+use hyperlane_starknet::{
+    config::{ChainConfig, Config, Protocol},
+    contracts::{
+        eth,
+        strk::{
+            ism::ismReader,
+            mailbox::{mailboxReader, Event as MailboxEvent},
+        },
+    },
+    ism::ModuleType,
+    tx_watcher::{Finality, TxWatcher},
+};
+use starknet::core::types::FieldElement;
+
+use crate::utils::{format_bytes, parse_h256, u256_to_h256};
+
+#[derive(Args)]
+pub struct StatusArgs {
+    /// Id of the message, hex encoded.
+    #[arg(value_parser = parse_h256)]
+    message_id: [u8; 32],
+
+    /// Transaction dispatching the message on the origin chain. The destination chain and the
+    /// recipient are then read from the dispatched message.
+    #[arg(long, value_parser = parse_h256, requires = "origin")]
+    origin_tx: Option<[u8; 32]>,
+
+    /// Origin chain of the message, as named in the configuration.
+    #[arg(long, requires = "origin_tx")]
+    origin: Option<String>,
+
+    /// Recipient of the message, used to look up its ISM when the origin transaction is not given.
+    #[arg(long, value_parser = parse_h256)]
+    recipient: Option<[u8; 32]>,
+}
+
+/// Destination and recipient of a dispatched message.
+struct Dispatched {
+    destination: u32,
+    recipient: [u8; 32],
+}
+
+/// Delivery of a message on its destination mailbox.
+struct Delivery {
+    processor: [u8; 32],
+    block_number: u64,
+}
+
+pub async fn run(config: &Config, chain: Option<&str>, args: StatusArgs) -> eyre::Result<()> {
+    let message_id = args.message_id;
+    println!("Message {}", format_bytes(&message_id));
+
+    let dispatched = match (&args.origin, args.origin_tx) {
+        (Some(origin), Some(origin_tx)) => {
+            let origin = config.chain(Some(origin))?;
+            let dispatched = match origin.protocol {
+                Protocol::Starknet => strk_dispatched(origin, message_id, origin_tx).await?,
+                Protocol::Ethereum => evm_dispatched(origin, message_id, origin_tx).await?,
+            };
+
+            match &dispatched {
+                Some(dispatched) => println!(
+                    "Dispatched on domain {} to domain {}, recipient {}",
+                    origin.domain,
+                    dispatched.destination,
+                    format_bytes(&dispatched.recipient)
+                ),
+                None => println!(
+                    "Not dispatched by transaction {} on domain {}",
+                    format_bytes(&origin_tx),
+                    origin.domain
+                ),
+            }
+
+            dispatched
+        }
+        _ => None,
+    };
+
+    let destination = match (&dispatched, chain) {
+        (Some(dispatched), None) => config.chain_by_domain(dispatched.destination)?,
+        (_, chain) => config.chain(chain)?,
+    };
+    let recipient = dispatched.map(|d| d.recipient).or(args.recipient);
+
+    if let Some(recipient) = recipient {
+        let (ism, module_type) = match destination.protocol {
+            Protocol::Starknet => strk_recipient_ism(destination, recipient).await?,
+            Protocol::Ethereum => evm_recipient_ism(destination, recipient).await?,
+        };
+        println!(
+            "Recipient ISM on domain {}: {} ({module_type})",
+            destination.domain,
+            format_bytes(&ism)
+        );
+    }
+
+    let delivery = match destination.protocol {
+        Protocol::Starknet => strk_delivery(destination, message_id).await?,
+        Protocol::Ethereum => evm_delivery(destination, message_id).await?,
+    };
+    match delivery {
+        Some(delivery) => {
+            println!("Delivered on domain {}", destination.domain);
+            println!("Processor: {}", format_bytes(&delivery.processor));
+            println!("Processed at block: {}", delivery.block_number);
+        }
+        None => println!("Not delivered on domain {}", destination.domain),
+    }
+
+    Ok(())
+}
+
+async fn strk_dispatched(
+    chain: &ChainConfig,
+    message_id: [u8; 32],
+    tx: [u8; 32],
+) -> eyre::Result<Option<Dispatched>> {
+    let provider = chain.provider()?;
+    let mailbox = chain.mailbox()?;
+
+    let outcome = TxWatcher::new(&provider)
+        .with_finality(Finality::Pending)
+        .with_timeout(Duration::from_secs(5))
+        .wait(
+            FieldElement::from_bytes_be(&tx)
+                .map_err(|_| eyre::eyre!("invalid transaction hash {}", format_bytes(&tx)))?,
+        )
+        .await?;
+
+    // each `Dispatch` is directly followed by the `DispatchId` of the message
+    let mut dispatches = vec![];
+    let mut ids = vec![];
+    for event in outcome.events_from::<MailboxEvent>(mailbox) {
+        match event {
+            MailboxEvent::Dispatch(dispatch) => dispatches.push(dispatch),
+            MailboxEvent::DispatchId(dispatch_id) => ids.push(dispatch_id.id),
+            _ => {}
+        }
+    }
+
+    let message_id = U256::from_bytes_be(&message_id);
+    Ok(dispatches
+        .into_iter()
+        .zip(ids)
+        .find(|(_, id)| *id == message_id)
+        .map(|(dispatch, _)| Dispatched {
+            destination: dispatch.message.destination,
+            recipient: u256_to_h256(&dispatch.message.recipient),
+        }))
+}
+
+async fn evm_dispatched(
+    chain: &ChainConfig,
+    message_id: [u8; 32],
+    tx: [u8; 32],
+) -> eyre::Result<Option<Dispatched>> {
+    let provider = chain.evm_provider()?;
+    let mailbox = chain.evm_address("mailbox")?;
+
+    let receipt = provider
+        .get_transaction_receipt(H256(tx))
+        .await?
+        .ok_or(eyre::eyre!("transaction {} not found", format_bytes(&tx)))?;
+
+    let mut dispatches = vec![];
+    let mut ids = vec![];
+    for log in receipt.logs.into_iter().filter(|l| l.address == mailbox) {
+        if let Ok(dispatch) = parse_log::<eth::mailbox::DispatchFilter>(log.clone()) {
+            dispatches.push(dispatch);
+        } else if let Ok(dispatch_id) = parse_log::<eth::mailbox::DispatchIdFilter>(log) {
+            ids.push(dispatch_id.message_id);
+        }
+    }
+
+    Ok(dispatches
+        .into_iter()
+        .zip(ids)
+        .find(|(_, id)| *id == message_id)
+        .map(|(dispatch, _)| Dispatched {
+            destination: dispatch.destination,
+            recipient: dispatch.recipient,
+        }))
+}
+
+async fn strk_recipient_ism(
+    chain: &ChainConfig,
+    recipient: [u8; 32],
+) -> eyre::Result<([u8; 32], ModuleType)> {
+    let provider = chain.provider()?;
+
+    let ism = mailboxReader::new(chain.mailbox()?, &provider)
+        .recipient_ism(&U256::from_bytes_be(&recipient))
+        .call()
+        .await?;
+    let module_type = ismReader::new(ism.0, &provider)
+        .module_type()
+        .call()
+        .await?;
+
+    Ok((ism.0.to_bytes_be(), ModuleType::from(&module_type)))
+}
+
+async fn evm_recipient_ism(
+    chain: &ChainConfig,
+    recipient: [u8; 32],
+) -> eyre::Result<([u8; 32], ModuleType)> {
+    let client = Arc::new(chain.evm_provider()?);
+
+    let ism = eth::mailbox::Mailbox::new(chain.evm_address("mailbox")?, client.clone())
+        .recipient_ism(Address::from_slice(&recipient[12..]))
+        .call()
+        .await?;
+    let module_type = eth::ism::IInterchainSecurityModule::new(ism, client)
+        .module_type()
+        .call()
+        .await?;
+    let module_type =
+        ModuleType::from_u8(module_type).ok_or(eyre::eyre!("unknown module type {module_type}"))?;
+
+    Ok((H256::from(ism).0, module_type))
+}
+
+async fn strk_delivery(
+    chain: &ChainConfig,
+    message_id: [u8; 32],
+) -> eyre::Result<Option<Delivery>> {
+    let provider = chain.provider()?;
+    let mailbox = mailboxReader::new(chain.mailbox()?, &provider);
+    let message_id = U256::from_bytes_be(&message_id);
+
+    if !mailbox.delivered(&message_id).call().await? {
+        return Ok(None);
+    }
+
+    let processor = mailbox.processor(&message_id).call().await?;
+    let block_number = mailbox.processed_at(&message_id).call().await?;
+
+    Ok(Some(Delivery {
+        processor: processor.0.to_bytes_be(),
+        block_number,
+    }))
+}
+
+async fn evm_delivery(chain: &ChainConfig, message_id: [u8; 32]) -> eyre::Result<Option<Delivery>> {
+    let client = Arc::new(chain.evm_provider()?);
+    let mailbox = eth::mailbox::Mailbox::new(chain.evm_address("mailbox")?, client);
+
+    if !mailbox.delivered(message_id).call().await? {
+        return Ok(None);
+    }
+
+    let processor = mailbox.processor(message_id).call().await?;
+    let block_number = mailbox.processed_at(message_id).call().await?;
+
+    Ok(Some(Delivery {
+        processor: H256::from(processor).0,
+        block_number,
+    }))
+}
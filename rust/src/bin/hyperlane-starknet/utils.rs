@@ -0,0 +1,53 @@
+use cainome::cairo_serde::U256;
+
+/// Decodes a hex string, with or without `0x` prefix.
+pub fn parse_hex(value: &str) -> Result<Vec<u8>, String> {
+    let digits = value.trim_start_matches("0x");
+    let digits = if digits.len() % 2 == 1 {
+        format!("0{digits}")
+    } else {
+        digits.to_string()
+    };
+
+    hex::decode(digits).map_err(|e| format!("invalid hex {value}: {e}"))
+}
+
+/// Parses a hex encoded 32 bytes value, left padding shorter values such as EVM addresses.
+pub fn parse_h256(value: &str) -> Result<[u8; 32], String> {
+    let bytes = parse_hex(value)?;
+    if bytes.len() > 32 {
+        return Err(format!("{value} is longer than 32 bytes"));
+    }
+
+    let mut output = [0u8; 32];
+    output[32 - bytes.len()..].copy_from_slice(&bytes);
+    Ok(output)
+}
+
+/// Parses a hex encoded 32 bytes value into a cairo `u256`.
+pub fn parse_u256(value: &str) -> Result<U256, String> {
+    parse_h256(value).map(|bytes| U256::from_bytes_be(&bytes))
+}
+
+/// Returns whether `a` is lower than `b`.
+pub fn u256_lt(a: &U256, b: &U256) -> bool {
+    (a.high, a.low) < (b.high, b.low)
+}
+
+/// Formats a cairo `u256` as a 32 bytes hex string.
+pub fn format_u256(value: &U256) -> String {
+    format!("0x{:032x}{:032x}", value.high, value.low)
+}
+
+/// Returns the 32 bytes big endian encoding of a cairo `u256`.
+pub fn u256_to_h256(value: &U256) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    bytes[..16].copy_from_slice(&value.high.to_be_bytes());
+    bytes[16..].copy_from_slice(&value.low.to_be_bytes());
+    bytes
+}
+
+/// Formats bytes as a `0x` prefixed hex string.
+pub fn format_bytes(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
@@ -0,0 +1,91 @@
+use clap::{Args, Subcommand};
+use ethers::signers::Signer;
+use hyperlane_starknet::{
+    checkpoint::Announcement,
+    checkpoint_syncer::syncer_from_storage,
+    config::{ChainConfig, ValidatorSignerArgs},
+    validator::{
+        announce, announcement_digest_onchain, sign_announcement, Announced,
+        StarknetValidatorAnnouncer,
+    },
+};
+
+use crate::utils::format_bytes;
+
+#[derive(Subcommand)]
+pub enum ValidatorCommand {
+    /// Announce the storage of the validator checkpoints in `validator_announce`, unless already
+    /// announced.
+    Announce(AnnounceArgs),
+}
+
+#[derive(Args)]
+pub struct AnnounceArgs {
+    #[command(flatten)]
+    signer: ValidatorSignerArgs,
+
+    /// Storage the signed checkpoints are written to: a local directory, or an
+    /// `s3://{bucket}/{region}[/{folder}]` location.
+    #[arg(
+        long,
+        env = "HYPERLANE_VALIDATOR_CHECKPOINTS",
+        default_value = "checkpoints"
+    )]
+    checkpoints: String,
+
+    /// S3 compatible endpoint the S3 requests are sent to instead of AWS.
+    #[arg(long, env = "HYPERLANE_S3_ENDPOINT")]
+    s3_endpoint: Option<String>,
+
+    /// Only sign the announcement and check its digest against the contract.
+    #[arg(long)]
+    dry_run: bool,
+}
+
+pub async fn run(chain: &ChainConfig, command: ValidatorCommand) -> eyre::Result<()> {
+    match command {
+        ValidatorCommand::Announce(args) => run_announce(chain, args).await,
+    }
+}
+
+async fn run_announce(chain: &ChainConfig, args: AnnounceArgs) -> eyre::Result<()> {
+    let validator_announce = chain.address("validator_announce")?;
+    let signer = args.signer.evm_signer().await?;
+    let syncer = syncer_from_storage(&args.checkpoints, args.s3_endpoint.as_deref())?;
+
+    let announcement = Announcement {
+        validator: signer.address(),
+        mailbox_address: chain.mailbox()?.to_bytes_be(),
+        mailbox_domain: chain.domain,
+        storage_location: syncer.announcement_location(),
+    };
+    let signed = sign_announcement(&signer, announcement).await?;
+    println!("Validator: {:?}", signed.value.validator);
+    println!("Storage location: {}", signed.value.storage_location);
+    println!("Digest: {}", format_bytes(&signed.value.digest()));
+
+    if args.dry_run {
+        let expected = announcement_digest_onchain(
+            &chain.provider()?,
+            validator_announce,
+            &signed.value.storage_location,
+        )
+        .await?;
+        if expected != signed.value.digest() {
+            eyre::bail!(
+                "digest does not match the validator_announce digest {}",
+                format_bytes(&expected)
+            );
+        }
+        println!("Digest matches validator_announce");
+        return Ok(());
+    }
+
+    let contract = StarknetValidatorAnnouncer::new(chain.account().await?, validator_announce);
+    match announce(&contract, &signed, syncer.as_ref()).await? {
+        Announced::Already => println!("Storage location already announced"),
+        Announced::Submitted(hash) => println!("Announce transaction {hash:#064x} accepted"),
+    }
+
+    Ok(())
+}
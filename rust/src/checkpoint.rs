@@ -0,0 +1,208 @@
+use ethers::{
+    types::Address,
+    utils::{hash_message, keccak256},
+};
+
+/// Depth of the merkle tree of dispatched messages.
+pub const TREE_DEPTH: usize = 32;
+
+/// State of the merkle tree hook of an origin mailbox after the insertion of message `index`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Checkpoint {
+    pub merkle_tree_hook: [u8; 32],
+    pub origin: u32,
+    pub root: [u8; 32],
+    pub index: u32,
+}
+
+/// Checkpoint along with the id of the message inserted at its index, the value validators sign.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CheckpointWithMessageId {
+    pub checkpoint: Checkpoint,
+    pub message_id: [u8; 32],
+}
+
+impl CheckpointWithMessageId {
+    /// Digest signed by the validators, see [`checkpoint_digest`].
+    pub fn digest(&self) -> [u8; 32] {
+        checkpoint_digest(
+            self.checkpoint.origin,
+            &self.checkpoint.merkle_tree_hook,
+            &self.checkpoint.root,
+            self.checkpoint.index,
+            &self.message_id,
+        )
+    }
+}
+
+/// Checkpoint signed by a validator, `r || s || v` signature.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SignedCheckpoint {
+    pub value: CheckpointWithMessageId,
+    pub signature: Vec<u8>,
+}
+
+/// Hash binding the checkpoints to the merkle tree hook of their origin chain.
+pub fn domain_hash(origin: u32, merkle_tree_hook: &[u8; 32]) -> [u8; 32] {
+    let mut input = Vec::with_capacity(45);
+    input.extend_from_slice(&origin.to_be_bytes());
+    input.extend_from_slice(merkle_tree_hook);
+    input.extend_from_slice(b"HYPERLANE");
+    keccak256(input)
+}
+
+/// Digest signed by the validators for a checkpoint, `eth_sign` prefix included.
+///
+/// # Arguments
+///
+/// * `origin` - The origin domain of the checkpoint.
+/// * `merkle_tree_hook` - The merkle tree hook of the origin mailbox.
+/// * `root` - The root of the merkle tree at the checkpoint.
+/// * `index` - The index of the last message inserted in the tree at the checkpoint.
+/// * `message_id` - The id of this last message.
+pub fn checkpoint_digest(
+    origin: u32,
+    merkle_tree_hook: &[u8; 32],
+    root: &[u8; 32],
+    index: u32,
+    message_id: &[u8; 32],
+) -> [u8; 32] {
+    let mut input = Vec::with_capacity(100);
+    input.extend_from_slice(&domain_hash(origin, merkle_tree_hook));
+    input.extend_from_slice(root);
+    input.extend_from_slice(&index.to_be_bytes());
+    input.extend_from_slice(message_id);
+    hash_message(keccak256(input)).0
+}
+
+/// Root of the merkle tree holding `leaf` at `index`, given its merkle `proof`.
+pub fn branch_root(leaf: &[u8; 32], proof: &[[u8; 32]; TREE_DEPTH], index: u32) -> [u8; 32] {
+    let mut current = *leaf;
+    for (i, next) in proof.iter().enumerate() {
+        let mut input = [0u8; 64];
+        if (index >> i) & 1 == 1 {
+            input[..32].copy_from_slice(next);
+            input[32..].copy_from_slice(&current);
+        } else {
+            input[..32].copy_from_slice(&current);
+            input[32..].copy_from_slice(next);
+        }
+        current = keccak256(input);
+    }
+    current
+}
+
+/// Root of an empty subtree of each height.
+pub(crate) fn zero_hashes() -> [[u8; 32]; TREE_DEPTH] {
+    let mut zeros = [[0u8; 32]; TREE_DEPTH];
+    let mut zero = [0u8; 32];
+    for node in zeros.iter_mut() {
+        *node = zero;
+        zero = keccak256([zero, zero].concat());
+    }
+    zeros
+}
+
+/// Append-only merkle tree of the dispatched message ids, mirroring the merkle tree hook: only
+/// the left branch of the next insertion is kept.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IncrementalMerkle {
+    branch: [[u8; 32]; TREE_DEPTH],
+    count: u32,
+}
+
+impl Default for IncrementalMerkle {
+    fn default() -> Self {
+        Self {
+            branch: [[0u8; 32]; TREE_DEPTH],
+            count: 0,
+        }
+    }
+}
+
+impl IncrementalMerkle {
+    /// Number of leaves inserted.
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    /// Inserts `leaf` at index `count`.
+    pub fn ingest(&mut self, leaf: [u8; 32]) {
+        self.count += 1;
+        let mut size = self.count;
+        let mut node = leaf;
+        for branch in self.branch.iter_mut() {
+            if size & 1 == 1 {
+                *branch = node;
+                return;
+            }
+            node = keccak256([*branch, node].concat());
+            size /= 2;
+        }
+    }
+
+    pub fn root(&self) -> [u8; 32] {
+        let zeros = zero_hashes();
+        let mut node = [0u8; 32];
+        for (i, (branch, zero)) in self.branch.iter().zip(zeros).enumerate() {
+            node = if (self.count >> i) & 1 == 1 {
+                keccak256([*branch, node].concat())
+            } else {
+                keccak256([node, zero].concat())
+            };
+        }
+        node
+    }
+}
+
+/// Storage location a validator announces for its checkpoints, on the mailbox of its origin.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Announcement {
+    pub validator: Address,
+    pub mailbox_address: [u8; 32],
+    pub mailbox_domain: u32,
+    /// Location the signed checkpoints are published to, e.g. `file:///checkpoints` or
+    /// `s3://bucket/region/folder`.
+    pub storage_location: String,
+}
+
+impl Announcement {
+    /// Digest signed by the validator, see [`announcement_digest`].
+    pub fn digest(&self) -> [u8; 32] {
+        announcement_digest(
+            self.mailbox_domain,
+            &self.mailbox_address,
+            &self.storage_location,
+        )
+    }
+}
+
+/// Hash binding the announcements to the mailbox of their origin chain.
+pub fn announcement_domain_hash(mailbox_domain: u32, mailbox_address: &[u8; 32]) -> [u8; 32] {
+    let mut input = Vec::with_capacity(58);
+    input.extend_from_slice(&mailbox_domain.to_be_bytes());
+    input.extend_from_slice(mailbox_address);
+    input.extend_from_slice(b"HYPERLANE_ANNOUNCEMENT");
+    keccak256(input)
+}
+
+/// Digest signed by a validator announcing `storage_location`, `eth_sign` prefix included.
+///
+/// `validator_announce` hashes the location from the felts it is stored in, each of them
+/// trimmed of its leading zero bytes, the same bytes as the location string.
+pub fn announcement_digest(
+    mailbox_domain: u32,
+    mailbox_address: &[u8; 32],
+    storage_location: &str,
+) -> [u8; 32] {
+    let mut input = announcement_domain_hash(mailbox_domain, mailbox_address).to_vec();
+    input.extend_from_slice(storage_location.as_bytes());
+    hash_message(keccak256(input)).0
+}
+
+/// Announcement signed by its validator, `r || s || v` signature.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SignedAnnouncement {
+    pub value: Announcement,
+    pub signature: Vec<u8>,
+}
@@ -0,0 +1,113 @@
+use ethers::types::{Address, Bytes, Signature, H256};
+
+use crate::checkpoint::{
+    Announcement, Checkpoint, CheckpointWithMessageId, SignedAnnouncement, SignedCheckpoint,
+};
+
+/// Checkpoint in the JSON layout of the upstream Hyperlane agents.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct JsonCheckpoint {
+    merkle_tree_hook_address: H256,
+    mailbox_domain: u32,
+    root: H256,
+    index: u32,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct JsonCheckpointWithMessageId {
+    checkpoint: JsonCheckpoint,
+    message_id: H256,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct JsonAnnouncement {
+    validator: Address,
+    mailbox_address: H256,
+    mailbox_domain: u32,
+    storage_location: String,
+}
+
+/// Value signed by a validator, in the JSON layout of the upstream Hyperlane agents.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct JsonSigned<T> {
+    value: T,
+    signature: Signature,
+    #[serde(default)]
+    serialized_signature: Option<Bytes>,
+}
+
+impl<T> JsonSigned<T> {
+    fn new(value: T, signature: &[u8]) -> eyre::Result<Self> {
+        Ok(Self {
+            value,
+            signature: Signature::try_from(signature)
+                .map_err(|e| eyre::eyre!("invalid signature: {e}"))?,
+            serialized_signature: Some(signature.to_vec().into()),
+        })
+    }
+}
+
+pub fn checkpoint_to_json(signed: &SignedCheckpoint) -> eyre::Result<String> {
+    let checkpoint = &signed.value.checkpoint;
+    let value = JsonCheckpointWithMessageId {
+        checkpoint: JsonCheckpoint {
+            merkle_tree_hook_address: H256(checkpoint.merkle_tree_hook),
+            mailbox_domain: checkpoint.origin,
+            root: H256(checkpoint.root),
+            index: checkpoint.index,
+        },
+        message_id: H256(signed.value.message_id),
+    };
+
+    Ok(serde_json::to_string_pretty(&JsonSigned::new(
+        value,
+        &signed.signature,
+    )?)?)
+}
+
+pub fn checkpoint_from_json(json: &str) -> eyre::Result<SignedCheckpoint> {
+    let signed: JsonSigned<JsonCheckpointWithMessageId> = serde_json::from_str(json)?;
+    let checkpoint = signed.value.checkpoint;
+
+    Ok(SignedCheckpoint {
+        value: CheckpointWithMessageId {
+            checkpoint: Checkpoint {
+                merkle_tree_hook: checkpoint.merkle_tree_hook_address.0,
+                origin: checkpoint.mailbox_domain,
+                root: checkpoint.root.0,
+                index: checkpoint.index,
+            },
+            message_id: signed.value.message_id.0,
+        },
+        signature: signed.signature.to_vec(),
+    })
+}
+
+pub fn announcement_to_json(signed: &SignedAnnouncement) -> eyre::Result<String> {
+    let announcement = &signed.value;
+    let value = JsonAnnouncement {
+        validator: announcement.validator,
+        mailbox_address: H256(announcement.mailbox_address),
+        mailbox_domain: announcement.mailbox_domain,
+        storage_location: announcement.storage_location.clone(),
+    };
+
+    Ok(serde_json::to_string_pretty(&JsonSigned::new(
+        value,
+        &signed.signature,
+    )?)?)
+}
+
+pub fn announcement_from_json(json: &str) -> eyre::Result<SignedAnnouncement> {
+    let signed: JsonSigned<JsonAnnouncement> = serde_json::from_str(json)?;
+
+    Ok(SignedAnnouncement {
+        value: Announcement {
+            validator: signed.value.validator,
+            mailbox_address: signed.value.mailbox_address.0,
+            mailbox_domain: signed.value.mailbox_domain,
+            storage_location: signed.value.storage_location,
+        },
+        signature: signed.signature.to_vec(),
+    })
+}
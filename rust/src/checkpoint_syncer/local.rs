@@ -0,0 +1,143 @@
+use std::path::{Path, PathBuf};
+
+use crate::{
+    checkpoint::{SignedAnnouncement, SignedCheckpoint},
+    utils::BoxFuture,
+};
+
+use super::{
+    json::{
+        announcement_from_json, announcement_to_json, checkpoint_from_json, checkpoint_to_json,
+    },
+    CheckpointSyncer,
+};
+
+/// Checkpoints stored in a local directory, in the layout of the upstream Hyperlane agents:
+/// `{index}_with_id.json` for each signed checkpoint, `index.json` for the latest index and
+/// `announcement.json` for the signed announcement.
+pub struct LocalCheckpointSyncer {
+    path: PathBuf,
+}
+
+impl LocalCheckpointSyncer {
+    /// Stores to the directory at `path`, creating it when missing.
+    pub fn new(path: impl Into<PathBuf>) -> eyre::Result<Self> {
+        let path = path.into();
+        std::fs::create_dir_all(&path)
+            .map_err(|e| eyre::eyre!("failed to create {}: {e}", path.display()))?;
+
+        Ok(Self { path })
+    }
+
+    /// Storage announced as `file://{path}`.
+    pub fn from_location(location: &str) -> eyre::Result<Self> {
+        let path = location
+            .strip_prefix("file://")
+            .ok_or(eyre::eyre!("{location} is not a local storage location"))?;
+
+        Ok(Self {
+            path: PathBuf::from(path),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn checkpoint_path(&self, index: u32) -> PathBuf {
+        self.path.join(format!("{index}_with_id.json"))
+    }
+
+    fn index_path(&self) -> PathBuf {
+        self.path.join("index.json")
+    }
+
+    fn announcement_path(&self) -> PathBuf {
+        self.path.join("announcement.json")
+    }
+
+    /// Signed announcement written next to the checkpoints, unset when there is none.
+    pub fn announcement(&self) -> eyre::Result<Option<SignedAnnouncement>> {
+        read_optional(&self.announcement_path())?
+            .map(|content| announcement_from_json(&content))
+            .transpose()
+    }
+
+    fn latest(&self) -> eyre::Result<Option<u32>> {
+        Ok(match read_optional(&self.index_path())? {
+            Some(content) => Some(serde_json::from_str(&content)?),
+            None => None,
+        })
+    }
+
+    fn checkpoint(&self, index: u32) -> eyre::Result<Option<SignedCheckpoint>> {
+        read_optional(&self.checkpoint_path(index))?
+            .map(|content| checkpoint_from_json(&content))
+            .transpose()
+    }
+
+    fn write(&self, signed: &SignedCheckpoint) -> eyre::Result<()> {
+        let index = signed.value.checkpoint.index;
+        write_atomic(&self.checkpoint_path(index), &checkpoint_to_json(signed)?)?;
+
+        // the latest index only moves forward, relayers wait for the checkpoints below it
+        if self.latest()?.is_none_or(|latest| latest < index) {
+            write_atomic(&self.index_path(), &index.to_string())?;
+        }
+
+        Ok(())
+    }
+}
+
+impl CheckpointSyncer for LocalCheckpointSyncer {
+    fn latest_index(&self) -> BoxFuture<'_, eyre::Result<Option<u32>>> {
+        Box::pin(async move { self.latest() })
+    }
+
+    fn fetch_checkpoint(
+        &self,
+        index: u32,
+    ) -> BoxFuture<'_, eyre::Result<Option<SignedCheckpoint>>> {
+        Box::pin(async move { self.checkpoint(index) })
+    }
+
+    fn write_checkpoint<'a>(
+        &'a self,
+        checkpoint: &'a SignedCheckpoint,
+    ) -> BoxFuture<'a, eyre::Result<()>> {
+        Box::pin(async move { self.write(checkpoint) })
+    }
+
+    fn write_announcement<'a>(
+        &'a self,
+        announcement: &'a SignedAnnouncement,
+    ) -> BoxFuture<'a, eyre::Result<()>> {
+        Box::pin(async move {
+            write_atomic(
+                &self.announcement_path(),
+                &announcement_to_json(announcement)?,
+            )
+        })
+    }
+
+    fn announcement_location(&self) -> String {
+        let path = std::path::absolute(&self.path).unwrap_or_else(|_| self.path.clone());
+        format!("file://{}", path.display())
+    }
+}
+
+fn read_optional(path: &Path) -> eyre::Result<Option<String>> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => Ok(Some(content)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(eyre::eyre!("failed to read {}: {e}", path.display())),
+    }
+}
+
+/// Writes `content` to `path` through a temporary file, so that readers never see a partial file.
+fn write_atomic(path: &Path, content: &str) -> eyre::Result<()> {
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, content)
+        .and_then(|()| std::fs::rename(&tmp, path))
+        .map_err(|e| eyre::eyre!("failed to write {}: {e}", path.display()))
+}
@@ -0,0 +1,99 @@
+mod json;
+mod local;
+mod s3;
+
+use std::sync::Arc;
+
+use starknet::core::types::FieldElement;
+
+use crate::{
+    checkpoint::{SignedAnnouncement, SignedCheckpoint},
+    utils::BoxFuture,
+};
+
+pub use self::s3::*;
+pub use local::*;
+
+/// Storage of the checkpoints signed by a validator, written by the validator and read by the
+/// relayers from the location it announced in `validator_announce`.
+///
+/// Backends follow the layout of the upstream Hyperlane agents, so that their validators and
+/// relayers interoperate with the Starknet ones.
+pub trait CheckpointSyncer: Send + Sync {
+    /// Highest checkpoint index written, unset before the first one.
+    fn latest_index(&self) -> BoxFuture<'_, eyre::Result<Option<u32>>>;
+
+    /// Checkpoint signed at `index`, unset when it was not written.
+    fn fetch_checkpoint(&self, index: u32)
+        -> BoxFuture<'_, eyre::Result<Option<SignedCheckpoint>>>;
+
+    /// Writes a signed checkpoint, moving the latest index forward when it is above it.
+    fn write_checkpoint<'a>(
+        &'a self,
+        checkpoint: &'a SignedCheckpoint,
+    ) -> BoxFuture<'a, eyre::Result<()>>;
+
+    /// Writes the signed announcement of the validator, next to its checkpoints.
+    fn write_announcement<'a>(
+        &'a self,
+        announcement: &'a SignedAnnouncement,
+    ) -> BoxFuture<'a, eyre::Result<()>>;
+
+    /// Storage location announced for this storage, e.g. `file:///checkpoints`.
+    fn announcement_location(&self) -> String;
+}
+
+/// Opens the storage announced at `location`: `file://{path}` or
+/// `s3://{bucket}/{region}[/{folder}]`, S3 requests being sent to `s3_endpoint` when set.
+pub fn syncer_from_location(
+    location: &str,
+    s3_endpoint: Option<&str>,
+) -> eyre::Result<Arc<dyn CheckpointSyncer>> {
+    if location.starts_with("file://") {
+        Ok(Arc::new(LocalCheckpointSyncer::from_location(location)?))
+    } else if location.starts_with("s3://") {
+        Ok(Arc::new(S3CheckpointSyncer::from_location(
+            location,
+            s3_endpoint,
+        )?))
+    } else {
+        eyre::bail!("unsupported storage location {location}")
+    }
+}
+
+/// Opens the storage a validator writes its checkpoints to, given on its command line: a local
+/// directory, created when missing, or an `s3://{bucket}/{region}[/{folder}]` location.
+pub fn syncer_from_storage(
+    storage: &str,
+    s3_endpoint: Option<&str>,
+) -> eyre::Result<Arc<dyn CheckpointSyncer>> {
+    if storage.starts_with("s3://") {
+        syncer_from_location(storage, s3_endpoint)
+    } else {
+        Ok(Arc::new(LocalCheckpointSyncer::new(storage)?))
+    }
+}
+
+/// Encodes a storage location the way `validator_announce` stores it, in felts of 31 bytes.
+pub fn storage_location_to_felts(location: &str) -> Vec<FieldElement> {
+    location
+        .as_bytes()
+        .chunks(31)
+        .map(|chunk| FieldElement::from_byte_slice_be(chunk).expect("31 bytes fit in a felt"))
+        .collect()
+}
+
+/// Decodes a storage location stored by `validator_announce`, see
+/// [`storage_location_to_felts`].
+pub fn storage_location_from_felts(felts: &[FieldElement]) -> String {
+    let bytes: Vec<u8> = felts
+        .iter()
+        .flat_map(|felt| {
+            let bytes = felt.to_bytes_be();
+            let start = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+            bytes[start..].to_vec()
+        })
+        .collect();
+
+    String::from_utf8_lossy(&bytes).into_owned()
+}
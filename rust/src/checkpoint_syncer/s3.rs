@@ -0,0 +1,168 @@
+use s3::{creds::Credentials, error::S3Error, Bucket, Region};
+
+use crate::{
+    checkpoint::{SignedAnnouncement, SignedCheckpoint},
+    utils::BoxFuture,
+};
+
+use super::{
+    json::{announcement_to_json, checkpoint_from_json, checkpoint_to_json},
+    CheckpointSyncer,
+};
+
+const LATEST_INDEX_KEY: &str = "checkpoint_latest_index.json";
+const ANNOUNCEMENT_KEY: &str = "announcement.json";
+
+/// Checkpoints stored in an S3 bucket, in the layout of the upstream Hyperlane agents:
+/// `checkpoint_{index}_with_id.json` for each signed checkpoint,
+/// `checkpoint_latest_index.json` for the latest index and `announcement.json` for the signed
+/// announcement, under an optional folder.
+///
+/// Requests are signed with the AWS credentials of the environment when there are some, and sent
+/// anonymously otherwise, enough to read the public buckets of the validators.
+pub struct S3CheckpointSyncer {
+    bucket: Bucket,
+    region: String,
+    folder: Option<String>,
+}
+
+impl S3CheckpointSyncer {
+    /// Stores to `bucket` in `region`, under `folder` when set.
+    ///
+    /// A custom `endpoint`, e.g. a local S3 compatible server, is addressed in path style.
+    pub fn new(
+        bucket: &str,
+        region: &str,
+        folder: Option<String>,
+        endpoint: Option<&str>,
+    ) -> eyre::Result<Self> {
+        let credentials = Credentials::from_env().or_else(|_| Credentials::anonymous())?;
+        let bucket = match endpoint {
+            Some(endpoint) => Bucket::new(
+                bucket,
+                Region::Custom {
+                    region: region.to_string(),
+                    endpoint: endpoint.trim_end_matches('/').to_string(),
+                },
+                credentials,
+            )?
+            .with_path_style(),
+            None => Bucket::new(bucket, region.parse()?, credentials)?,
+        };
+
+        Ok(Self {
+            bucket,
+            region: region.to_string(),
+            folder: folder.filter(|folder| !folder.is_empty()),
+        })
+    }
+
+    /// Storage announced as `s3://{bucket}/{region}` or `s3://{bucket}/{region}/{folder}`.
+    pub fn from_location(location: &str, endpoint: Option<&str>) -> eyre::Result<Self> {
+        let path = location
+            .strip_prefix("s3://")
+            .ok_or(eyre::eyre!("{location} is not an S3 storage location"))?;
+        let mut parts = path.splitn(3, '/');
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(bucket), Some(region), folder) if !bucket.is_empty() && !region.is_empty() => {
+                Self::new(bucket, region, folder.map(str::to_string), endpoint)
+            }
+            _ => eyre::bail!("invalid S3 storage location {location}, expected s3://bucket/region"),
+        }
+    }
+
+    fn key(&self, name: &str) -> String {
+        match &self.folder {
+            Some(folder) => format!("{folder}/{name}"),
+            None => name.to_string(),
+        }
+    }
+
+    fn checkpoint_key(&self, index: u32) -> String {
+        self.key(&format!("checkpoint_{index}_with_id.json"))
+    }
+
+    async fn get(&self, key: &str) -> eyre::Result<Option<String>> {
+        match self.bucket.get_object(key).await {
+            Ok(response) => Ok(Some(String::from_utf8(response.bytes().to_vec())?)),
+            Err(S3Error::HttpFailWithBody(404, _)) => Ok(None),
+            Err(e) => Err(eyre::eyre!("failed to get {key} from S3: {e}")),
+        }
+    }
+
+    async fn put(&self, key: &str, content: &str) -> eyre::Result<()> {
+        self.bucket
+            .put_object_with_content_type(key, content.as_bytes(), "application/json")
+            .await
+            .map_err(|e| eyre::eyre!("failed to put {key} to S3: {e}"))?;
+
+        Ok(())
+    }
+
+    async fn latest(&self) -> eyre::Result<Option<u32>> {
+        Ok(match self.get(&self.key(LATEST_INDEX_KEY)).await? {
+            Some(content) => Some(serde_json::from_str(&content)?),
+            None => None,
+        })
+    }
+}
+
+impl CheckpointSyncer for S3CheckpointSyncer {
+    fn latest_index(&self) -> BoxFuture<'_, eyre::Result<Option<u32>>> {
+        Box::pin(self.latest())
+    }
+
+    fn fetch_checkpoint(
+        &self,
+        index: u32,
+    ) -> BoxFuture<'_, eyre::Result<Option<SignedCheckpoint>>> {
+        Box::pin(async move {
+            self.get(&self.checkpoint_key(index))
+                .await?
+                .map(|content| checkpoint_from_json(&content))
+                .transpose()
+        })
+    }
+
+    fn write_checkpoint<'a>(
+        &'a self,
+        checkpoint: &'a SignedCheckpoint,
+    ) -> BoxFuture<'a, eyre::Result<()>> {
+        Box::pin(async move {
+            let index = checkpoint.value.checkpoint.index;
+            self.put(
+                &self.checkpoint_key(index),
+                &checkpoint_to_json(checkpoint)?,
+            )
+            .await?;
+
+            // the latest index only moves forward, relayers wait for the checkpoints below it
+            if self.latest().await?.is_none_or(|latest| latest < index) {
+                self.put(&self.key(LATEST_INDEX_KEY), &index.to_string())
+                    .await?;
+            }
+
+            Ok(())
+        })
+    }
+
+    fn write_announcement<'a>(
+        &'a self,
+        announcement: &'a SignedAnnouncement,
+    ) -> BoxFuture<'a, eyre::Result<()>> {
+        Box::pin(async move {
+            self.put(
+                &self.key(ANNOUNCEMENT_KEY),
+                &announcement_to_json(announcement)?,
+            )
+            .await
+        })
+    }
+
+    fn announcement_location(&self) -> String {
+        match &self.folder {
+            Some(folder) => format!("s3://{}/{}/{folder}", self.bucket.name(), self.region),
+            None => format!("s3://{}/{}", self.bucket.name(), self.region),
+        }
+    }
+}
@@ -0,0 +1,347 @@
+use std::{collections::BTreeMap, path::Path, sync::Arc};
+
+use ethers::{
+    middleware::SignerMiddleware,
+    providers::{Http, Middleware, Provider as EvmProvider},
+    signers::Signer as _,
+    types::Address,
+};
+use starknet::{
+    core::types::{BlockId, BlockTag, FieldElement},
+    providers::{jsonrpc::HttpTransport, AnyProvider, JsonRpcClient, Provider, Url},
+};
+
+use crate::{
+    contracts::strk::{build_single_owner_account, StarknetAccount},
+    process::FeeSettings,
+    signer::{Curve, EvmSigner, LocalSigner, RemoteSigner, Signer, StarknetSigner},
+};
+
+const DEFAULT_PRIVATE_KEY_VAR: &str = "STARKNET_PRIVATE_KEY";
+const DEFAULT_KEYSTORE_PASSWORD_VAR: &str = "STARKNET_KEYSTORE_PASSWORD";
+
+/// EVM provider signing transactions with the configured account.
+pub type EvmClient = SignerMiddleware<EvmProvider<Http>, EvmSigner>;
+
+/// Chains the tooling operates on, keyed by name.
+///
+/// ```json
+/// {
+///   "chains": {
+///     "katana": {
+///       "rpc_url": "http://localhost:5050",
+///       "domain": 2,
+///       "addresses": {
+///         "mailbox": "0x064bb5e29a7e9fc67dedabb6d1c385050feef028563078daa9025dd8b218e596"
+///       },
+///       "account": {
+///         "address": "0xb3ff441a68610b30fd5e2abbf3a1548eb6ba6f3559f2862bf2dc757e5828ca",
+///         "signer": { "type": "keystore", "path": "keystore.json" }
+///       }
+///     }
+///   }
+/// }
+/// ```
+#[derive(Debug, serde::Deserialize)]
+pub struct Config {
+    pub chains: BTreeMap<String, ChainConfig>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct ChainConfig {
+    #[serde(default)]
+    pub protocol: Protocol,
+    pub rpc_url: String,
+    /// Hyperlane domain of the chain.
+    pub domain: u32,
+    /// Deployed contracts, keyed like the deployment files written by the deploy scripts
+    /// (`mailbox`, `validator_announce`, `merkle_tree_hook`, ...).
+    #[serde(default)]
+    pub addresses: BTreeMap<String, FieldElement>,
+    /// Account used to send transactions. Read-only commands do not need it.
+    #[serde(default)]
+    pub account: Option<AccountConfig>,
+    /// Block the relayer starts indexing dispatched messages from, the latest block by default.
+    /// Validators, and relayers for the merkle proofs of the messages, index the merkle tree hook
+    /// from it, the genesis block by default.
+    #[serde(default)]
+    pub start_block: Option<u64>,
+    /// Fee policy of the `process` transactions.
+    #[serde(default)]
+    pub fee: FeeSettings,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Protocol {
+    #[default]
+    Starknet,
+    /// EVM chain, addresses being configured as felts holding the 20 bytes address.
+    Ethereum,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct AccountConfig {
+    /// Account contract address. Unused on EVM chains, where it derives from the signer.
+    #[serde(default)]
+    pub address: FieldElement,
+    /// Whether the account is a legacy (Cairo 0) account.
+    #[serde(default)]
+    pub legacy: bool,
+    #[serde(default)]
+    pub signer: SignerConfig,
+}
+
+/// Source of the private key signing the account transactions, see [`Signer`].
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SignerConfig {
+    /// Hex encoded private key read from an environment variable.
+    Env {
+        #[serde(default = "default_private_key_var")]
+        var: String,
+    },
+    /// Encrypted JSON keystore, unlocked with a password read from an environment variable.
+    Keystore {
+        path: String,
+        #[serde(default = "default_keystore_password_var")]
+        password_var: String,
+    },
+    /// Key held by a remote signing service, see [`RemoteSigner`].
+    Remote {
+        url: String,
+        key_id: String,
+        /// Environment variable holding the bearer token of the requests, if any.
+        #[serde(default)]
+        token_var: Option<String>,
+    },
+}
+
+impl Default for SignerConfig {
+    fn default() -> Self {
+        Self::Env {
+            var: default_private_key_var(),
+        }
+    }
+}
+
+fn default_private_key_var() -> String {
+    DEFAULT_PRIVATE_KEY_VAR.to_string()
+}
+
+fn default_keystore_password_var() -> String {
+    DEFAULT_KEYSTORE_PASSWORD_VAR.to_string()
+}
+
+impl Config {
+    /// Reads the configuration from a JSON file.
+    pub fn load(path: impl AsRef<Path>) -> eyre::Result<Self> {
+        let path = path.as_ref();
+        let file = std::fs::File::open(path)
+            .map_err(|e| eyre::eyre!("failed to open config {}: {e}", path.display()))?;
+
+        Ok(serde_json::from_reader(file)?)
+    }
+
+    /// Returns the chain named `name`. When no name is given, the configuration must hold a
+    /// single chain.
+    pub fn chain(&self, name: Option<&str>) -> eyre::Result<&ChainConfig> {
+        match name {
+            Some(name) => self
+                .chains
+                .get(name)
+                .ok_or(eyre::eyre!("chain {name} not found in config")),
+            None if self.chains.len() == 1 => Ok(self.chains.values().next().unwrap()),
+            None => Err(eyre::eyre!(
+                "config holds {} chains, select one with --chain",
+                self.chains.len()
+            )),
+        }
+    }
+
+    /// Returns the chain whose hyperlane domain is `domain`.
+    pub fn chain_by_domain(&self, domain: u32) -> eyre::Result<&ChainConfig> {
+        self.chains
+            .values()
+            .find(|c| c.domain == domain)
+            .ok_or(eyre::eyre!("no chain with domain {domain} in config"))
+    }
+}
+
+impl ChainConfig {
+    pub fn provider(&self) -> eyre::Result<AnyProvider> {
+        self.expect_protocol(Protocol::Starknet)?;
+        let url = Url::parse(&self.rpc_url)?;
+
+        Ok(AnyProvider::JsonRpcHttp(JsonRpcClient::new(
+            HttpTransport::new(url),
+        )))
+    }
+
+    /// Returns the configured address of the `name` contract.
+    pub fn address(&self, name: &str) -> eyre::Result<FieldElement> {
+        self.addresses.get(name).copied().ok_or(eyre::eyre!(
+            "no {name} address configured for domain {}",
+            self.domain
+        ))
+    }
+
+    pub fn mailbox(&self) -> eyre::Result<FieldElement> {
+        self.address("mailbox")
+    }
+
+    pub fn evm_provider(&self) -> eyre::Result<EvmProvider<Http>> {
+        self.expect_protocol(Protocol::Ethereum)?;
+
+        Ok(EvmProvider::<Http>::try_from(self.rpc_url.as_str())?)
+    }
+
+    /// Returns the configured address of the `name` contract on an EVM chain.
+    pub fn evm_address(&self, name: &str) -> eyre::Result<Address> {
+        let address = self.address(name)?.to_bytes_be();
+        if address[..12].iter().any(|b| *b != 0) {
+            eyre::bail!("{name} address is not a valid EVM address");
+        }
+
+        Ok(Address::from_slice(&address[12..]))
+    }
+
+    fn expect_protocol(&self, protocol: Protocol) -> eyre::Result<()> {
+        if self.protocol != protocol {
+            eyre::bail!(
+                "domain {} is a {:?} chain, expected {:?}",
+                self.domain,
+                self.protocol,
+                protocol
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Builds the configured account, fetching the chain id from the node.
+    pub async fn account(&self) -> eyre::Result<StarknetAccount> {
+        let config = self.account.as_ref().ok_or(eyre::eyre!(
+            "no account configured for domain {}",
+            self.domain
+        ))?;
+
+        if config.address == FieldElement::ZERO {
+            eyre::bail!("no account address configured for domain {}", self.domain);
+        }
+
+        let signer = StarknetSigner::new(config.signer.signer(Curve::Stark)?)?;
+        let chain_id = self.provider()?.chain_id().await?;
+
+        let mut account = build_single_owner_account(
+            &Url::parse(&self.rpc_url)?,
+            signer,
+            &config.address,
+            config.legacy,
+            chain_id,
+        );
+        account.set_block_id(BlockId::Tag(BlockTag::Pending));
+
+        Ok(account)
+    }
+
+    /// Builds the EVM provider signing with the configured account, fetching the chain id from
+    /// the node.
+    pub async fn evm_client(&self) -> eyre::Result<EvmClient> {
+        let config = self.account.as_ref().ok_or(eyre::eyre!(
+            "no account configured for domain {}",
+            self.domain
+        ))?;
+
+        let provider = self.evm_provider()?;
+        let chain_id = provider.get_chainid().await?;
+        let signer = EvmSigner::new(config.signer.signer(Curve::Secp256k1)?)
+            .await?
+            .with_chain_id(chain_id.as_u64());
+
+        Ok(SignerMiddleware::new(provider, signer))
+    }
+}
+
+impl SignerConfig {
+    /// Builds the signer of the configured key, a key of `curve`.
+    pub fn signer(&self, curve: Curve) -> eyre::Result<Arc<dyn Signer>> {
+        let signer: Arc<dyn Signer> = match self {
+            SignerConfig::Env { var } => Arc::new(LocalSigner::from_env(curve, var)?),
+            SignerConfig::Keystore { path, password_var } => {
+                let password = std::env::var(password_var)
+                    .map_err(|_| eyre::eyre!("environment variable {password_var} is not set"))?;
+
+                Arc::new(LocalSigner::from_keystore(curve, path, &password)?)
+            }
+            SignerConfig::Remote {
+                url,
+                key_id,
+                token_var,
+            } => {
+                let mut signer = RemoteSigner::new(url, key_id, curve);
+                if let Some(var) = token_var {
+                    let token = std::env::var(var)
+                        .map_err(|_| eyre::eyre!("environment variable {var} is not set"))?;
+                    signer = signer.with_token(token);
+                }
+
+                Arc::new(signer)
+            }
+        };
+
+        Ok(signer)
+    }
+}
+
+/// Command line flags selecting the key of a validator: a hex key read from the environment by
+/// default, an encrypted keystore or a remote signer.
+#[derive(Debug, Clone, clap::Args)]
+pub struct ValidatorSignerArgs {
+    /// Environment variable holding the hex encoded secp256k1 key of the validator.
+    #[arg(long, default_value = "HYPERLANE_VALIDATOR_KEY")]
+    pub key_var: String,
+
+    /// Encrypted JSON keystore holding the key of the validator.
+    #[arg(long, conflicts_with = "remote_signer")]
+    pub keystore: Option<String>,
+
+    /// Environment variable holding the password of the keystore.
+    #[arg(long, default_value = "HYPERLANE_VALIDATOR_KEYSTORE_PASSWORD")]
+    pub keystore_password_var: String,
+
+    /// Remote signing service holding the key of the validator.
+    #[arg(long, requires = "remote_key_id")]
+    pub remote_signer: Option<String>,
+
+    /// Id of the key of the validator in the remote signing service.
+    #[arg(long)]
+    pub remote_key_id: Option<String>,
+
+    /// Environment variable holding the bearer token of the remote signing service.
+    #[arg(long)]
+    pub remote_token_var: Option<String>,
+}
+
+impl ValidatorSignerArgs {
+    /// Signer configuration of the selected key, a remote signer taking precedence.
+    pub fn config(self) -> SignerConfig {
+        match (self.remote_signer, self.keystore) {
+            (Some(url), _) => SignerConfig::Remote {
+                url,
+                key_id: self.remote_key_id.unwrap_or_default(),
+                token_var: self.remote_token_var,
+            },
+            (None, Some(path)) => SignerConfig::Keystore {
+                path,
+                password_var: self.keystore_password_var,
+            },
+            (None, None) => SignerConfig::Env { var: self.key_var },
+        }
+    }
+
+    /// Builds the [`EvmSigner`] of the selected secp256k1 key.
+    pub async fn evm_signer(self) -> eyre::Result<EvmSigner> {
+        EvmSigner::new(self.config().signer(Curve::Secp256k1)?).await
+    }
+}
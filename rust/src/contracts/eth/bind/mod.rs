@@ -1,10 +1,18 @@
 #[allow(clippy::all)]
+pub mod aggregation_ism;
+#[allow(clippy::all)]
 pub mod fast_hyp_erc20;
 #[allow(clippy::all)]
 pub mod fast_hyp_erc20_collateral;
 #[allow(clippy::all)]
+pub mod ism;
+#[allow(clippy::all)]
 pub mod mailbox;
 #[allow(clippy::all)]
+pub mod multisig_ism;
+#[allow(clippy::all)]
+pub mod routing_ism;
+#[allow(clippy::all)]
 pub mod test_merkle_tree_hook;
 #[allow(clippy::all)]
 pub mod test_mock_ism;
@@ -0,0 +1,3 @@
+pub mod bind;
+
+pub use bind::*;
@@ -0,0 +1,2 @@
+pub mod eth;
+pub mod strk;
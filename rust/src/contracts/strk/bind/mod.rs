@@ -0,0 +1,34 @@
+#[allow(clippy::all)]
+pub mod aggregation;
+#[allow(clippy::all)]
+pub mod hook;
+#[allow(clippy::all)]
+pub mod hyp_xerc20;
+#[allow(clippy::all)]
+pub mod hyp_xerc20_lockbox;
+#[allow(clippy::all)]
+pub mod ism;
+#[allow(clippy::all)]
+pub mod mailbox;
+#[allow(clippy::all)]
+pub mod merkle_tree_hook;
+#[allow(clippy::all)]
+pub mod multisig_ism;
+#[allow(clippy::all)]
+pub mod pausable_ism;
+#[allow(clippy::all)]
+pub mod protocol_fee;
+#[allow(clippy::all)]
+pub mod routing;
+#[allow(clippy::all)]
+pub mod routing_hook;
+#[allow(clippy::all)]
+pub mod test_erc20;
+#[allow(clippy::all)]
+pub mod validator_announce;
+#[allow(clippy::all)]
+pub mod xerc20_factory;
+#[allow(clippy::all)]
+pub mod xerc20_lockbox;
+#[allow(clippy::all)]
+pub mod xerc20_token;
@@ -0,0 +1,6 @@
+pub mod bind;
+mod utils;
+pub mod xerc20;
+
+pub use bind::*;
+pub use utils::*;
@@ -0,0 +1,286 @@
+use std::sync::Arc;
+
+use starknet::{
+    accounts::{Account, ConnectedAccount, SingleOwnerAccount},
+    contract::ContractFactory,
+    core::types::{
+        contract::{CompiledClass, SierraClass},
+        BlockId, BlockTag, Event, FieldElement, FlattenedSierraClass, InvokeTransactionResult,
+        MaybePendingTransactionReceipt, PendingTransactionReceipt, StarknetError,
+        TransactionReceipt,
+    },
+    macros::felt,
+    providers::{jsonrpc::HttpTransport, AnyProvider, JsonRpcClient, Provider, ProviderError, Url},
+    signers::SigningKey,
+};
+
+use super::bind::mailbox::Bytes;
+use crate::{
+    signer::{LocalSigner, StarknetSigner},
+    tx_watcher::TxWatcher,
+};
+
+pub type StarknetAccount = SingleOwnerAccount<AnyProvider, StarknetSigner>;
+
+/// Build artifacts prefix of the hyperlane core contracts (and the mocks built alongside them).
+pub const BUILD_PATH_PREFIX: &str = "../cairo/target/dev/contracts_";
+
+/// Build artifacts prefix of the warp route contracts.
+pub const TOKEN_BUILD_PATH_PREFIX: &str = "../cairo/target/dev/token_";
+
+/// Build artifacts prefix of the xERC20 package.
+pub const XERC20_BUILD_PATH_PREFIX: &str = "../xerc20/target/dev/xerc20_";
+
+/// Token the mailbox charges the dispatch fees in (ETH).
+pub const MAILBOX_FEE_TOKEN: FieldElement =
+    felt!("0x049d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7");
+
+const KATANA_RPC_URL: &str = "http://localhost:5050";
+
+const KATANA_PREFUNDED_ACCOUNTS: [(&str, &str); 3] = [
+    (
+        "0xb3ff441a68610b30fd5e2abbf3a1548eb6ba6f3559f2862bf2dc757e5828ca",
+        "0x2bbf4f9fd0bbb2e60b0316c1fe0b76cf7a4d0198bd493ced9b8df2a3a24d68a",
+    ),
+    (
+        "0xe29882a1fcba1e7e10cad46212257fea5c752a4f9b1b1ec683c503a2cf5c8a",
+        "0x14d6672dcb4b77ca36a887e9a11cd9d637d5012468175829e9c6e770c61642",
+    ),
+    (
+        "0x29873c310fbefde666dc32a1554fea6bb45eecc84f680f8a2b0a8fbb8cb89af",
+        "0xc5b2fcab997346f3ea1c00b002ecf6f382c5f9c9659a3894eb783c5320f912",
+    ),
+];
+
+const KATANA_CHAIN_ID: u64 = 82743958523457;
+
+/// Returns the events emitted by the transaction of the given receipt.
+pub fn receipt_events(receipt: &MaybePendingTransactionReceipt) -> &[Event] {
+    match receipt {
+        MaybePendingTransactionReceipt::Receipt(receipt) => match receipt {
+            TransactionReceipt::Invoke(receipt) => &receipt.events,
+            TransactionReceipt::L1Handler(receipt) => &receipt.events,
+            TransactionReceipt::Declare(receipt) => &receipt.events,
+            TransactionReceipt::Deploy(receipt) => &receipt.events,
+            TransactionReceipt::DeployAccount(receipt) => &receipt.events,
+        },
+        MaybePendingTransactionReceipt::PendingReceipt(receipt) => match receipt {
+            PendingTransactionReceipt::Invoke(receipt) => &receipt.events,
+            PendingTransactionReceipt::L1Handler(receipt) => &receipt.events,
+            PendingTransactionReceipt::Declare(receipt) => &receipt.events,
+            PendingTransactionReceipt::DeployAccount(receipt) => &receipt.events,
+        },
+    }
+}
+
+/// Returns a pre-funded account for a local katana chain.
+pub fn get_dev_account(index: u32) -> StarknetAccount {
+    let (address, private_key) = *KATANA_PREFUNDED_ACCOUNTS
+        .get(index as usize)
+        .expect("Invalid index");
+
+    let signer = StarknetSigner::new(Arc::new(LocalSigner::Stark(
+        SigningKey::from_secret_scalar(FieldElement::from_hex_be(private_key).unwrap()),
+    )))
+    .expect("stark key");
+
+    let mut account = build_single_owner_account(
+        &Url::parse(KATANA_RPC_URL).expect("Invalid rpc url"),
+        signer,
+        &FieldElement::from_hex_be(address).unwrap(),
+        false,
+        KATANA_CHAIN_ID.into(),
+    );
+
+    // `SingleOwnerAccount` defaults to checking nonce and estimating fees against the latest
+    // block. Optionally change the target block to pending with the following line:
+    account.set_block_id(BlockId::Tag(BlockTag::Pending));
+
+    account
+}
+
+/// Creates a single owner account for a given signer and account address.
+///
+/// # Arguments
+///
+/// * `rpc_url` - The rpc url of the chain.
+/// * `signer` - The signer of the account.
+/// * `account_address` - The address of the account.
+/// * `is_legacy` - Whether the account is legacy (Cairo 0) or not.
+/// * `chain_id` - The starknet chain id.
+pub fn build_single_owner_account(
+    rpc_url: &Url,
+    signer: StarknetSigner,
+    account_address: &FieldElement,
+    is_legacy: bool,
+    chain_id: FieldElement,
+) -> StarknetAccount {
+    let rpc_client =
+        AnyProvider::JsonRpcHttp(JsonRpcClient::new(HttpTransport::new(rpc_url.clone())));
+
+    let execution_encoding = if is_legacy {
+        starknet::accounts::ExecutionEncoding::Legacy
+    } else {
+        starknet::accounts::ExecutionEncoding::New
+    };
+
+    SingleOwnerAccount::new(
+        rpc_client,
+        signer,
+        *account_address,
+        chain_id,
+        execution_encoding,
+    )
+}
+
+/// Packs bytes into the 16 bytes words of an alexandria `Bytes`, padding the last word with zeros.
+/// See https://github.com/keep-starknet-strange/alexandria/blob/main/src/bytes/src/bytes.cairo#L16
+pub fn to_strk_message_bytes(bytes: &[u8]) -> Bytes {
+    let data = bytes
+        .chunks(16)
+        .map(|chunk| {
+            let mut word = [0u8; 16];
+            word[..chunk.len()].copy_from_slice(chunk);
+            u128::from_be_bytes(word)
+        })
+        .collect();
+
+    Bytes {
+        size: bytes.len() as u32,
+        data,
+    }
+}
+
+/// Unpacks an alexandria `Bytes`, dropping the padding of its last word.
+pub fn from_strk_message_bytes(bytes: &Bytes) -> Vec<u8> {
+    let mut output: Vec<u8> = bytes.data.iter().flat_map(|w| w.to_be_bytes()).collect();
+    output.truncate(bytes.size as usize);
+    output
+}
+
+/// Get the contract artifact from the build directory.
+/// # Arguments
+/// * `build_path_prefix` - The build directory joined with the scarb package prefix.
+/// * `contract_name` - The contract name.
+/// # Returns
+/// The contract artifact.
+fn contract_artifacts(
+    build_path_prefix: &str,
+    contract_name: &str,
+) -> eyre::Result<(FlattenedSierraClass, FieldElement)> {
+    let artifact_path = format!("{build_path_prefix}{contract_name}.contract_class.json");
+
+    let file = std::fs::File::open(artifact_path.clone())?;
+    let sierra_class: SierraClass = serde_json::from_reader(file)?;
+
+    let artifact_path = format!("{build_path_prefix}{contract_name}.compiled_contract_class.json");
+    let file = std::fs::File::open(artifact_path)?;
+
+    let compiled_class: CompiledClass = serde_json::from_reader(file)?;
+
+    Ok((sierra_class.flatten()?, compiled_class.class_hash()?))
+}
+
+/// Deploys a contract with the given class hash and constructor calldata, using a zero salt.
+/// Returns the deployed address and the transaction result.
+pub async fn deploy_contract(
+    class_hash: FieldElement,
+    constructor_calldata: Vec<FieldElement>,
+    deployer: &StarknetAccount,
+) -> (FieldElement, InvokeTransactionResult) {
+    deploy_contract_with_salt(class_hash, constructor_calldata, felt!("0"), deployer)
+        .await
+        .unwrap_or_else(|err| panic!("Deployment failed: {}", err))
+}
+
+/// Deploys a contract with the given class hash, constructor calldata, and salt through the
+/// universal deployer. Returns the deployed address and the transaction result.
+pub async fn deploy_contract_with_salt(
+    class_hash: FieldElement,
+    constructor_calldata: Vec<FieldElement>,
+    salt: FieldElement,
+    deployer: &StarknetAccount,
+) -> eyre::Result<(FieldElement, InvokeTransactionResult)> {
+    let contract_factory = ContractFactory::new(class_hash, deployer);
+
+    let deployment = contract_factory.deploy(constructor_calldata, salt, false);
+
+    let deploy_res = deployment.send().await?;
+    TxWatcher::new(deployer.provider())
+        .wait(deploy_res.transaction_hash)
+        .await?;
+
+    Ok((deployment.deployed_address(), deploy_res))
+}
+
+/// Check if a contract class is already declared.
+/// # Arguments
+/// * `provider` - The StarkNet provider.
+/// * `class_hash` - The contract class hash.
+/// # Returns
+/// `true` if the contract class is already declared, `false` otherwise.
+async fn is_already_declared<P>(provider: &P, class_hash: &FieldElement) -> eyre::Result<bool>
+where
+    P: Provider,
+{
+    match provider
+        .get_class(BlockId::Tag(BlockTag::Pending), class_hash)
+        .await
+    {
+        Ok(_) => {
+            eprintln!("Not declaring class as it's already declared. Class hash:");
+            println!("{:#064x}", class_hash);
+
+            Ok(true)
+        }
+        Err(ProviderError::StarknetError(StarknetError::ClassHashNotFound)) => Ok(false),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Declare a hyperlane core contract class. If the contract class is already declared, do nothing.
+/// # Arguments
+/// * `account` - The StarkNet account.
+/// * `contract_name` - The contract name.
+/// # Returns
+/// The contract class hash.
+pub async fn declare_contract(
+    account: &StarknetAccount,
+    contract_name: &str,
+) -> eyre::Result<FieldElement> {
+    declare_contract_from(account, BUILD_PATH_PREFIX, contract_name).await
+}
+
+/// Declare a contract class from the given build directory. If the contract class is already
+/// declared, do nothing.
+/// # Arguments
+/// * `account` - The StarkNet account.
+/// * `build_path_prefix` - The build directory joined with the scarb package prefix.
+/// * `contract_name` - The contract name.
+/// # Returns
+/// The contract class hash.
+pub async fn declare_contract_from(
+    account: &StarknetAccount,
+    build_path_prefix: &str,
+    contract_name: &str,
+) -> eyre::Result<FieldElement> {
+    // Load the contract artifact.
+    let (flattened_class, compiled_class_hash) =
+        contract_artifacts(build_path_prefix, contract_name)?;
+    let class_hash = flattened_class.class_hash();
+
+    // Declare the contract class if it is not already declared.
+    if !is_already_declared(account.provider(), &class_hash).await? {
+        println!("\n==> Declaring Contract: {contract_name}");
+        let res = account
+            .declare(Arc::new(flattened_class), compiled_class_hash)
+            .send()
+            .await?;
+        TxWatcher::new(account.provider())
+            .wait(res.transaction_hash)
+            .await?;
+        println!("Declared Class Hash: {:#064x}", class_hash);
+    };
+
+    Ok(class_hash)
+}
@@ -0,0 +1,248 @@
+use cainome::cairo_serde::{ByteArray, ContractAddress, U256};
+use starknet::{
+    accounts::{Account, ConnectedAccount},
+    core::{types::FieldElement, utils::get_selector_from_name},
+};
+
+use super::{
+    bind::{xerc20_factory::XERC20Factory, xerc20_token::XERC20},
+    declare_contract_from, deploy_contract, StarknetAccount, TOKEN_BUILD_PATH_PREFIX,
+    XERC20_BUILD_PATH_PREFIX,
+};
+use crate::tx_watcher::{TxOutcome, TxWatcher};
+
+/// Class hashes of the xERC20 package and of the warp routes bridging it.
+pub struct XERC20Codes {
+    pub factory: FieldElement,
+    pub xerc20: FieldElement,
+    pub lockbox: FieldElement,
+
+    pub hyp_xerc20: FieldElement,
+    pub hyp_xerc20_lockbox: FieldElement,
+}
+
+/// Maximum amounts a bridge is allowed to mint and burn within the xERC20 rate limit window.
+#[derive(Clone, Debug)]
+pub struct BridgeLimits {
+    pub minting: U256,
+    pub burning: U256,
+}
+
+/// Parameters of an xERC20 warp route deployment.
+pub struct XERC20RouteConfig<'a> {
+    pub name: &'a str,
+    pub symbol: &'a str,
+    /// ERC20 wrapped by a lockbox. When set, a lockbox and a `HypXERC20Lockbox` route are deployed
+    /// alongside the `HypXERC20` route.
+    pub base_token: Option<FieldElement>,
+    /// Limits granted to every warp route bridging the xERC20.
+    pub limits: BridgeLimits,
+
+    pub mailbox: FieldElement,
+    pub hook: FieldElement,
+    pub ism: FieldElement,
+}
+
+#[derive(Debug)]
+pub struct XERC20Deployments {
+    pub xerc20: FieldElement,
+    pub hyp_xerc20: FieldElement,
+    pub lockbox: Option<FieldElement>,
+    pub hyp_xerc20_lockbox: Option<FieldElement>,
+}
+
+/// Declares the xERC20 factory, token and lockbox classes as well as the xERC20 warp routes.
+pub async fn declare_xerc20_classes(deployer: &StarknetAccount) -> eyre::Result<XERC20Codes> {
+    let factory =
+        declare_contract_from(deployer, XERC20_BUILD_PATH_PREFIX, "XERC20Factory").await?;
+    let xerc20 = declare_contract_from(deployer, XERC20_BUILD_PATH_PREFIX, "XERC20").await?;
+    let lockbox =
+        declare_contract_from(deployer, XERC20_BUILD_PATH_PREFIX, "XERC20Lockbox").await?;
+    let hyp_xerc20 = declare_contract_from(deployer, TOKEN_BUILD_PATH_PREFIX, "HypXERC20").await?;
+    let hyp_xerc20_lockbox =
+        declare_contract_from(deployer, TOKEN_BUILD_PATH_PREFIX, "HypXERC20Lockbox").await?;
+
+    Ok(XERC20Codes {
+        factory,
+        xerc20,
+        lockbox,
+        hyp_xerc20,
+        hyp_xerc20_lockbox,
+    })
+}
+
+/// Deploys the xERC20 factory, owned by `owner`, with the declared token and lockbox classes.
+pub async fn deploy_xerc20_factory(
+    codes: &XERC20Codes,
+    owner: &StarknetAccount,
+    deployer: &StarknetAccount,
+) -> eyre::Result<FieldElement> {
+    let (factory, _) = deploy_contract(
+        codes.factory,
+        vec![codes.xerc20, codes.lockbox, owner.address()],
+        deployer,
+    )
+    .await;
+    println!("Deployed XERC20Factory {:x?}", factory);
+
+    Ok(factory)
+}
+
+/// Deploys an xERC20 through the factory. The ownership of the token is transferred to `owner`.
+///
+/// # Arguments
+///
+/// * `factory` - The xERC20 factory address.
+/// * `owner` - The account calling the factory, owner of the deployed token.
+/// * `name` - The token name.
+/// * `symbol` - The token symbol.
+/// * `bridges` - Bridges allowed to mint and burn the token along with their limits.
+pub async fn deploy_xerc20(
+    factory: FieldElement,
+    owner: &StarknetAccount,
+    name: &str,
+    symbol: &str,
+    bridges: &[(FieldElement, BridgeLimits)],
+) -> eyre::Result<FieldElement> {
+    let contract = XERC20Factory::new(factory, owner);
+    let res = contract
+        .deploy_xerc20(
+            &ByteArray::from_string(name)?,
+            &ByteArray::from_string(symbol)?,
+            &bridges.iter().map(|(_, l)| l.minting).collect(),
+            &bridges.iter().map(|(_, l)| l.burning).collect(),
+            &bridges.iter().map(|(b, _)| ContractAddress(*b)).collect(),
+        )
+        .send()
+        .await?;
+    let outcome = TxWatcher::new(owner.provider())
+        .wait(res.transaction_hash)
+        .await?;
+
+    let xerc20 = deployed_from_event(&outcome, factory, "XERC20Deployed")?;
+    println!("Deployed XERC20 {:x?}", xerc20);
+
+    Ok(xerc20)
+}
+
+/// Deploys the lockbox of `xerc20` wrapping `base_token` through the factory.
+/// `owner` must be the owner of the xERC20.
+pub async fn deploy_lockbox(
+    factory: FieldElement,
+    owner: &StarknetAccount,
+    xerc20: FieldElement,
+    base_token: FieldElement,
+) -> eyre::Result<FieldElement> {
+    let contract = XERC20Factory::new(factory, owner);
+    let res = contract
+        .deploy_lockbox(&ContractAddress(xerc20), &ContractAddress(base_token))
+        .send()
+        .await?;
+    TxWatcher::new(owner.provider())
+        .wait(res.transaction_hash)
+        .await?;
+
+    let lockbox = contract
+        .get_lockbox_for_erc20(&ContractAddress(base_token))
+        .call()
+        .await?;
+    println!("Deployed XERC20Lockbox {:x?}", lockbox.0);
+
+    Ok(lockbox.0)
+}
+
+/// Sets the minting and burning limits of `bridge` on `xerc20`. `owner` must own the xERC20.
+pub async fn set_bridge_limits(
+    xerc20: FieldElement,
+    owner: &StarknetAccount,
+    bridge: FieldElement,
+    limits: &BridgeLimits,
+) -> eyre::Result<()> {
+    let contract = XERC20::new(xerc20, owner);
+    let res = contract
+        .set_limits(&ContractAddress(bridge), &limits.minting, &limits.burning)
+        .send()
+        .await?;
+    TxWatcher::new(owner.provider())
+        .wait(res.transaction_hash)
+        .await?;
+
+    Ok(())
+}
+
+/// Deploys an xERC20 and the warp routes bridging it, each route being granted `config.limits`.
+///
+/// A `HypXERC20` route is always deployed. If `config.base_token` is set, a lockbox wrapping it
+/// and a `HypXERC20Lockbox` route are deployed as well.
+pub async fn deploy_xerc20_warp_route(
+    codes: &XERC20Codes,
+    factory: FieldElement,
+    config: XERC20RouteConfig<'_>,
+    owner: &StarknetAccount,
+    deployer: &StarknetAccount,
+) -> eyre::Result<XERC20Deployments> {
+    println!("\n==> Deploying XERC20 warp route");
+    let xerc20 = deploy_xerc20(factory, owner, config.name, config.symbol, &[]).await?;
+
+    let (hyp_xerc20, _) = deploy_contract(
+        codes.hyp_xerc20,
+        vec![
+            config.mailbox,
+            xerc20,
+            owner.address(),
+            config.hook,
+            config.ism,
+        ],
+        deployer,
+    )
+    .await;
+    println!("Deployed HypXERC20 {:x?}", hyp_xerc20);
+    set_bridge_limits(xerc20, owner, hyp_xerc20, &config.limits).await?;
+
+    let (lockbox, hyp_xerc20_lockbox) = match config.base_token {
+        Some(base_token) => {
+            let lockbox = deploy_lockbox(factory, owner, xerc20, base_token).await?;
+
+            let (hyp_xerc20_lockbox, _) = deploy_contract(
+                codes.hyp_xerc20_lockbox,
+                vec![
+                    config.mailbox,
+                    lockbox,
+                    owner.address(),
+                    config.hook,
+                    config.ism,
+                ],
+                deployer,
+            )
+            .await;
+            println!("Deployed HypXERC20Lockbox {:x?}", hyp_xerc20_lockbox);
+            set_bridge_limits(xerc20, owner, hyp_xerc20_lockbox, &config.limits).await?;
+
+            (Some(lockbox), Some(hyp_xerc20_lockbox))
+        }
+        None => (None, None),
+    };
+
+    Ok(XERC20Deployments {
+        xerc20,
+        hyp_xerc20,
+        lockbox,
+        hyp_xerc20_lockbox,
+    })
+}
+
+/// Returns the address carried by the first data field of the `event_name` event emitted by `emitter`.
+fn deployed_from_event(
+    outcome: &TxOutcome,
+    emitter: FieldElement,
+    event_name: &str,
+) -> eyre::Result<FieldElement> {
+    let key = get_selector_from_name(event_name)?;
+
+    outcome
+        .raw_events()
+        .iter()
+        .find(|e| e.from_address == emitter && e.keys.first() == Some(&key))
+        .and_then(|e| e.data.first().copied())
+        .ok_or(eyre::eyre!("{event_name} event not found in receipt"))
+}
@@ -0,0 +1,103 @@
+use ethers::{
+    core::k256::ecdsa::{RecoveryId, Signature, VerifyingKey},
+    types::Address,
+    utils::keccak256,
+};
+
+/// Length of a `r || s || v` signature.
+pub const SIGNATURE_LENGTH: usize = 65;
+
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum EcdsaError {
+    #[error("invalid signature length {0}, expected {SIGNATURE_LENGTH} bytes")]
+    InvalidLength(usize),
+    #[error("invalid recovery id v = {0}")]
+    InvalidV(u8),
+    #[error("r or s out of the secp256k1 scalar range")]
+    InvalidSignature,
+    #[error("no public key recovered from the signature")]
+    Recovery,
+    #[error("signer {0:?} is not a validator")]
+    UnknownSigner(Address),
+}
+
+/// Rewrites `v` of a `r || s || v` signature to 27/28, the values expected on Starknet:
+/// `signature_from_vrs` reads the y parity from the parity of `v`, so that 0/1 would flip it.
+pub fn normalize_signature(signature: &[u8]) -> Result<[u8; SIGNATURE_LENGTH], EcdsaError> {
+    let mut output: [u8; SIGNATURE_LENGTH] = signature
+        .try_into()
+        .map_err(|_| EcdsaError::InvalidLength(signature.len()))?;
+
+    output[64] = match output[64] {
+        v @ (0 | 1) => v + 27,
+        v @ (27 | 28) => v,
+        v => return Err(EcdsaError::InvalidV(v)),
+    };
+    Ok(output)
+}
+
+/// Recovers the address signing `digest`, as `bool_is_eth_signature_valid` does on Starknet: the
+/// y parity is set for even `v`, and high `s` values are accepted.
+pub fn recover_signer(digest: &[u8; 32], signature: &[u8]) -> Result<Address, EcdsaError> {
+    if signature.len() != SIGNATURE_LENGTH {
+        return Err(EcdsaError::InvalidLength(signature.len()));
+    }
+
+    let (rs, v) = signature.split_at(64);
+    let y_odd = v[0] % 2 == 0;
+
+    let signature = Signature::from_slice(rs).map_err(|_| EcdsaError::InvalidSignature)?;
+    // k256 only recovers from low `s` signatures
+    let (signature, y_odd) = match signature.normalize_s() {
+        Some(normalized) => (normalized, !y_odd),
+        None => (signature, y_odd),
+    };
+
+    let key = VerifyingKey::recover_from_prehash(digest, &signature, RecoveryId::new(y_odd, false))
+        .map_err(|_| EcdsaError::Recovery)?;
+    let point = key.to_encoded_point(false);
+    Ok(Address::from_slice(
+        &keccak256(&point.as_bytes()[1..])[12..],
+    ))
+}
+
+/// Returns whether `signer` signed `digest`, matching `bool_is_eth_signature_valid`.
+pub fn is_eth_signature_valid(digest: &[u8; 32], signature: &[u8], signer: Address) -> bool {
+    recover_signer(digest, signature) == Ok(signer)
+}
+
+/// Normalizes validator signatures and sorts them in the order of `validators`, the order the
+/// multisig ISMs match them in. Duplicate signatures of a validator are dropped.
+///
+/// # Arguments
+///
+/// * `digest` - The checkpoint digest signed by the validators.
+/// * `signatures` - The `r || s || v` signatures, `v` being either 0/1 or 27/28.
+/// * `validators` - The validators of the ISM, in order.
+pub fn sort_signatures<S>(
+    digest: &[u8; 32],
+    signatures: &[S],
+    validators: &[Address],
+) -> Result<Vec<[u8; SIGNATURE_LENGTH]>, EcdsaError>
+where
+    S: AsRef<[u8]>,
+{
+    let mut indexed = vec![];
+    for signature in signatures {
+        let signature = normalize_signature(signature.as_ref())?;
+        let signer = recover_signer(digest, &signature)?;
+        let index = validators
+            .iter()
+            .position(|validator| *validator == signer)
+            .ok_or(EcdsaError::UnknownSigner(signer))?;
+
+        indexed.push((index, signature));
+    }
+
+    indexed.sort_by_key(|(index, _)| *index);
+    indexed.dedup_by_key(|(index, _)| *index);
+    Ok(indexed
+        .into_iter()
+        .map(|(_, signature)| signature)
+        .collect())
+}
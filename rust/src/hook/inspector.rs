@@ -0,0 +1,393 @@
+use std::fmt;
+
+use cainome::cairo_serde::{CairoSerde, U256};
+use starknet::{
+    core::types::{BlockId, BlockTag, FieldElement, FunctionCall},
+    macros::selector,
+    providers::Provider,
+};
+
+use super::HookType;
+use crate::{
+    contracts::strk::{
+        hook::{self, hookReader},
+        mailbox::mailboxReader,
+        protocol_fee::protocol_feeReader,
+        routing_hook::domain_routing_hookReader,
+    },
+    message::HYPERLANE_VERSION,
+    utils::BoxFuture,
+};
+
+/// Maximum nesting of the inspected hooks, bounding the walk of misconfigured routing cycles.
+const MAX_DEPTH: usize = 16;
+
+/// A post dispatch hook along with, recursively, the hooks it delegates to.
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct HookNode {
+    pub address: FieldElement,
+    pub hook_type: HookType,
+    /// Fee quoted by the hook for the inspected message, including the fees of its sub hooks.
+    pub quote: Option<ethers::types::U256>,
+    /// Revert reason of the quote, when the hook cannot handle the message.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quote_error: Option<String>,
+    #[serde(flatten)]
+    pub kind: HookKind,
+}
+
+#[derive(Clone, Debug, serde::Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum HookKind {
+    /// `domain_routing_hook`, delegating to the hook configured for the message destination.
+    Routing {
+        destination: u32,
+        hook: Option<Box<HookNode>>,
+    },
+    /// `protocol_fee`, charging a flat fee collected by `beneficiary`.
+    ProtocolFee {
+        protocol_fee: ethers::types::U256,
+        beneficiary: FieldElement,
+    },
+    /// `merkle_tree_hook` or any other hook without sub hooks.
+    Leaf,
+}
+
+/// Hooks run by the mailbox on dispatch, and the fees they charge.
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct DispatchHooks {
+    pub destination: u32,
+    pub required: HookNode,
+    /// Mailbox default hook, or the custom hook of the dispatch.
+    pub default: HookNode,
+    /// Fee charged by the mailbox, the sum of the required and default hook quotes as computed by
+    /// the mailbox `quote_dispatch`. Unset when one of the quotes reverted.
+    pub total: Option<ethers::types::U256>,
+}
+
+/// Builds the message the mailbox would dispatch next, as quoted by its hooks.
+///
+/// # Arguments
+///
+/// * `provider` - The Starknet provider.
+/// * `mailbox` - The origin mailbox.
+/// * `sender` - The sender of the message.
+/// * `destination` - The destination domain.
+/// * `recipient` - The recipient on the destination domain.
+/// * `body` - The message body.
+pub async fn build_dispatch_message<P>(
+    provider: &P,
+    mailbox: FieldElement,
+    sender: FieldElement,
+    destination: u32,
+    recipient: &U256,
+    body: &[u8],
+) -> eyre::Result<hook::Message>
+where
+    P: Provider + Sync,
+{
+    let mailbox = mailboxReader::new(mailbox, provider);
+    let body = crate::contracts::strk::to_strk_message_bytes(body);
+
+    Ok(hook::Message {
+        version: HYPERLANE_VERSION,
+        nonce: mailbox.nonce().call().await?,
+        origin: mailbox.get_local_domain().call().await?,
+        sender: U256::from_bytes_be(&sender.to_bytes_be()),
+        destination,
+        recipient: U256 {
+            low: recipient.low,
+            high: recipient.high,
+        },
+        body: hook::Bytes {
+            size: body.size,
+            data: body.data,
+        },
+    })
+}
+
+/// Reads of the mailbox and hook contracts the inspector walks.
+pub trait HookSource: Send + Sync {
+    fn required_hook(&self, mailbox: FieldElement) -> BoxFuture<'_, eyre::Result<FieldElement>>;
+
+    fn default_hook(&self, mailbox: FieldElement) -> BoxFuture<'_, eyre::Result<FieldElement>>;
+
+    fn hook_type(&self, hook: FieldElement) -> BoxFuture<'_, eyre::Result<HookType>>;
+
+    /// Fee quoted by the hook to post dispatch `message`, failing when the quote reverts.
+    fn quote_dispatch<'a>(
+        &'a self,
+        hook: FieldElement,
+        metadata: &'a hook::Bytes,
+        message: &'a hook::Message,
+    ) -> BoxFuture<'a, eyre::Result<ethers::types::U256>>;
+
+    /// Hook a routing hook delegates to for `destination`, zero when none is configured.
+    fn routing_hook(
+        &self,
+        hook: FieldElement,
+        destination: u32,
+    ) -> BoxFuture<'_, eyre::Result<FieldElement>>;
+
+    fn protocol_fee(&self, hook: FieldElement) -> BoxFuture<'_, eyre::Result<ethers::types::U256>>;
+
+    fn beneficiary(&self, hook: FieldElement) -> BoxFuture<'_, eyre::Result<FieldElement>>;
+}
+
+/// Mailbox and hooks deployed on a Starknet chain.
+pub struct StarknetHookSource<'a, P> {
+    provider: &'a P,
+}
+
+impl<'a, P> StarknetHookSource<'a, P> {
+    pub fn new(provider: &'a P) -> Self {
+        Self { provider }
+    }
+}
+
+impl<P> HookSource for StarknetHookSource<'_, P>
+where
+    P: Provider + Sync,
+{
+    fn required_hook(&self, mailbox: FieldElement) -> BoxFuture<'_, eyre::Result<FieldElement>> {
+        Box::pin(async move {
+            let hook = mailboxReader::new(mailbox, self.provider)
+                .get_required_hook()
+                .call()
+                .await?;
+            Ok(hook.0)
+        })
+    }
+
+    fn default_hook(&self, mailbox: FieldElement) -> BoxFuture<'_, eyre::Result<FieldElement>> {
+        Box::pin(async move {
+            let hook = mailboxReader::new(mailbox, self.provider)
+                .get_default_hook()
+                .call()
+                .await?;
+            Ok(hook.0)
+        })
+    }
+
+    fn hook_type(&self, hook: FieldElement) -> BoxFuture<'_, eyre::Result<HookType>> {
+        Box::pin(async move {
+            let hook_type = hookReader::new(hook, self.provider)
+                .hook_type()
+                .call()
+                .await?;
+            Ok(HookType::from(&hook_type))
+        })
+    }
+
+    /// Hooks take `ref self` in `quote_dispatch`, so the bindings only expose it as an invoke: the
+    /// call is built by hand instead.
+    fn quote_dispatch<'a>(
+        &'a self,
+        hook: FieldElement,
+        metadata: &'a hook::Bytes,
+        message: &'a hook::Message,
+    ) -> BoxFuture<'a, eyre::Result<ethers::types::U256>> {
+        Box::pin(async move {
+            let mut calldata = hook::Bytes::cairo_serialize(metadata);
+            calldata.extend(hook::Message::cairo_serialize(message));
+
+            let res = self
+                .provider
+                .call(
+                    FunctionCall {
+                        contract_address: hook,
+                        entry_point_selector: selector!("quote_dispatch"),
+                        calldata,
+                    },
+                    BlockId::Tag(BlockTag::Pending),
+                )
+                .await?;
+
+            Ok(to_ethers_u256(&U256::cairo_deserialize(&res, 0)?))
+        })
+    }
+
+    fn routing_hook(
+        &self,
+        hook: FieldElement,
+        destination: u32,
+    ) -> BoxFuture<'_, eyre::Result<FieldElement>> {
+        Box::pin(async move {
+            let hook = domain_routing_hookReader::new(hook, self.provider)
+                .get_hook(&destination)
+                .call()
+                .await?;
+            Ok(hook.0)
+        })
+    }
+
+    fn protocol_fee(&self, hook: FieldElement) -> BoxFuture<'_, eyre::Result<ethers::types::U256>> {
+        Box::pin(async move {
+            let protocol_fee = protocol_feeReader::new(hook, self.provider)
+                .get_protocol_fee()
+                .call()
+                .await?;
+            Ok(to_ethers_u256(&protocol_fee))
+        })
+    }
+
+    fn beneficiary(&self, hook: FieldElement) -> BoxFuture<'_, eyre::Result<FieldElement>> {
+        Box::pin(async move {
+            let beneficiary = protocol_feeReader::new(hook, self.provider)
+                .get_beneficiary()
+                .call()
+                .await?;
+            Ok(beneficiary.0)
+        })
+    }
+}
+
+/// Resolves the required and default (or custom) hooks of the mailbox for `message`, with the fee
+/// quoted by each of them.
+pub async fn inspect_dispatch_hooks(
+    source: &dyn HookSource,
+    mailbox: FieldElement,
+    metadata: &hook::Bytes,
+    message: &hook::Message,
+    custom_hook: Option<FieldElement>,
+) -> eyre::Result<DispatchHooks> {
+    let required = source.required_hook(mailbox).await?;
+    let default = match custom_hook {
+        Some(hook) => hook,
+        None => source.default_hook(mailbox).await?,
+    };
+
+    let required = inspect_hook(source, required, metadata, message).await?;
+    let default = inspect_hook(source, default, metadata, message).await?;
+    let total = match (required.quote, default.quote) {
+        (Some(required), Some(default)) => Some(required + default),
+        _ => None,
+    };
+
+    Ok(DispatchHooks {
+        destination: message.destination,
+        required,
+        default,
+        total,
+    })
+}
+
+/// Resolves the graph of hooks rooted at `address` for `message`, with the fee quoted by each node.
+pub async fn inspect_hook(
+    source: &dyn HookSource,
+    address: FieldElement,
+    metadata: &hook::Bytes,
+    message: &hook::Message,
+) -> eyre::Result<HookNode> {
+    inspect_node(source, address, metadata, message, 0).await
+}
+
+fn inspect_node<'a>(
+    source: &'a dyn HookSource,
+    address: FieldElement,
+    metadata: &'a hook::Bytes,
+    message: &'a hook::Message,
+    depth: usize,
+) -> BoxFuture<'a, eyre::Result<HookNode>> {
+    Box::pin(async move {
+        if depth > MAX_DEPTH {
+            eyre::bail!("hook graph deeper than {MAX_DEPTH} levels, is there a routing cycle?");
+        }
+
+        let hook_type = source.hook_type(address).await?;
+
+        let (quote, quote_error) = match source.quote_dispatch(address, metadata, message).await {
+            Ok(quote) => (Some(quote), None),
+            Err(e) => (None, Some(e.to_string())),
+        };
+
+        let kind = match hook_type {
+            HookType::Routing | HookType::FallbackRouting => {
+                let hook = source.routing_hook(address, message.destination).await?;
+
+                let hook = if hook == FieldElement::ZERO {
+                    None
+                } else {
+                    Some(Box::new(
+                        inspect_node(source, hook, metadata, message, depth + 1).await?,
+                    ))
+                };
+
+                HookKind::Routing {
+                    destination: message.destination,
+                    hook,
+                }
+            }
+            HookType::ProtocolFee => HookKind::ProtocolFee {
+                protocol_fee: source.protocol_fee(address).await?,
+                beneficiary: source.beneficiary(address).await?,
+            },
+            _ => HookKind::Leaf,
+        };
+
+        Ok(HookNode {
+            address,
+            hook_type,
+            quote,
+            quote_error,
+            kind,
+        })
+    })
+}
+
+fn to_ethers_u256(value: &U256) -> ethers::types::U256 {
+    (ethers::types::U256::from(value.high) << 128) + ethers::types::U256::from(value.low)
+}
+
+impl HookNode {
+    fn write_tree(&self, f: &mut fmt::Formatter<'_>, indent: usize, label: &str) -> fmt::Result {
+        let pad = "  ".repeat(indent);
+        write!(f, "{pad}{label}{} {:#064x}", self.hook_type, self.address)?;
+        match (&self.quote, &self.quote_error) {
+            (Some(quote), _) => write!(f, " quote {quote}")?,
+            (None, Some(error)) => write!(f, " quote reverted: {error}")?,
+            (None, None) => {}
+        }
+
+        match &self.kind {
+            HookKind::Routing { destination, hook } => {
+                writeln!(f)?;
+                match hook {
+                    Some(hook) => {
+                        hook.write_tree(f, indent + 1, &format!("destination {destination}: "))?
+                    }
+                    None => writeln!(f, "{pad}  destination {destination}: no hook")?,
+                }
+            }
+            HookKind::ProtocolFee {
+                protocol_fee,
+                beneficiary,
+            } => writeln!(
+                f,
+                " (protocol fee {protocol_fee}, beneficiary {:#064x})",
+                beneficiary
+            )?,
+            HookKind::Leaf => writeln!(f)?,
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for HookNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_tree(f, 0, "")
+    }
+}
+
+impl fmt::Display for DispatchHooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Hooks for destination {}", self.destination)?;
+        self.required.write_tree(f, 1, "required: ")?;
+        self.default.write_tree(f, 1, "default: ")?;
+        match &self.total {
+            Some(total) => writeln!(f, "Total fee: {total}"),
+            None => writeln!(f, "Total fee: unavailable, a hook quote reverted"),
+        }
+    }
+}
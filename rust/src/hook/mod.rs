@@ -0,0 +1,83 @@
+mod inspector;
+
+use std::fmt;
+
+use crate::contracts::strk::hook;
+
+pub use inspector::*;
+
+/// Type of a post dispatch hook, numbered as the hyperlane `IPostDispatchHook.Types` enum shared by
+/// the Starknet and EVM implementations.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HookType {
+    Unused,
+    Routing,
+    Aggregation,
+    MerkleTree,
+    InterchainGasPaymaster,
+    FallbackRouting,
+    IdAuthIsm,
+    Pausable,
+    ProtocolFee,
+    LayerZeroV1,
+    RateLimited,
+}
+
+impl HookType {
+    /// Decodes the hook type returned by an EVM hook `hookType()`.
+    pub fn from_u8(value: u8) -> Option<Self> {
+        Some(match value {
+            0 => Self::Unused,
+            1 => Self::Routing,
+            2 => Self::Aggregation,
+            3 => Self::MerkleTree,
+            4 => Self::InterchainGasPaymaster,
+            5 => Self::FallbackRouting,
+            6 => Self::IdAuthIsm,
+            7 => Self::Pausable,
+            8 => Self::ProtocolFee,
+            9 => Self::LayerZeroV1,
+            10 => Self::RateLimited,
+            _ => return None,
+        })
+    }
+}
+
+impl From<&hook::Types> for HookType {
+    fn from(value: &hook::Types) -> Self {
+        match value {
+            hook::Types::UNUSED => Self::Unused,
+            hook::Types::ROUTING => Self::Routing,
+            hook::Types::AGGREGATION => Self::Aggregation,
+            hook::Types::MERKLE_TREE => Self::MerkleTree,
+            hook::Types::INTERCHAIN_GAS_PAYMASTER => Self::InterchainGasPaymaster,
+            hook::Types::FALLBACK_ROUTING => Self::FallbackRouting,
+            hook::Types::ID_AUTH_ISM => Self::IdAuthIsm,
+            hook::Types::PAUSABLE => Self::Pausable,
+            hook::Types::PROTOCOL_FEE => Self::ProtocolFee,
+            hook::Types::LAYER_ZERO_V1 => Self::LayerZeroV1,
+            hook::Types::Rate_Limited_Hook => Self::RateLimited,
+        }
+    }
+}
+
+impl fmt::Display for HookType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Unused => "unused",
+            Self::Routing => "routing",
+            Self::Aggregation => "aggregation",
+            Self::MerkleTree => "merkle_tree",
+            Self::InterchainGasPaymaster => "interchain_gas_paymaster",
+            Self::FallbackRouting => "fallback_routing",
+            Self::IdAuthIsm => "id_auth_ism",
+            Self::Pausable => "pausable",
+            Self::ProtocolFee => "protocol_fee",
+            Self::LayerZeroV1 => "layer_zero_v1",
+            Self::RateLimited => "rate_limited",
+        };
+
+        f.write_str(name)
+    }
+}
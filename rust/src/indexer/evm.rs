@@ -0,0 +1,119 @@
+use ethers::{providers::Middleware, types::H256};
+
+use crate::{
+    contracts::eth::mailbox::{Mailbox, MailboxEvents},
+    message::{HyperlaneAddress, HyperlaneMessage},
+    utils::BoxFuture,
+};
+
+use super::{IndexedEvent, MailboxEvent, MailboxEventSource};
+
+/// Mailbox of an EVM chain, its events read with `eth_getLogs`.
+pub struct EvmMailboxEvents<M> {
+    pub mailbox: Mailbox<M>,
+}
+
+impl<M> EvmMailboxEvents<M> {
+    pub fn new(mailbox: Mailbox<M>) -> Self {
+        Self { mailbox }
+    }
+}
+
+impl<M: Middleware + 'static> MailboxEventSource for EvmMailboxEvents<M> {
+    fn latest_block(&self) -> BoxFuture<'_, eyre::Result<u64>> {
+        Box::pin(async move { Ok(self.mailbox.client().get_block_number().await?.as_u64()) })
+    }
+
+    fn block_hash(&self, block: u64) -> BoxFuture<'_, eyre::Result<Option<[u8; 32]>>> {
+        Box::pin(async move {
+            let block = self.mailbox.client().get_block(block).await?;
+
+            Ok(block.and_then(|block| block.hash).map(|hash| hash.0))
+        })
+    }
+
+    fn block_timestamp(&self, block_hash: [u8; 32]) -> BoxFuture<'_, eyre::Result<u64>> {
+        Box::pin(evm_block_timestamp(self.mailbox.client_ref(), block_hash))
+    }
+
+    fn events(&self, from: u64, to: u64) -> BoxFuture<'_, eyre::Result<Vec<IndexedEvent>>> {
+        Box::pin(evm_mailbox_events(&self.mailbox, from, to))
+    }
+}
+
+/// Events of the EVM `mailbox` between blocks `from` and `to`, both included.
+pub async fn evm_mailbox_events<M: Middleware + 'static>(
+    mailbox: &Mailbox<M>,
+    from: u64,
+    to: u64,
+) -> eyre::Result<Vec<IndexedEvent>> {
+    let mut logs = mailbox
+        .events()
+        .from_block(from)
+        .to_block(to)
+        .query_with_meta()
+        .await?;
+    logs.sort_by_key(|(_, meta)| (meta.block_number, meta.log_index));
+
+    let mut events = vec![];
+    for (log, meta) in logs {
+        let event = match log {
+            MailboxEvents::DispatchFilter(dispatch) => {
+                MailboxEvent::Dispatch(HyperlaneMessage::from_bytes(&dispatch.message)?)
+            }
+            MailboxEvents::DispatchIdFilter(dispatch_id) => {
+                MailboxEvent::DispatchId(dispatch_id.message_id)
+            }
+            MailboxEvents::ProcessFilter(process) => MailboxEvent::Process {
+                origin: process.origin,
+                sender: process.sender,
+                recipient: HyperlaneAddress::from(process.recipient).0,
+            },
+            MailboxEvents::ProcessIdFilter(process_id) => {
+                MailboxEvent::ProcessId(process_id.message_id)
+            }
+            _ => continue,
+        };
+
+        events.push(IndexedEvent {
+            block_number: meta.block_number.as_u64(),
+            block_hash: meta.block_hash.0,
+            block_timestamp: None,
+            transaction_hash: meta.transaction_hash.0,
+            event,
+        });
+    }
+
+    Ok(events)
+}
+
+/// Messages dispatched by the EVM `mailbox` between blocks `from` and `to`, both included,
+/// querying its `Dispatch` logs only.
+pub async fn evm_dispatched_messages<M: Middleware + 'static>(
+    mailbox: &Mailbox<M>,
+    from: u64,
+    to: u64,
+) -> eyre::Result<Vec<HyperlaneMessage>> {
+    mailbox
+        .dispatch_filter()
+        .from_block(from)
+        .to_block(to)
+        .query()
+        .await?
+        .into_iter()
+        .map(|dispatch| HyperlaneMessage::from_bytes(&dispatch.message))
+        .collect()
+}
+
+/// Unix timestamp of the EVM block of hash `block_hash`, in seconds.
+pub async fn evm_block_timestamp<M: Middleware + 'static>(
+    client: &M,
+    block_hash: [u8; 32],
+) -> eyre::Result<u64> {
+    let block = client
+        .get_block(H256(block_hash))
+        .await?
+        .ok_or(eyre::eyre!("block 0x{} not found", hex::encode(block_hash)))?;
+
+    Ok(block.timestamp.as_u64())
+}
@@ -0,0 +1,280 @@
+use std::collections::HashMap;
+
+use ethers::utils::keccak256;
+
+use crate::{
+    checkpoint::{zero_hashes, IncrementalMerkle, TREE_DEPTH},
+    validator::MerkleTreeHookSource,
+};
+
+/// Append-only merkle tree of the dispatched message ids, keeping every complete node so that
+/// proofs are served for any leaf against the root of any past size of the tree.
+#[derive(Clone, Debug, Default)]
+pub struct MerkleTree {
+    /// Roots of the complete subtrees of each height, from left to right.
+    levels: Vec<Vec<[u8; 32]>>,
+    ids: HashMap<[u8; 32], u32>,
+    /// Left branch of the next insertion, giving the current root as the merkle tree hook does.
+    frontier: IncrementalMerkle,
+}
+
+impl MerkleTree {
+    /// Number of leaves inserted.
+    pub fn count(&self) -> u32 {
+        self.frontier.count()
+    }
+
+    pub fn root(&self) -> [u8; 32] {
+        self.frontier.root()
+    }
+
+    pub fn frontier(&self) -> &IncrementalMerkle {
+        &self.frontier
+    }
+
+    /// Message id inserted at `index`.
+    pub fn leaf(&self, index: u32) -> Option<[u8; 32]> {
+        self.levels.first()?.get(index as usize).copied()
+    }
+
+    /// Root of the tree when it held the leaves up to `index` included, as signed in the
+    /// checkpoint of `index`.
+    pub fn root_at(&self, index: u32) -> Option<[u8; 32]> {
+        (index < self.count()).then(|| self.node(&zero_hashes(), TREE_DEPTH, 0, index as u64 + 1))
+    }
+
+    /// Index of the leaf of message `id`, unset when not inserted.
+    pub fn leaf_index(&self, id: &[u8; 32]) -> Option<u32> {
+        self.ids.get(id).copied()
+    }
+
+    /// Inserts `leaf` at index `count`.
+    pub fn ingest(&mut self, leaf: [u8; 32]) {
+        self.ids.insert(leaf, self.count());
+        self.frontier.ingest(leaf);
+
+        let mut node = leaf;
+        for height in 0..TREE_DEPTH {
+            if self.levels.len() == height {
+                self.levels.push(vec![]);
+            }
+            let level = &mut self.levels[height];
+            level.push(node);
+            if level.len() % 2 == 1 {
+                break;
+            }
+            node = keccak256([level[level.len() - 2], node].concat());
+        }
+    }
+
+    /// Drops the leaves from index `count` on.
+    pub fn truncate(&mut self, count: u32) {
+        if count < self.count() {
+            let leaves = self.levels[0][..count as usize].to_vec();
+            *self = Self::default();
+            for leaf in leaves {
+                self.ingest(leaf);
+            }
+        }
+    }
+
+    /// Proof of leaf `leaf_index` against the root of the tree when it held the leaves up to
+    /// `root_index` included.
+    pub fn proof(&self, leaf_index: u32, root_index: u32) -> eyre::Result<[[u8; 32]; TREE_DEPTH]> {
+        if leaf_index > root_index || root_index >= self.count() {
+            eyre::bail!(
+                "no proof of leaf {leaf_index} at index {root_index} in a tree of {} leaves",
+                self.count()
+            );
+        }
+
+        let zeros = zero_hashes();
+        let size = root_index as u64 + 1;
+        let mut proof = [[0u8; 32]; TREE_DEPTH];
+        for (height, node) in proof.iter_mut().enumerate() {
+            *node = self.node(&zeros, height, ((leaf_index as u64) >> height) ^ 1, size);
+        }
+        Ok(proof)
+    }
+
+    /// Node `index` of `height` in the tree holding its first `size` leaves.
+    fn node(
+        &self,
+        zeros: &[[u8; 32]; TREE_DEPTH],
+        height: usize,
+        index: u64,
+        size: u64,
+    ) -> [u8; 32] {
+        let start = index << height;
+        if start >= size {
+            return zeros[height];
+        }
+        if start + (1 << height) <= size {
+            return self.levels[height][index as usize];
+        }
+
+        keccak256(
+            [
+                self.node(zeros, height - 1, 2 * index, size),
+                self.node(zeros, height - 1, 2 * index + 1, size),
+            ]
+            .concat(),
+        )
+    }
+}
+
+/// Last block of a range indexed by a [`MerkleTreeIndexer`], with the tree of the hook as of it.
+#[derive(Clone, Copy, Debug)]
+struct IndexedRange {
+    block: u64,
+    count: u32,
+    root: [u8; 32],
+}
+
+/// Rebuilds the merkle tree of a merkle tree hook from its `InsertedIntoTree` events.
+///
+/// After each indexed range of blocks, the local tree is checked against the latest checkpoint
+/// of the hook at the last block of the range. The tree as of the last blocks of the latest
+/// ranges is compared with the hook before indexing further, so that a reorg of the origin is
+/// detected and the tree rewound to the last range still in the chain, or indexed again from the
+/// start block when the reorg is deeper than the ranges remembered.
+pub struct MerkleTreeIndexer {
+    hook: Box<dyn MerkleTreeHookSource>,
+    tree: MerkleTree,
+    start_block: u64,
+    next_block: u64,
+    max_block_range: u64,
+    reorg_window: usize,
+    /// Last blocks of the latest indexed ranges, oldest first.
+    indexed: Vec<IndexedRange>,
+}
+
+impl MerkleTreeIndexer {
+    /// Indexes `hook` from `start_block`, at or before its deployment.
+    pub fn new(hook: impl MerkleTreeHookSource + 'static, start_block: u64) -> Self {
+        Self {
+            hook: Box::new(hook),
+            tree: MerkleTree::default(),
+            start_block,
+            next_block: start_block,
+            max_block_range: 1000,
+            reorg_window: 64,
+            indexed: vec![],
+        }
+    }
+
+    /// Maximum number of blocks indexed per range, 1000 by default.
+    pub fn with_max_block_range(mut self, max_block_range: u64) -> Self {
+        self.max_block_range = max_block_range.max(1);
+        self
+    }
+
+    /// Number of indexed ranges remembered to find the common ancestor of a reorg, 64 by
+    /// default.
+    pub fn with_reorg_window(mut self, reorg_window: usize) -> Self {
+        self.reorg_window = reorg_window.max(1);
+        self
+    }
+
+    pub fn hook(&self) -> &dyn MerkleTreeHookSource {
+        self.hook.as_ref()
+    }
+
+    pub fn tree(&self) -> &MerkleTree {
+        &self.tree
+    }
+
+    /// Indexes the messages inserted up to the latest block, returning the number of leaves
+    /// kept when a reorg of the origin dropped the others.
+    pub async fn sync(&mut self) -> eyre::Result<Option<u32>> {
+        let mut kept = self.rewind().await?;
+
+        let latest_block = self.hook.latest_block().await?;
+        while self.next_block <= latest_block {
+            let to = latest_block.min(self.next_block + self.max_block_range - 1);
+            let inserted = self.hook.inserted_ids(self.next_block, to).await?;
+
+            let mut frontier = self.tree.frontier().clone();
+            for inserted in &inserted {
+                if inserted.index != frontier.count() {
+                    eyre::bail!(
+                        "expected message {} in the merkle tree hook, got message {}: the start \
+                         block must not be after the deployment of the merkle tree hook",
+                        frontier.count(),
+                        inserted.index
+                    );
+                }
+                frontier.ingest(inserted.id);
+            }
+
+            let (count, root) = self.hook_tree(to).await?;
+            if count != frontier.count() || root != frontier.root() {
+                // the indexed blocks were reorganized since the last check
+                if let Some(rewound) = self.rewind().await? {
+                    kept = Some(kept.map_or(rewound, |kept| kept.min(rewound)));
+                    continue;
+                }
+                eyre::bail!(
+                    "indexed tree of {} messages with root 0x{} does not match the merkle tree \
+                     hook tree of {count} messages with root 0x{} at block {to}",
+                    frontier.count(),
+                    hex::encode(frontier.root()),
+                    hex::encode(root)
+                );
+            }
+
+            for inserted in inserted {
+                self.tree.ingest(inserted.id);
+            }
+            self.next_block = to + 1;
+            self.indexed.push(IndexedRange {
+                block: to,
+                count,
+                root,
+            });
+            let excess = self.indexed.len().saturating_sub(self.reorg_window);
+            self.indexed.drain(..excess);
+        }
+
+        Ok(kept)
+    }
+
+    /// Number of messages in the tree of the hook and its root as of `block`.
+    async fn hook_tree(&self, block: u64) -> eyre::Result<(u32, [u8; 32])> {
+        Ok(match self.hook.latest_checkpoint(block).await? {
+            Some(checkpoint) => (checkpoint.index + 1, checkpoint.root),
+            None => (0, IncrementalMerkle::default().root()),
+        })
+    }
+
+    /// Rewinds the tree past the indexed ranges whose tree no longer matches the hook, returning
+    /// the number of leaves kept.
+    async fn rewind(&mut self) -> eyre::Result<Option<u32>> {
+        let mut kept = self.indexed.len();
+        while kept > 0 {
+            let range = self.indexed[kept - 1];
+            if self.hook_tree(range.block).await? == (range.count, range.root) {
+                break;
+            }
+            kept -= 1;
+        }
+        if kept == self.indexed.len() {
+            return Ok(None);
+        }
+
+        self.indexed.truncate(kept);
+        match self.indexed.last() {
+            Some(ancestor) => {
+                self.tree.truncate(ancestor.count);
+                self.next_block = ancestor.block + 1;
+            }
+            // deeper than the ranges remembered
+            None => {
+                self.tree = MerkleTree::default();
+                self.next_block = self.start_block;
+            }
+        }
+
+        Ok(Some(self.tree.count()))
+    }
+}
@@ -0,0 +1,435 @@
+use std::{path::Path, time::Duration};
+
+use rusqlite::{params, params_from_iter, types::Value, Connection, OptionalExtension, Row};
+
+use crate::message::HyperlaneMessage;
+
+use super::{
+    resolve_block_timestamps, IndexedEvent, IndexerCursor, IndexerUpdate, MailboxEvent,
+    MailboxEventSource, MailboxIndexer,
+};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS cursors (
+    domain INTEGER PRIMARY KEY,
+    cursor TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS dispatches (
+    id BLOB PRIMARY KEY,
+    origin INTEGER NOT NULL,
+    nonce INTEGER NOT NULL,
+    sender BLOB NOT NULL,
+    destination INTEGER NOT NULL,
+    recipient BLOB NOT NULL,
+    message BLOB NOT NULL,
+    block_number INTEGER NOT NULL,
+    transaction_hash BLOB NOT NULL,
+    timestamp INTEGER NOT NULL
+);
+CREATE UNIQUE INDEX IF NOT EXISTS dispatches_nonce ON dispatches (origin, nonce);
+CREATE INDEX IF NOT EXISTS dispatches_sender ON dispatches (sender);
+CREATE INDEX IF NOT EXISTS dispatches_recipient ON dispatches (recipient);
+CREATE INDEX IF NOT EXISTS dispatches_timestamp ON dispatches (timestamp);
+CREATE TABLE IF NOT EXISTS deliveries (
+    id BLOB PRIMARY KEY,
+    destination INTEGER NOT NULL,
+    origin INTEGER NOT NULL,
+    sender BLOB NOT NULL,
+    recipient BLOB NOT NULL,
+    block_number INTEGER NOT NULL,
+    transaction_hash BLOB NOT NULL,
+    timestamp INTEGER NOT NULL
+);
+";
+
+const MESSAGE_COLUMNS: &str = "d.message, d.block_number, d.transaction_hash, d.timestamp, \
+     p.destination, p.block_number, p.transaction_hash, p.timestamp";
+
+const DELIVERY_COLUMNS: &str = "destination, block_number, transaction_hash, timestamp";
+
+/// Delivery of a message by the mailbox of its destination.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MessageDelivery {
+    pub destination: u32,
+    pub block_number: u64,
+    pub transaction_hash: [u8; 32],
+    /// Unix timestamp of the block, in seconds.
+    pub timestamp: u64,
+}
+
+impl MessageDelivery {
+    fn from_row(row: &Row, offset: usize) -> rusqlite::Result<Self> {
+        Ok(Self {
+            destination: row.get(offset)?,
+            block_number: row.get(offset + 1)?,
+            transaction_hash: hash_from_row(row, offset + 2)?,
+            timestamp: row.get(offset + 3)?,
+        })
+    }
+}
+
+/// Message dispatched by an indexed mailbox, along with its delivery once the mailbox of its
+/// destination processed it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IndexedMessage {
+    pub message: HyperlaneMessage,
+    pub block_number: u64,
+    pub transaction_hash: [u8; 32],
+    /// Unix timestamp of the block, in seconds.
+    pub timestamp: u64,
+    /// Unset while the delivery is not indexed, or when the destination is not.
+    pub delivery: Option<MessageDelivery>,
+}
+
+impl IndexedMessage {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        let bytes: Vec<u8> = row.get(0)?;
+        let delivered: Option<u32> = row.get(4)?;
+
+        Ok(Self {
+            message: HyperlaneMessage::from_bytes(&bytes).map_err(|e| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    0,
+                    rusqlite::types::Type::Blob,
+                    e.to_string().into(),
+                )
+            })?,
+            block_number: row.get(1)?,
+            transaction_hash: hash_from_row(row, 2)?,
+            timestamp: row.get(3)?,
+            delivery: match delivered {
+                Some(_) => Some(MessageDelivery::from_row(row, 4)?),
+                None => None,
+            },
+        })
+    }
+}
+
+fn hash_from_row(row: &Row, index: usize) -> rusqlite::Result<[u8; 32]> {
+    let bytes: Vec<u8> = row.get(index)?;
+    bytes.try_into().map_err(|_| {
+        rusqlite::Error::FromSqlConversionFailure(
+            index,
+            rusqlite::types::Type::Blob,
+            "hash is not 32 bytes".into(),
+        )
+    })
+}
+
+fn block_timestamp(indexed: &IndexedEvent) -> eyre::Result<u64> {
+    indexed.block_timestamp.ok_or(eyre::eyre!(
+        "timestamp of block {} not resolved",
+        indexed.block_number
+    ))
+}
+
+/// Filter of [`MessageDb::query`], unset fields matching every message.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MessageQuery {
+    pub origin: Option<u32>,
+    pub destination: Option<u32>,
+    pub sender: Option<[u8; 32]>,
+    pub recipient: Option<[u8; 32]>,
+    /// Dispatched at or after this unix timestamp, in seconds.
+    pub dispatched_after: Option<u64>,
+    /// Dispatched before this unix timestamp, in seconds.
+    pub dispatched_before: Option<u64>,
+    /// Whether the delivery of the message is indexed.
+    pub delivered: Option<bool>,
+    /// Maximum number of messages returned.
+    pub limit: Option<usize>,
+}
+
+/// Messages dispatched and delivered by the indexed mailboxes, in a sqlite database fed by their
+/// [`MailboxIndexer`]s.
+///
+/// Dispatches are joined with the deliveries of their destination, so that the messages not
+/// delivered yet are listed from the indexed chains alone.
+pub struct MessageDb {
+    conn: Connection,
+}
+
+impl MessageDb {
+    /// Opens the database at `path`, creating it when missing.
+    pub fn open(path: impl AsRef<Path>) -> eyre::Result<Self> {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    /// Opens a database living in memory, lost when dropped.
+    pub fn in_memory() -> eyre::Result<Self> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(conn: Connection) -> eyre::Result<Self> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    /// Cursor of the indexer of `domain`, unset before its first indexing.
+    pub fn cursor(&self, domain: u32) -> eyre::Result<Option<IndexerCursor>> {
+        let cursor: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT cursor FROM cursors WHERE domain = ?1",
+                params![domain],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        Ok(cursor
+            .map(|cursor| serde_json::from_str(&cursor))
+            .transpose()?)
+    }
+
+    /// Indexes the next range of blocks of the mailbox of `domain` and records it, along with the
+    /// timestamp of the blocks of its events.
+    ///
+    /// The indexer is rewound to its previous cursor when the update cannot be recorded, so that
+    /// the range is indexed again.
+    pub async fn index<S: MailboxEventSource>(
+        &self,
+        domain: u32,
+        indexer: &mut MailboxIndexer<S>,
+    ) -> eyre::Result<IndexerUpdate> {
+        let previous = indexer.cursor().clone();
+        let mut update = indexer.next().await?;
+        let mut recorded = Ok(());
+        if let IndexerUpdate::Indexed { events, .. } = &mut update {
+            let source = indexer.source();
+            recorded =
+                resolve_block_timestamps(events, |block_hash| source.block_timestamp(block_hash))
+                    .await;
+        }
+        if let Err(e) = recorded.and_then(|()| self.record(domain, &update, indexer.cursor())) {
+            indexer.reset(previous);
+            return Err(e);
+        }
+
+        Ok(update)
+    }
+
+    /// Records an update of the indexer of the mailbox of `domain` along with its new `cursor`,
+    /// atomically so that a restart neither skips nor duplicates events.
+    ///
+    /// The dispatches and deliveries of the reorganized blocks are removed. The block timestamp of
+    /// the events must be resolved.
+    pub fn record(
+        &self,
+        domain: u32,
+        update: &IndexerUpdate,
+        cursor: &IndexerCursor,
+    ) -> eyre::Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        match update {
+            IndexerUpdate::Indexed { events, .. } => {
+                // each `ProcessId` directly follows the `Process` of the message
+                let mut process = None;
+                for indexed in events {
+                    match &indexed.event {
+                        MailboxEvent::Dispatch(message) => {
+                            tx.execute(
+                                "INSERT OR REPLACE INTO dispatches (id, origin, nonce, sender,
+                                 destination, recipient, message, block_number, transaction_hash,
+                                 timestamp) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                                params![
+                                    message.id().to_vec(),
+                                    message.origin,
+                                    message.nonce,
+                                    message.sender.to_vec(),
+                                    message.destination,
+                                    message.recipient.to_vec(),
+                                    message.to_bytes(),
+                                    indexed.block_number,
+                                    indexed.transaction_hash.to_vec(),
+                                    block_timestamp(indexed)?,
+                                ],
+                            )?;
+                        }
+                        MailboxEvent::DispatchId(_) => {}
+                        MailboxEvent::Process {
+                            origin,
+                            sender,
+                            recipient,
+                        } => process = Some((*origin, *sender, *recipient)),
+                        MailboxEvent::ProcessId(id) => {
+                            let Some((origin, sender, recipient)) = process.take() else {
+                                eyre::bail!(
+                                    "delivery of message 0x{} without its Process event",
+                                    hex::encode(id)
+                                );
+                            };
+                            tx.execute(
+                                "INSERT OR REPLACE INTO deliveries (id, destination, origin,
+                                 sender, recipient, block_number, transaction_hash, timestamp)
+                                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                                params![
+                                    id.to_vec(),
+                                    domain,
+                                    origin,
+                                    sender.to_vec(),
+                                    recipient.to_vec(),
+                                    indexed.block_number,
+                                    indexed.transaction_hash.to_vec(),
+                                    block_timestamp(indexed)?,
+                                ],
+                            )?;
+                        }
+                    }
+                }
+            }
+            IndexerUpdate::Reorg { from } => {
+                tx.execute(
+                    "DELETE FROM dispatches WHERE origin = ?1 AND block_number >= ?2",
+                    params![domain, from],
+                )?;
+                tx.execute(
+                    "DELETE FROM deliveries WHERE destination = ?1 AND block_number >= ?2",
+                    params![domain, from],
+                )?;
+            }
+            IndexerUpdate::Synced => {}
+        }
+        tx.execute(
+            "INSERT INTO cursors (domain, cursor) VALUES (?1, ?2)
+             ON CONFLICT (domain) DO UPDATE SET cursor = excluded.cursor",
+            params![domain, serde_json::to_string(cursor)?],
+        )?;
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    pub fn message(&self, id: &[u8; 32]) -> eyre::Result<Option<IndexedMessage>> {
+        Ok(self
+            .conn
+            .query_row(
+                &format!(
+                    "SELECT {MESSAGE_COLUMNS} FROM dispatches d
+                     LEFT JOIN deliveries p ON p.id = d.id WHERE d.id = ?1"
+                ),
+                params![id.to_vec()],
+                IndexedMessage::from_row,
+            )
+            .optional()?)
+    }
+
+    /// Message dispatched by the mailbox of `origin` with `nonce`.
+    pub fn message_by_nonce(
+        &self,
+        origin: u32,
+        nonce: u32,
+    ) -> eyre::Result<Option<IndexedMessage>> {
+        Ok(self
+            .conn
+            .query_row(
+                &format!(
+                    "SELECT {MESSAGE_COLUMNS} FROM dispatches d
+                     LEFT JOIN deliveries p ON p.id = d.id WHERE d.origin = ?1 AND d.nonce = ?2"
+                ),
+                params![origin, nonce],
+                IndexedMessage::from_row,
+            )
+            .optional()?)
+    }
+
+    /// Delivery of message `id`, also known when its origin is not indexed.
+    pub fn delivery(&self, id: &[u8; 32]) -> eyre::Result<Option<MessageDelivery>> {
+        Ok(self
+            .conn
+            .query_row(
+                &format!("SELECT {DELIVERY_COLUMNS} FROM deliveries WHERE id = ?1"),
+                params![id.to_vec()],
+                |row| MessageDelivery::from_row(row, 0),
+            )
+            .optional()?)
+    }
+
+    /// Messages matching `query`, oldest first.
+    pub fn query(&self, query: &MessageQuery) -> eyre::Result<Vec<IndexedMessage>> {
+        let mut conditions = vec![];
+        let mut values = vec![];
+        let mut filter = |condition: &str, value: Value| {
+            values.push(value);
+            conditions.push(format!("{condition} ?{}", values.len()));
+        };
+        if let Some(origin) = query.origin {
+            filter("d.origin =", Value::Integer(origin.into()));
+        }
+        if let Some(destination) = query.destination {
+            filter("d.destination =", Value::Integer(destination.into()));
+        }
+        if let Some(sender) = query.sender {
+            filter("d.sender =", Value::Blob(sender.to_vec()));
+        }
+        if let Some(recipient) = query.recipient {
+            filter("d.recipient =", Value::Blob(recipient.to_vec()));
+        }
+        if let Some(after) = query.dispatched_after {
+            filter("d.timestamp >=", Value::Integer(after as i64));
+        }
+        if let Some(before) = query.dispatched_before {
+            filter("d.timestamp <", Value::Integer(before as i64));
+        }
+        match query.delivered {
+            Some(true) => conditions.push("p.id IS NOT NULL".to_string()),
+            Some(false) => conditions.push("p.id IS NULL".to_string()),
+            None => {}
+        }
+
+        let mut sql = format!(
+            "SELECT {MESSAGE_COLUMNS} FROM dispatches d LEFT JOIN deliveries p ON p.id = d.id"
+        );
+        if !conditions.is_empty() {
+            sql.push_str(&format!(" WHERE {}", conditions.join(" AND ")));
+        }
+        sql.push_str(" ORDER BY d.timestamp, d.origin, d.nonce");
+        if let Some(limit) = query.limit {
+            sql.push_str(&format!(" LIMIT {limit}"));
+        }
+
+        let mut statement = self.conn.prepare(&sql)?;
+        let messages = statement
+            .query_map(params_from_iter(values), IndexedMessage::from_row)?
+            .collect::<Result<_, _>>()?;
+        Ok(messages)
+    }
+
+    /// Messages sent by `sender`, oldest first.
+    pub fn messages_by_sender(&self, sender: &[u8; 32]) -> eyre::Result<Vec<IndexedMessage>> {
+        self.query(&MessageQuery {
+            sender: Some(*sender),
+            ..Default::default()
+        })
+    }
+
+    /// Messages sent to `recipient`, oldest first.
+    pub fn messages_by_recipient(&self, recipient: &[u8; 32]) -> eyre::Result<Vec<IndexedMessage>> {
+        self.query(&MessageQuery {
+            recipient: Some(*recipient),
+            ..Default::default()
+        })
+    }
+
+    /// Messages dispatched from unix timestamp `from` included to `to` excluded, oldest first.
+    pub fn messages_between(&self, from: u64, to: u64) -> eyre::Result<Vec<IndexedMessage>> {
+        self.query(&MessageQuery {
+            dispatched_after: Some(from),
+            dispatched_before: Some(to),
+            ..Default::default()
+        })
+    }
+
+    /// Messages dispatched more than `age` before unix timestamp `now` and still not delivered,
+    /// oldest first.
+    pub fn undelivered_older_than(
+        &self,
+        now: u64,
+        age: Duration,
+    ) -> eyre::Result<Vec<IndexedMessage>> {
+        self.query(&MessageQuery {
+            dispatched_before: Some(now.saturating_sub(age.as_secs())),
+            delivered: Some(false),
+            ..Default::default()
+        })
+    }
+}
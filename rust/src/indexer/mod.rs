@@ -0,0 +1,281 @@
+mod evm;
+mod merkle_tree;
+mod message_db;
+mod stark;
+
+use std::{collections::HashMap, future::Future};
+
+use crate::{message::HyperlaneMessage, utils::BoxFuture};
+
+pub use evm::*;
+pub use merkle_tree::*;
+pub use message_db::*;
+pub use stark::*;
+
+/// Event of a mailbox, decoded the same way on every chain.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MailboxEvent {
+    /// A message was dispatched.
+    Dispatch(HyperlaneMessage),
+    /// Id of the message dispatched by the preceding `Dispatch`.
+    DispatchId([u8; 32]),
+    /// A message was delivered to `recipient`.
+    Process {
+        origin: u32,
+        sender: [u8; 32],
+        recipient: [u8; 32],
+    },
+    /// Id of the message delivered by the preceding `Process`.
+    ProcessId([u8; 32]),
+}
+
+/// Mailbox event located in the chain.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IndexedEvent {
+    pub block_number: u64,
+    pub block_hash: [u8; 32],
+    /// Unix timestamp of the block, in seconds, unset until resolved with
+    /// [`resolve_block_timestamps`].
+    pub block_timestamp: Option<u64>,
+    pub transaction_hash: [u8; 32],
+    pub event: MailboxEvent,
+}
+
+/// Chain the events of a mailbox are read from.
+pub trait MailboxEventSource: Send + Sync {
+    fn latest_block(&self) -> BoxFuture<'_, eyre::Result<u64>>;
+
+    /// Hash of block `block`, unset when the chain does not reach it.
+    fn block_hash(&self, block: u64) -> BoxFuture<'_, eyre::Result<Option<[u8; 32]>>>;
+
+    /// Unix timestamp of the block of hash `block_hash`, in seconds.
+    fn block_timestamp(&self, block_hash: [u8; 32]) -> BoxFuture<'_, eyre::Result<u64>>;
+
+    /// Events emitted between blocks `from` and `to`, both included, in the order of the chain,
+    /// their block timestamp being left unresolved.
+    fn events(&self, from: u64, to: u64) -> BoxFuture<'_, eyre::Result<Vec<IndexedEvent>>>;
+}
+
+/// Sets the block timestamp of `events`, looking up the timestamp of each of their blocks once
+/// with `block_timestamp`.
+pub async fn resolve_block_timestamps<F, Fut>(
+    events: &mut [IndexedEvent],
+    block_timestamp: F,
+) -> eyre::Result<()>
+where
+    F: Fn([u8; 32]) -> Fut,
+    Fut: Future<Output = eyre::Result<u64>>,
+{
+    let mut timestamps = HashMap::new();
+    for indexed in events {
+        let timestamp = match timestamps.get(&indexed.block_hash) {
+            Some(timestamp) => *timestamp,
+            None => {
+                let timestamp = block_timestamp(indexed.block_hash).await?;
+                timestamps.insert(indexed.block_hash, timestamp);
+                timestamp
+            }
+        };
+        indexed.block_timestamp = Some(timestamp);
+    }
+
+    Ok(())
+}
+
+/// Tuning of a [`MailboxIndexer`].
+#[derive(Clone, Debug)]
+pub struct IndexerSettings {
+    /// Maximum number of blocks indexed per call of [`MailboxIndexer::next`], at least 1.
+    pub max_block_range: u64,
+    /// Blocks left unindexed behind the head of the chain.
+    pub confirmations: u64,
+    /// Number of indexed ranges remembered to find the common ancestor of a reorg.
+    pub reorg_window: usize,
+}
+
+impl Default for IndexerSettings {
+    fn default() -> Self {
+        Self {
+            max_block_range: 1000,
+            confirmations: 0,
+            reorg_window: 64,
+        }
+    }
+}
+
+/// Last block of an indexed range.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct IndexedBlock {
+    pub number: u64,
+    pub hash: [u8; 32],
+    /// Nonce of the next dispatch as of this block, unknown until a dispatch was indexed.
+    pub next_nonce: Option<u32>,
+}
+
+/// Position of a [`MailboxIndexer`], to be persisted by its user so that indexing resumes where
+/// it stopped.
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct IndexerCursor {
+    /// Block indexing started at, indexed again from after a reorg deeper than the ranges
+    /// remembered.
+    #[serde(default)]
+    pub start_block: u64,
+    /// Next block to index.
+    pub next_block: u64,
+    /// Nonce of the next dispatch, unknown until a dispatch was indexed.
+    pub next_nonce: Option<u32>,
+    /// Last blocks of the latest indexed ranges, oldest first.
+    pub indexed: Vec<IndexedBlock>,
+}
+
+impl IndexerCursor {
+    /// Cursor of an indexer starting at `block`.
+    pub fn starting_at(block: u64) -> Self {
+        Self {
+            start_block: block,
+            next_block: block,
+            ..Default::default()
+        }
+    }
+}
+
+/// Outcome of [`MailboxIndexer::next`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IndexerUpdate {
+    /// Events of the blocks `from` to `to`, both included, in the order of the chain.
+    Indexed {
+        from: u64,
+        to: u64,
+        events: Vec<IndexedEvent>,
+    },
+    /// The blocks from `from` on were reorganized: the events emitted for them are void, and
+    /// indexing resumes at `from`.
+    Reorg { from: u64 },
+    /// No block left to index.
+    Synced,
+}
+
+/// Indexes the events of a mailbox in block ranges, emitting them in the order of the chain and
+/// thus the dispatches in nonce order.
+///
+/// The hash of the last block of every indexed range is kept in the cursor and compared with the
+/// chain before indexing further, so that a reorg of the indexed blocks is detected and indexing
+/// rewound to the last range still in the chain. A reorg deeper than the ranges remembered
+/// rewinds indexing to the start block of the cursor, reported as a reorg from that block.
+pub struct MailboxIndexer<S> {
+    source: S,
+    cursor: IndexerCursor,
+    settings: IndexerSettings,
+}
+
+impl<S: MailboxEventSource> MailboxIndexer<S> {
+    pub fn new(source: S, cursor: IndexerCursor, settings: IndexerSettings) -> Self {
+        Self {
+            source,
+            cursor,
+            settings,
+        }
+    }
+
+    pub fn source(&self) -> &S {
+        &self.source
+    }
+
+    pub fn cursor(&self) -> &IndexerCursor {
+        &self.cursor
+    }
+
+    /// Moves the indexer to `cursor`, e.g. back to a cursor its user failed to persist past.
+    pub fn reset(&mut self, cursor: IndexerCursor) {
+        self.cursor = cursor;
+    }
+
+    /// Indexes the next range of blocks, or rewinds the cursor when the last indexed blocks were
+    /// reorganized. The cursor is left untouched on error.
+    pub async fn next(&mut self) -> eyre::Result<IndexerUpdate> {
+        if let Some(from) = self.rewind().await? {
+            return Ok(IndexerUpdate::Reorg { from });
+        }
+
+        let latest = self
+            .source
+            .latest_block()
+            .await?
+            .saturating_sub(self.settings.confirmations);
+        let from = self.cursor.next_block;
+        if from > latest {
+            return Ok(IndexerUpdate::Synced);
+        }
+        let to = latest.min(from + self.settings.max_block_range.max(1) - 1);
+
+        // read before the events, so that a reorg racing the query is caught by the next call
+        let hash = self
+            .source
+            .block_hash(to)
+            .await?
+            .ok_or(eyre::eyre!("block {to} not found"))?;
+        let events = self.source.events(from, to).await?;
+
+        let mut next_nonce = self.cursor.next_nonce;
+        for event in &events {
+            if let MailboxEvent::Dispatch(message) = &event.event {
+                if let Some(expected) = next_nonce.filter(|nonce| *nonce != message.nonce) {
+                    eyre::bail!(
+                        "dispatch of nonce {} indexed in block {} while nonce {expected} was \
+                         expected, events are missing",
+                        message.nonce,
+                        event.block_number
+                    );
+                }
+                next_nonce = Some(message.nonce + 1);
+            }
+        }
+
+        self.cursor.next_block = to + 1;
+        self.cursor.next_nonce = next_nonce;
+        self.cursor.indexed.push(IndexedBlock {
+            number: to,
+            hash,
+            next_nonce,
+        });
+        let excess = self
+            .cursor
+            .indexed
+            .len()
+            .saturating_sub(self.settings.reorg_window.max(1));
+        self.cursor.indexed.drain(..excess);
+
+        Ok(IndexerUpdate::Indexed { from, to, events })
+    }
+
+    /// Rewinds the cursor past the indexed blocks no longer in the chain, returning the first
+    /// block to index again.
+    async fn rewind(&mut self) -> eyre::Result<Option<u64>> {
+        let mut kept = self.cursor.indexed.len();
+        while kept > 0 {
+            let block = self.cursor.indexed[kept - 1];
+            if self.source.block_hash(block.number).await? == Some(block.hash) {
+                break;
+            }
+            kept -= 1;
+        }
+        if kept == self.cursor.indexed.len() {
+            return Ok(None);
+        }
+
+        self.cursor.indexed.truncate(kept);
+        match self.cursor.indexed.last() {
+            Some(ancestor) => {
+                self.cursor.next_block = ancestor.number + 1;
+                self.cursor.next_nonce = ancestor.next_nonce;
+            }
+            // deeper than the ranges remembered
+            None => {
+                self.cursor.next_block = self.cursor.start_block;
+                self.cursor.next_nonce = None;
+            }
+        }
+
+        Ok(Some(self.cursor.next_block))
+    }
+}
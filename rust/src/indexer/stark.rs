@@ -0,0 +1,186 @@
+use starknet::{
+    core::types::{
+        BlockId, EventFilter, FieldElement, MaybePendingBlockWithTxHashes, StarknetError,
+    },
+    macros::selector,
+    providers::{AnyProvider, Provider, ProviderError},
+};
+
+use crate::{
+    contracts::strk::mailbox::Event,
+    message::{u256_to_bytes, HyperlaneMessage},
+    utils::BoxFuture,
+};
+
+use super::{IndexedEvent, MailboxEvent, MailboxEventSource};
+
+/// Maximum number of events fetched per `starknet_getEvents` page.
+const EVENTS_CHUNK_SIZE: u64 = 100;
+
+/// Mailbox of a Starknet chain, its events read with `starknet_getEvents`.
+pub struct StarknetMailboxEvents {
+    pub provider: AnyProvider,
+    pub mailbox: FieldElement,
+}
+
+impl StarknetMailboxEvents {
+    pub fn new(provider: AnyProvider, mailbox: FieldElement) -> Self {
+        Self { provider, mailbox }
+    }
+}
+
+impl MailboxEventSource for StarknetMailboxEvents {
+    fn latest_block(&self) -> BoxFuture<'_, eyre::Result<u64>> {
+        Box::pin(async move { Ok(self.provider.block_number().await?) })
+    }
+
+    fn block_hash(&self, block: u64) -> BoxFuture<'_, eyre::Result<Option<[u8; 32]>>> {
+        Box::pin(async move {
+            match self
+                .provider
+                .get_block_with_tx_hashes(BlockId::Number(block))
+                .await
+            {
+                Ok(MaybePendingBlockWithTxHashes::Block(block)) => {
+                    Ok(Some(block.block_hash.to_bytes_be()))
+                }
+                Ok(MaybePendingBlockWithTxHashes::PendingBlock(_))
+                | Err(ProviderError::StarknetError(StarknetError::BlockNotFound)) => Ok(None),
+                Err(e) => Err(e.into()),
+            }
+        })
+    }
+
+    fn block_timestamp(&self, block_hash: [u8; 32]) -> BoxFuture<'_, eyre::Result<u64>> {
+        Box::pin(starknet_block_timestamp(&self.provider, block_hash))
+    }
+
+    fn events(&self, from: u64, to: u64) -> BoxFuture<'_, eyre::Result<Vec<IndexedEvent>>> {
+        Box::pin(starknet_mailbox_events(
+            &self.provider,
+            self.mailbox,
+            from,
+            to,
+        ))
+    }
+}
+
+/// Events of the Starknet `mailbox` between blocks `from` and `to`, both included, paging through
+/// `starknet_getEvents` with its continuation tokens.
+pub async fn starknet_mailbox_events(
+    provider: &AnyProvider,
+    mailbox: FieldElement,
+    from: u64,
+    to: u64,
+) -> eyre::Result<Vec<IndexedEvent>> {
+    let selectors = vec![
+        selector!("Dispatch"),
+        selector!("DispatchId"),
+        selector!("Process"),
+        selector!("ProcessId"),
+    ];
+    mailbox_events(provider, mailbox, selectors, from, to).await
+}
+
+/// Messages dispatched by the Starknet `mailbox` between blocks `from` and `to`, both included,
+/// querying its `Dispatch` events only.
+pub async fn starknet_dispatched_messages(
+    provider: &AnyProvider,
+    mailbox: FieldElement,
+    from: u64,
+    to: u64,
+) -> eyre::Result<Vec<HyperlaneMessage>> {
+    let events = mailbox_events(provider, mailbox, vec![selector!("Dispatch")], from, to).await?;
+
+    Ok(events
+        .into_iter()
+        .filter_map(|indexed| match indexed.event {
+            MailboxEvent::Dispatch(message) => Some(message),
+            _ => None,
+        })
+        .collect())
+}
+
+/// Events of `mailbox` with one of `selectors` between blocks `from` and `to`, both included.
+async fn mailbox_events(
+    provider: &AnyProvider,
+    mailbox: FieldElement,
+    selectors: Vec<FieldElement>,
+    from: u64,
+    to: u64,
+) -> eyre::Result<Vec<IndexedEvent>> {
+    let filter = EventFilter {
+        from_block: Some(BlockId::Number(from)),
+        to_block: Some(BlockId::Number(to)),
+        address: Some(mailbox),
+        keys: Some(vec![selectors]),
+    };
+
+    let mut events = vec![];
+    let mut continuation_token = None;
+    loop {
+        let page = provider
+            .get_events(filter.clone(), continuation_token, EVENTS_CHUNK_SIZE)
+            .await?;
+
+        for emitted in page.events {
+            let (Some(block_number), Some(block_hash)) = (emitted.block_number, emitted.block_hash)
+            else {
+                eyre::bail!(
+                    "event of transaction {:#064x} is still pending",
+                    emitted.transaction_hash
+                );
+            };
+            let transaction_hash = emitted.transaction_hash.to_bytes_be();
+            let event = match Event::try_from(emitted) {
+                Ok(Event::Dispatch(dispatch)) => {
+                    MailboxEvent::Dispatch(HyperlaneMessage::from(&dispatch.message))
+                }
+                Ok(Event::DispatchId(dispatch_id)) => {
+                    MailboxEvent::DispatchId(u256_to_bytes(&dispatch_id.id))
+                }
+                Ok(Event::Process(process)) => MailboxEvent::Process {
+                    origin: process.origin,
+                    sender: u256_to_bytes(&process.sender),
+                    recipient: u256_to_bytes(&process.recipient),
+                },
+                Ok(Event::ProcessId(process_id)) => {
+                    MailboxEvent::ProcessId(u256_to_bytes(&process_id.id))
+                }
+                _ => continue,
+            };
+
+            events.push(IndexedEvent {
+                block_number,
+                block_hash: block_hash.to_bytes_be(),
+                block_timestamp: None,
+                transaction_hash,
+                event,
+            });
+        }
+
+        match page.continuation_token {
+            Some(token) => continuation_token = Some(token),
+            None => break,
+        }
+    }
+
+    Ok(events)
+}
+
+/// Unix timestamp of the Starknet block of hash `block_hash`, in seconds.
+pub async fn starknet_block_timestamp(
+    provider: &AnyProvider,
+    block_hash: [u8; 32],
+) -> eyre::Result<u64> {
+    let hash = FieldElement::from_bytes_be(&block_hash)?;
+    Ok(
+        match provider
+            .get_block_with_tx_hashes(BlockId::Hash(hash))
+            .await?
+        {
+            MaybePendingBlockWithTxHashes::Block(block) => block.timestamp,
+            MaybePendingBlockWithTxHashes::PendingBlock(block) => block.timestamp,
+        },
+    )
+}
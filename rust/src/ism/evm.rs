@@ -0,0 +1,110 @@
+use std::sync::Arc;
+
+use ethers::{
+    providers::Middleware,
+    types::{Address, Bytes},
+};
+use starknet::core::types::FieldElement;
+
+use super::{inspector::MAX_DEPTH, IsmKind, IsmNode, IsmRoute, ModuleType};
+use crate::{
+    contracts::eth::{
+        aggregation_ism::IAggregationIsm, ism::IInterchainSecurityModule,
+        multisig_ism::IMultisigIsm, routing_ism::IRoutingIsm,
+    },
+    message::HyperlaneMessage,
+    utils::BoxFuture,
+};
+
+/// Resolves the tree of EVM ISMs rooted at `address` verifying `message`.
+///
+/// EVM routing, aggregation and multisig ISMs configure their modules per message, so only the
+/// modules verifying `message` are resolved: routing ISMs hold the route of the message origin
+/// alone. ISM addresses are held by felts, as in the chain configuration, and the ISMs of the null
+/// module type are left of unknown kind, their metadata being empty either way.
+pub async fn inspect_evm_ism<M>(
+    client: Arc<M>,
+    address: Address,
+    message: &HyperlaneMessage,
+) -> eyre::Result<IsmNode>
+where
+    M: Middleware + 'static,
+{
+    inspect_node(client, address, message, 0).await
+}
+
+fn inspect_node<M>(
+    client: Arc<M>,
+    address: Address,
+    message: &HyperlaneMessage,
+    depth: usize,
+) -> BoxFuture<'_, eyre::Result<IsmNode>>
+where
+    M: Middleware + 'static,
+{
+    Box::pin(async move {
+        if depth > MAX_DEPTH {
+            eyre::bail!("ISM tree deeper than {MAX_DEPTH} levels, is there a routing cycle?");
+        }
+
+        let bytes = Bytes::from(message.to_bytes());
+        let module_type = IInterchainSecurityModule::new(address, client.clone())
+            .module_type()
+            .call()
+            .await?;
+        let module_type = ModuleType::from_u8(module_type).ok_or(eyre::eyre!(
+            "unknown module type {module_type} of ISM {address:?}"
+        ))?;
+
+        let kind = match module_type {
+            ModuleType::Routing => {
+                let module = IRoutingIsm::new(address, client.clone())
+                    .route(bytes)
+                    .call()
+                    .await?;
+
+                IsmKind::Routing {
+                    routes: vec![IsmRoute {
+                        origin: message.origin,
+                        ism: inspect_node(client, module, message, depth + 1).await?,
+                    }],
+                    fallback: None,
+                }
+            }
+            ModuleType::Aggregation => {
+                let (modules, threshold) = IAggregationIsm::new(address, client.clone())
+                    .modules_and_threshold(bytes)
+                    .call()
+                    .await?;
+
+                let mut nodes = vec![];
+                for module in modules {
+                    nodes.push(inspect_node(client.clone(), module, message, depth + 1).await?);
+                }
+
+                IsmKind::Aggregation {
+                    threshold,
+                    modules: nodes,
+                }
+            }
+            ModuleType::MessageIdMultisig | ModuleType::MerkleRootMultisig => {
+                let (validators, threshold) = IMultisigIsm::new(address, client)
+                    .validators_and_threshold(bytes)
+                    .call()
+                    .await?;
+
+                IsmKind::Multisig {
+                    threshold: threshold.into(),
+                    validators,
+                }
+            }
+            _ => IsmKind::Unknown,
+        };
+
+        Ok(IsmNode {
+            address: FieldElement::from_byte_slice_be(address.as_bytes())?,
+            module_type,
+            kind,
+        })
+    })
+}
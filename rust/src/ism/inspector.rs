@@ -0,0 +1,415 @@
+use std::fmt;
+
+use cainome::cairo_serde::{self, U256};
+use ethers::types::Address;
+use starknet::{
+    core::{
+        types::{BlockId, BlockTag, FieldElement, StarknetError},
+        utils::get_storage_var_address,
+    },
+    providers::{Provider, ProviderError},
+};
+
+use super::ModuleType;
+use crate::{
+    contracts::strk::{
+        aggregation::aggregationReader, ism::ismReader, mailbox::mailboxReader,
+        multisig_ism::messageid_multisig_ismReader, pausable_ism::pausable_ismReader,
+        routing::domain_routing_ismReader,
+    },
+    utils::BoxFuture,
+};
+
+/// Maximum nesting of the inspected ISMs, bounding the walk of misconfigured routing cycles.
+pub(super) const MAX_DEPTH: usize = 16;
+
+/// Origin probed to tell a `default_fallback_routing_ism` from a `domain_routing_ism`: the former
+/// falls back to the mailbox default ISM for unknown origins while the latter reverts.
+const UNKNOWN_ORIGIN: u32 = u32::MAX;
+
+/// An interchain security module along with, recursively, the modules it relies on.
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct IsmNode {
+    pub address: FieldElement,
+    pub module_type: ModuleType,
+    #[serde(flatten)]
+    pub kind: IsmKind,
+}
+
+#[derive(Clone, Debug, serde::Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum IsmKind {
+    /// `domain_routing_ism` or `default_fallback_routing_ism`, routing messages by origin.
+    Routing {
+        routes: Vec<IsmRoute>,
+        /// ISM of the origins without route, set for a `default_fallback_routing_ism` only.
+        fallback: Option<FieldElement>,
+    },
+    /// `aggregation`, requiring `threshold` of its modules to verify the message.
+    Aggregation {
+        threshold: u8,
+        modules: Vec<IsmNode>,
+    },
+    /// `messageid_multisig_ism` or `merkleroot_multisig_ism`.
+    Multisig {
+        threshold: u32,
+        validators: Vec<Address>,
+    },
+    Pausable {
+        paused: bool,
+    },
+    /// `trusted_relayer_ism`, accepting the messages processed by `relayer`.
+    TrustedRelayer {
+        relayer: FieldElement,
+    },
+    /// `noop_ism`, accepting any message.
+    Noop,
+    /// Module type the inspector does not know how to walk.
+    Unknown,
+}
+
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct IsmRoute {
+    pub origin: u32,
+    pub ism: IsmNode,
+}
+
+/// Reads of the ISM contracts the inspector walks.
+pub trait IsmSource: Send + Sync {
+    fn module_type(&self, ism: FieldElement) -> BoxFuture<'_, eyre::Result<ModuleType>>;
+
+    /// Origin domains with a configured route.
+    fn routing_domains(&self, ism: FieldElement) -> BoxFuture<'_, eyre::Result<Vec<u32>>>;
+
+    /// ISM routing the messages of `origin`, unset when the routing ISM reverts as the origin
+    /// has no route. Failures to read the ISM are errors.
+    fn routing_module(
+        &self,
+        ism: FieldElement,
+        origin: u32,
+    ) -> BoxFuture<'_, eyre::Result<Option<FieldElement>>>;
+
+    fn aggregation_threshold(&self, ism: FieldElement) -> BoxFuture<'_, eyre::Result<u8>>;
+
+    fn aggregation_modules(
+        &self,
+        ism: FieldElement,
+    ) -> BoxFuture<'_, eyre::Result<Vec<FieldElement>>>;
+
+    fn multisig_threshold(&self, ism: FieldElement) -> BoxFuture<'_, eyre::Result<u32>>;
+
+    /// Validators of a multisig ISM, as the felts of their Ethereum addresses.
+    fn multisig_validators(
+        &self,
+        ism: FieldElement,
+    ) -> BoxFuture<'_, eyre::Result<Vec<FieldElement>>>;
+
+    /// Whether a pausable ISM is paused, failing for the other ISMs.
+    fn is_paused(&self, ism: FieldElement) -> BoxFuture<'_, eyre::Result<bool>>;
+
+    /// Relayer of a trusted relayer ISM, zero for the other ISMs.
+    fn trusted_relayer(&self, ism: FieldElement) -> BoxFuture<'_, eyre::Result<FieldElement>>;
+}
+
+/// ISMs deployed on a Starknet chain.
+pub struct StarknetIsmSource<'a, P> {
+    provider: &'a P,
+}
+
+impl<'a, P> StarknetIsmSource<'a, P> {
+    pub fn new(provider: &'a P) -> Self {
+        Self { provider }
+    }
+}
+
+impl<P> IsmSource for StarknetIsmSource<'_, P>
+where
+    P: Provider + Sync,
+{
+    fn module_type(&self, ism: FieldElement) -> BoxFuture<'_, eyre::Result<ModuleType>> {
+        Box::pin(async move {
+            let module_type = ismReader::new(ism, self.provider)
+                .module_type()
+                .call()
+                .await?;
+            Ok(ModuleType::from(&module_type))
+        })
+    }
+
+    fn routing_domains(&self, ism: FieldElement) -> BoxFuture<'_, eyre::Result<Vec<u32>>> {
+        Box::pin(async move {
+            Ok(domain_routing_ismReader::new(ism, self.provider)
+                .domains()
+                .call()
+                .await?)
+        })
+    }
+
+    fn routing_module(
+        &self,
+        ism: FieldElement,
+        origin: u32,
+    ) -> BoxFuture<'_, eyre::Result<Option<FieldElement>>> {
+        Box::pin(async move {
+            let module = domain_routing_ismReader::new(ism, self.provider)
+                .module(&origin)
+                .call()
+                .await;
+            match module {
+                Ok(module) => Ok(Some(module.0)),
+                Err(cairo_serde::Error::Provider(ProviderError::StarknetError(
+                    StarknetError::ContractError(_),
+                ))) => Ok(None),
+                Err(e) => Err(e.into()),
+            }
+        })
+    }
+
+    fn aggregation_threshold(&self, ism: FieldElement) -> BoxFuture<'_, eyre::Result<u8>> {
+        Box::pin(async move {
+            Ok(aggregationReader::new(ism, self.provider)
+                .get_threshold()
+                .call()
+                .await?)
+        })
+    }
+
+    fn aggregation_modules(
+        &self,
+        ism: FieldElement,
+    ) -> BoxFuture<'_, eyre::Result<Vec<FieldElement>>> {
+        Box::pin(async move {
+            let modules = aggregationReader::new(ism, self.provider)
+                .get_modules()
+                .call()
+                .await?;
+            Ok(modules.into_iter().map(|module| module.0).collect())
+        })
+    }
+
+    fn multisig_threshold(&self, ism: FieldElement) -> BoxFuture<'_, eyre::Result<u32>> {
+        // both multisig ISMs expose the same validator configuration
+        Box::pin(async move {
+            Ok(messageid_multisig_ismReader::new(ism, self.provider)
+                .get_threshold()
+                .call()
+                .await?)
+        })
+    }
+
+    fn multisig_validators(
+        &self,
+        ism: FieldElement,
+    ) -> BoxFuture<'_, eyre::Result<Vec<FieldElement>>> {
+        Box::pin(async move {
+            let validators = messageid_multisig_ismReader::new(ism, self.provider)
+                .get_validators()
+                .call()
+                .await?;
+            Ok(validators
+                .into_iter()
+                .map(|validator| validator.0)
+                .collect())
+        })
+    }
+
+    fn is_paused(&self, ism: FieldElement) -> BoxFuture<'_, eyre::Result<bool>> {
+        Box::pin(async move {
+            Ok(pausable_ismReader::new(ism, self.provider)
+                .is_paused()
+                .call()
+                .await?)
+        })
+    }
+
+    fn trusted_relayer(&self, ism: FieldElement) -> BoxFuture<'_, eyre::Result<FieldElement>> {
+        Box::pin(async move {
+            Ok(self
+                .provider
+                .get_storage_at(
+                    ism,
+                    get_storage_var_address("trusted_relayer", &[])?,
+                    BlockId::Tag(BlockTag::Pending),
+                )
+                .await?)
+        })
+    }
+}
+
+/// Resolves the tree of ISMs rooted at `address`.
+///
+/// # Arguments
+///
+/// * `source` - The ISM contracts, e.g. a [`StarknetIsmSource`].
+/// * `address` - The root ISM.
+/// * `origin` - When set, routing ISMs only resolve the route of this origin domain instead of
+///   every configured domain, falling back to the mailbox default ISM for the fallback routing ISMs.
+pub async fn inspect_ism(
+    source: &dyn IsmSource,
+    address: FieldElement,
+    origin: Option<u32>,
+) -> eyre::Result<IsmNode> {
+    inspect_node(source, address, origin, 0).await
+}
+
+/// Resolves the tree of ISMs verifying the messages of `recipient`, as returned by
+/// `mailbox.recipient_ism`.
+pub async fn inspect_recipient_ism<P>(
+    provider: &P,
+    mailbox: FieldElement,
+    recipient: &U256,
+    origin: Option<u32>,
+) -> eyre::Result<IsmNode>
+where
+    P: Provider + Sync,
+{
+    let ism = mailboxReader::new(mailbox, provider)
+        .recipient_ism(recipient)
+        .call()
+        .await?;
+
+    inspect_ism(&StarknetIsmSource::new(provider), ism.0, origin).await
+}
+
+fn inspect_node<'a>(
+    source: &'a dyn IsmSource,
+    address: FieldElement,
+    origin: Option<u32>,
+    depth: usize,
+) -> BoxFuture<'a, eyre::Result<IsmNode>> {
+    Box::pin(async move {
+        if depth > MAX_DEPTH {
+            eyre::bail!("ISM tree deeper than {MAX_DEPTH} levels, is there a routing cycle?");
+        }
+
+        let module_type = source.module_type(address).await?;
+
+        let kind = match module_type {
+            ModuleType::Routing => {
+                let domains = source.routing_domains(address).await?;
+                let fallback = source.routing_module(address, UNKNOWN_ORIGIN).await?;
+
+                // the fallback routes the origins without configured route
+                let domains = match origin {
+                    Some(origin) if domains.contains(&origin) || fallback.is_some() => {
+                        vec![origin]
+                    }
+                    Some(_) => vec![],
+                    None => domains,
+                };
+
+                let mut routes = vec![];
+                for domain in domains {
+                    let module =
+                        source
+                            .routing_module(address, domain)
+                            .await?
+                            .ok_or(eyre::eyre!(
+                                "routing ISM {address:#064x} has no route for origin {domain}"
+                            ))?;
+                    routes.push(IsmRoute {
+                        origin: domain,
+                        ism: inspect_node(source, module, origin, depth + 1).await?,
+                    });
+                }
+
+                IsmKind::Routing { routes, fallback }
+            }
+            ModuleType::Aggregation => {
+                let threshold = source.aggregation_threshold(address).await?;
+
+                let mut modules = vec![];
+                for module in source.aggregation_modules(address).await? {
+                    modules.push(inspect_node(source, module, origin, depth + 1).await?);
+                }
+
+                IsmKind::Aggregation { threshold, modules }
+            }
+            ModuleType::MessageIdMultisig | ModuleType::MerkleRootMultisig => {
+                let threshold = source.multisig_threshold(address).await?;
+                let validators = source
+                    .multisig_validators(address)
+                    .await?
+                    .into_iter()
+                    .map(|validator| Address::from_slice(&validator.to_bytes_be()[12..]))
+                    .collect();
+
+                IsmKind::Multisig {
+                    threshold,
+                    validators,
+                }
+            }
+            // pausable, trusted relayer and noop ISMs all report a null module type
+            ModuleType::Null => null_ism_kind(source, address).await?,
+            _ => IsmKind::Unknown,
+        };
+
+        Ok(IsmNode {
+            address,
+            module_type,
+            kind,
+        })
+    })
+}
+
+async fn null_ism_kind(source: &dyn IsmSource, address: FieldElement) -> eyre::Result<IsmKind> {
+    if let Ok(paused) = source.is_paused(address).await {
+        return Ok(IsmKind::Pausable { paused });
+    }
+
+    let relayer = source.trusted_relayer(address).await?;
+    if relayer != FieldElement::ZERO {
+        return Ok(IsmKind::TrustedRelayer { relayer });
+    }
+
+    Ok(IsmKind::Noop)
+}
+
+impl IsmNode {
+    fn write_tree(&self, f: &mut fmt::Formatter<'_>, indent: usize) -> fmt::Result {
+        let pad = "  ".repeat(indent);
+        write!(f, "{pad}{} {:#064x}", self.module_type, self.address)?;
+
+        match &self.kind {
+            IsmKind::Routing { routes, fallback } => {
+                writeln!(f)?;
+                for route in routes {
+                    writeln!(f, "{pad}  origin {}:", route.origin)?;
+                    route.ism.write_tree(f, indent + 2)?;
+                }
+                if let Some(fallback) = fallback {
+                    writeln!(f, "{pad}  fallback: {:#064x}", fallback)?;
+                }
+            }
+            IsmKind::Aggregation { threshold, modules } => {
+                writeln!(f, " ({threshold} of {})", modules.len())?;
+                for module in modules {
+                    module.write_tree(f, indent + 1)?;
+                }
+            }
+            IsmKind::Multisig {
+                threshold,
+                validators,
+            } => {
+                writeln!(f, " ({threshold} of {})", validators.len())?;
+                for validator in validators {
+                    writeln!(f, "{pad}  validator {:?}", validator)?;
+                }
+            }
+            IsmKind::Pausable { paused } => writeln!(f, " pausable, paused: {paused}")?,
+            IsmKind::TrustedRelayer { relayer } => {
+                writeln!(f, " trusted relayer {:#064x}", relayer)?
+            }
+            IsmKind::Noop => writeln!(f, " noop")?,
+            IsmKind::Unknown => writeln!(f)?,
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for IsmNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_tree(f, 0)
+    }
+}
@@ -0,0 +1,75 @@
+mod evm;
+mod inspector;
+mod simulator;
+
+use std::fmt;
+
+use crate::contracts::strk::ism;
+
+pub use evm::*;
+pub use inspector::*;
+pub use simulator::*;
+
+/// Type of an interchain security module, numbered as the hyperlane `IInterchainSecurityModule.Types`
+/// enum shared by the Starknet and EVM implementations.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModuleType {
+    Unused,
+    Routing,
+    Aggregation,
+    LegacyMultisig,
+    MerkleRootMultisig,
+    MessageIdMultisig,
+    Null,
+    CcipRead,
+}
+
+impl ModuleType {
+    /// Decodes the module type returned by an EVM ISM `moduleType()`.
+    pub fn from_u8(value: u8) -> Option<Self> {
+        Some(match value {
+            0 => Self::Unused,
+            1 => Self::Routing,
+            2 => Self::Aggregation,
+            3 => Self::LegacyMultisig,
+            4 => Self::MerkleRootMultisig,
+            5 => Self::MessageIdMultisig,
+            6 => Self::Null,
+            7 => Self::CcipRead,
+            _ => return None,
+        })
+    }
+}
+
+impl From<&ism::ModuleType> for ModuleType {
+    fn from(value: &ism::ModuleType) -> Self {
+        match value {
+            ism::ModuleType::UNUSED(_) => Self::Unused,
+            ism::ModuleType::ROUTING(_) => Self::Routing,
+            ism::ModuleType::AGGREGATION(_) => Self::Aggregation,
+            ism::ModuleType::LEGACY_MULTISIG(_) => Self::LegacyMultisig,
+            ism::ModuleType::MERKLE_ROOT_MULTISIG(_) => Self::MerkleRootMultisig,
+            ism::ModuleType::MESSAGE_ID_MULTISIG(_) => Self::MessageIdMultisig,
+            ism::ModuleType::NULL => Self::Null,
+            ism::ModuleType::CCIP_READ(_) => Self::CcipRead,
+        }
+    }
+}
+
+impl fmt::Display for ModuleType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Unused => "unused",
+            Self::Routing => "routing",
+            Self::Aggregation => "aggregation",
+            Self::LegacyMultisig => "legacy_multisig",
+            Self::MerkleRootMultisig => "merkle_root_multisig",
+            Self::MessageIdMultisig => "message_id_multisig",
+            Self::Null => "null",
+            Self::CcipRead => "ccip_read",
+        };
+
+        f.write_str(name)
+    }
+}
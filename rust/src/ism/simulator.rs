@@ -0,0 +1,316 @@
+use std::fmt;
+
+use ethers::types::Address;
+use starknet::core::types::FieldElement;
+
+use super::{IsmKind, IsmNode, ModuleType};
+use crate::{
+    checkpoint::{branch_root, checkpoint_digest, TREE_DEPTH},
+    ecdsa::{recover_signer, SIGNATURE_LENGTH},
+    message::HyperlaneMessage,
+};
+
+/// Layout of the `messageid_multisig_ism` metadata: merkle tree hook, root, index, signatures.
+const MESSAGE_ID_SIGNATURES_OFFSET: usize = 68;
+/// Layout of the `merkleroot_multisig_ism` metadata: merkle tree hook, message index, signed
+/// message id, proof, signed index, signatures.
+const MERKLE_ROOT_MESSAGE_INDEX_OFFSET: usize = 32;
+const MERKLE_ROOT_MESSAGE_ID_OFFSET: usize = 36;
+const MERKLE_ROOT_PROOF_OFFSET: usize = 68;
+const MERKLE_ROOT_SIGNED_INDEX_OFFSET: usize = 1092;
+const MERKLE_ROOT_SIGNATURES_OFFSET: usize = 1096;
+
+/// Check of an ISM rejecting the message.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum VerifyError {
+    #[error("empty metadata")]
+    EmptyMetadata,
+    #[error("metadata too short: {size} bytes, reading {offset}..{end}")]
+    MetadataTooShort {
+        size: usize,
+        offset: usize,
+        end: usize,
+    },
+    #[error("invalid metadata range of module {index}: {start}..{end}")]
+    InvalidMetadataRange { index: usize, start: u32, end: u32 },
+    #[error("threshold not set")]
+    NoThreshold,
+    #[error("message index {message_index} is after the signed index {signed_index}")]
+    InvalidMerkleIndex {
+        message_index: u32,
+        signed_index: u32,
+    },
+    #[error("signature {index} does not match any validator")]
+    NoMatchForSignature {
+        index: usize,
+        /// Address recovered from the signature, unset when it is malformed.
+        signer: Option<Address>,
+    },
+    #[error("threshold not reached: {verified} of {threshold} modules verified the message")]
+    ThresholdNotReached { threshold: u8, verified: u8 },
+    #[error("no route for origin {origin}")]
+    NoRoute { origin: u32 },
+    #[error("ISM is paused")]
+    Paused,
+    #[error("processor {processor:#064x} is not the trusted relayer {relayer:#064x}")]
+    UntrustedRelayer {
+        relayer: FieldElement,
+        processor: FieldElement,
+    },
+    #[error("unsupported module type {0}")]
+    Unsupported(ModuleType),
+}
+
+/// Failed verification, along with the path from the root ISM to the ISM rejecting the message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerifyFailure {
+    pub path: Vec<String>,
+    pub error: VerifyError,
+}
+
+impl fmt::Display for VerifyFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for step in &self.path {
+            write!(f, "{step} > ")?;
+        }
+        write!(f, "{}", self.error)
+    }
+}
+
+impl std::error::Error for VerifyFailure {}
+
+/// Predicts the outcome of `verify(metadata, message)` on the ISM tree, as fetched by
+/// [`super::inspect_ism`] with the message origin.
+///
+/// # Arguments
+///
+/// * `ism` - The root ISM of the recipient.
+/// * `metadata` - The candidate metadata.
+/// * `message` - The message to deliver.
+/// * `processor` - The account submitting `process`, checked by the trusted relayer ISMs.
+pub fn simulate_verify(
+    ism: &IsmNode,
+    metadata: &[u8],
+    message: &HyperlaneMessage,
+    processor: FieldElement,
+) -> Result<(), VerifyFailure> {
+    verify_node(ism, metadata, message, processor).map_err(|(mut path, error)| {
+        path.reverse();
+        VerifyFailure { path, error }
+    })
+}
+
+type NodeResult = Result<(), (Vec<String>, VerifyError)>;
+
+fn verify_node(
+    ism: &IsmNode,
+    metadata: &[u8],
+    message: &HyperlaneMessage,
+    processor: FieldElement,
+) -> NodeResult {
+    let node = format!("{} {:#064x}", ism.module_type, ism.address);
+    let at_node = |error| (vec![node.clone()], error);
+
+    match &ism.kind {
+        IsmKind::Routing { routes, .. } => {
+            let Some(route) = routes.iter().find(|r| r.origin == message.origin) else {
+                return Err(at_node(VerifyError::NoRoute {
+                    origin: message.origin,
+                }));
+            };
+
+            verify_node(&route.ism, metadata, message, processor).map_err(|(path, error)| {
+                let step = format!("{node} origin {}", message.origin);
+                (with_step(path, step), error)
+            })
+        }
+        IsmKind::Aggregation { threshold, modules } => {
+            if *threshold == 0 {
+                return Err(at_node(VerifyError::NoThreshold));
+            }
+
+            // modules with metadata must all verify, until the threshold is reached
+            let mut remaining = *threshold;
+            for (index, module) in modules.iter().enumerate() {
+                if remaining == 0 {
+                    break;
+                }
+
+                let Some(module_metadata) =
+                    aggregation_metadata_at(metadata, index).map_err(at_node)?
+                else {
+                    continue;
+                };
+
+                verify_node(module, &module_metadata, message, processor).map_err(
+                    |(path, error)| {
+                        let step = format!("{node} module {index}");
+                        (with_step(path, step), error)
+                    },
+                )?;
+                remaining -= 1;
+            }
+
+            if remaining > 0 {
+                return Err(at_node(VerifyError::ThresholdNotReached {
+                    threshold: *threshold,
+                    verified: threshold - remaining,
+                }));
+            }
+            Ok(())
+        }
+        IsmKind::Multisig {
+            threshold,
+            validators,
+        } => {
+            if metadata.is_empty() {
+                return Err(at_node(VerifyError::EmptyMetadata));
+            }
+
+            let (digest, signatures_offset) = match ism.module_type {
+                ModuleType::MerkleRootMultisig => merkle_root_digest(metadata, message),
+                _ => message_id_digest(metadata, message),
+            }
+            .map_err(at_node)?;
+
+            if *threshold == 0 {
+                return Err(at_node(VerifyError::NoThreshold));
+            }
+
+            // signatures are expected in the order of the validators
+            let mut validator_index = 0;
+            for index in 0..*threshold as usize {
+                let offset = signatures_offset + SIGNATURE_LENGTH * index;
+                let signature = read(metadata, offset, SIGNATURE_LENGTH).map_err(at_node)?;
+                let signer = recover_signer(&digest, signature).ok();
+
+                let matched = validators[validator_index.min(validators.len())..]
+                    .iter()
+                    .position(|validator| Some(*validator) == signer);
+                match matched {
+                    Some(position) => validator_index += position + 1,
+                    None => {
+                        return Err(at_node(VerifyError::NoMatchForSignature { index, signer }))
+                    }
+                }
+            }
+            Ok(())
+        }
+        IsmKind::Pausable { paused: true } => Err(at_node(VerifyError::Paused)),
+        IsmKind::Pausable { paused: false } => Ok(()),
+        IsmKind::TrustedRelayer { relayer } if *relayer != processor => {
+            Err(at_node(VerifyError::UntrustedRelayer {
+                relayer: *relayer,
+                processor,
+            }))
+        }
+        IsmKind::TrustedRelayer { .. } | IsmKind::Noop => Ok(()),
+        IsmKind::Unknown => Err(at_node(VerifyError::Unsupported(ism.module_type))),
+    }
+}
+
+/// Adds `step` to a path collected from the failing ISM up to the root.
+fn with_step(mut path: Vec<String>, step: String) -> Vec<String> {
+    path.push(step);
+    path
+}
+
+fn read(metadata: &[u8], offset: usize, len: usize) -> Result<&[u8], VerifyError> {
+    metadata
+        .get(offset..offset + len)
+        .ok_or(VerifyError::MetadataTooShort {
+            size: metadata.len(),
+            offset,
+            end: offset + len,
+        })
+}
+
+fn read_u32(metadata: &[u8], offset: usize) -> Result<u32, VerifyError> {
+    let bytes = read(metadata, offset, 4)?;
+    Ok(u32::from_be_bytes(bytes.try_into().expect("4 bytes read")))
+}
+
+fn read_h256(metadata: &[u8], offset: usize) -> Result<[u8; 32], VerifyError> {
+    let bytes = read(metadata, offset, 32)?;
+    Ok(bytes.try_into().expect("32 bytes read"))
+}
+
+/// Extracts the metadata of the aggregated module `index`, unset when the module has none.
+///
+/// Mirrors the contract, which copies the metadata by chunks of 16 bytes and left pads its last
+/// chunk: module metadata whose size is not a multiple of 16 bytes reaches the module shifted.
+fn aggregation_metadata_at(metadata: &[u8], index: usize) -> Result<Option<Vec<u8>>, VerifyError> {
+    let start = read_u32(metadata, index * 8)?;
+    let end = read_u32(metadata, index * 8 + 4)?;
+    if start == 0 {
+        return Ok(None);
+    }
+    if end < start {
+        return Err(VerifyError::InvalidMetadataRange { index, start, end });
+    }
+
+    let mut output = vec![];
+    let mut offset = start as usize;
+    loop {
+        let len = (end as usize - offset).min(16);
+        let chunk = read(metadata, offset, len)?;
+        output.extend(std::iter::repeat(0).take(16 - len));
+        output.extend_from_slice(chunk);
+
+        offset += len;
+        if len < 16 || offset == end as usize {
+            break;
+        }
+    }
+
+    Ok(Some(output))
+}
+
+fn message_id_digest(
+    metadata: &[u8],
+    message: &HyperlaneMessage,
+) -> Result<([u8; 32], usize), VerifyError> {
+    let merkle_tree_hook = read_h256(metadata, 0)?;
+    let root = read_h256(metadata, 32)?;
+    let index = read_u32(metadata, 64)?;
+
+    let digest = checkpoint_digest(
+        message.origin,
+        &merkle_tree_hook,
+        &root,
+        index,
+        &message.id(),
+    );
+    Ok((digest, MESSAGE_ID_SIGNATURES_OFFSET))
+}
+
+fn merkle_root_digest(
+    metadata: &[u8],
+    message: &HyperlaneMessage,
+) -> Result<([u8; 32], usize), VerifyError> {
+    let message_index = read_u32(metadata, MERKLE_ROOT_MESSAGE_INDEX_OFFSET)?;
+    let signed_index = read_u32(metadata, MERKLE_ROOT_SIGNED_INDEX_OFFSET)?;
+    if message_index > signed_index {
+        return Err(VerifyError::InvalidMerkleIndex {
+            message_index,
+            signed_index,
+        });
+    }
+
+    let merkle_tree_hook = read_h256(metadata, 0)?;
+    let signed_message_id = read_h256(metadata, MERKLE_ROOT_MESSAGE_ID_OFFSET)?;
+    let mut proof = [[0u8; 32]; TREE_DEPTH];
+    for (i, node) in proof.iter_mut().enumerate() {
+        *node = read_h256(metadata, MERKLE_ROOT_PROOF_OFFSET + 32 * i)?;
+    }
+    let signed_root = branch_root(&message.id(), &proof, message_index);
+
+    let digest = checkpoint_digest(
+        message.origin,
+        &merkle_tree_hook,
+        &signed_root,
+        signed_index,
+        &signed_message_id,
+    );
+    Ok((digest, MERKLE_ROOT_SIGNATURES_OFFSET))
+}
@@ -1 +1,16 @@
-
+pub mod checkpoint;
+pub mod checkpoint_syncer;
+pub mod config;
+pub mod contracts;
+pub mod ecdsa;
+pub mod hook;
+pub mod indexer;
+pub mod ism;
+pub mod message;
+pub mod process;
+pub mod relayer;
+pub mod signer;
+pub mod tx_watcher;
+pub mod utils;
+pub mod validator;
+pub mod warp;
@@ -0,0 +1,178 @@
+use std::{fmt, str::FromStr};
+
+use ethers::{types::Address, utils::keccak256};
+
+use cainome::cairo_serde::U256;
+use starknet::core::types::FieldElement;
+
+use crate::contracts::strk::{from_strk_message_bytes, mailbox, to_strk_message_bytes};
+
+/// Version of the messages built by the mailbox.
+pub const HYPERLANE_VERSION: u8 = 3;
+
+/// Size of the message header, preceding the body.
+pub const HEADER_LENGTH: usize = 77;
+
+/// A hyperlane message, as encoded and hashed by the mailboxes of every chain.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct HyperlaneMessage {
+    pub version: u8,
+    pub nonce: u32,
+    pub origin: u32,
+    pub sender: [u8; 32],
+    pub destination: u32,
+    pub recipient: [u8; 32],
+    pub body: Vec<u8>,
+}
+
+impl HyperlaneMessage {
+    /// Packed encoding of the message: fixed size big endian header followed by the body.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_LENGTH + self.body.len());
+        bytes.push(self.version);
+        bytes.extend_from_slice(&self.nonce.to_be_bytes());
+        bytes.extend_from_slice(&self.origin.to_be_bytes());
+        bytes.extend_from_slice(&self.sender);
+        bytes.extend_from_slice(&self.destination.to_be_bytes());
+        bytes.extend_from_slice(&self.recipient);
+        bytes.extend_from_slice(&self.body);
+        bytes
+    }
+
+    /// Decodes a message from its packed encoding, as emitted by the EVM mailbox `Dispatch` event.
+    pub fn from_bytes(bytes: &[u8]) -> eyre::Result<Self> {
+        if bytes.len() < HEADER_LENGTH {
+            eyre::bail!(
+                "message of {} bytes is shorter than its {HEADER_LENGTH} bytes header",
+                bytes.len()
+            );
+        }
+
+        let u32_at = |offset: usize| {
+            u32::from_be_bytes(bytes[offset..offset + 4].try_into().expect("4 bytes slice"))
+        };
+        let h256_at = |offset: usize| -> [u8; 32] {
+            bytes[offset..offset + 32]
+                .try_into()
+                .expect("32 bytes slice")
+        };
+
+        Ok(Self {
+            version: bytes[0],
+            nonce: u32_at(1),
+            origin: u32_at(5),
+            sender: h256_at(9),
+            destination: u32_at(41),
+            recipient: h256_at(45),
+            body: bytes[HEADER_LENGTH..].to_vec(),
+        })
+    }
+
+    /// Id of the message, the keccak256 hash of its encoding.
+    pub fn id(&self) -> [u8; 32] {
+        keccak256(self.to_bytes())
+    }
+}
+
+impl From<&mailbox::Message> for HyperlaneMessage {
+    fn from(message: &mailbox::Message) -> Self {
+        Self {
+            version: message.version,
+            nonce: message.nonce,
+            origin: message.origin,
+            sender: u256_to_bytes(&message.sender),
+            destination: message.destination,
+            recipient: u256_to_bytes(&message.recipient),
+            body: from_strk_message_bytes(&message.body),
+        }
+    }
+}
+
+impl From<&HyperlaneMessage> for mailbox::Message {
+    fn from(message: &HyperlaneMessage) -> Self {
+        Self {
+            version: message.version,
+            nonce: message.nonce,
+            origin: message.origin,
+            sender: U256::from_bytes_be(&message.sender),
+            destination: message.destination,
+            recipient: U256::from_bytes_be(&message.recipient),
+            body: to_strk_message_bytes(&message.body),
+        }
+    }
+}
+
+/// Address in the 32 bytes form messages carry across VMs: Starknet addresses as the big endian
+/// bytes of their felt, EVM addresses left padded with zeros.
+///
+/// Parsed from and serialized as a hex string, shorter values being left padded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HyperlaneAddress(pub [u8; 32]);
+
+impl FromStr for HyperlaneAddress {
+    type Err = eyre::Report;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let digits = value.trim_start_matches("0x");
+        let digits = if digits.len() % 2 == 1 {
+            format!("0{digits}")
+        } else {
+            digits.to_string()
+        };
+
+        let bytes = hex::decode(digits).map_err(|e| eyre::eyre!("invalid address {value}: {e}"))?;
+        if bytes.len() > 32 {
+            eyre::bail!("address {value} is longer than 32 bytes");
+        }
+
+        let mut address = [0u8; 32];
+        address[32 - bytes.len()..].copy_from_slice(&bytes);
+        Ok(Self(address))
+    }
+}
+
+impl fmt::Display for HyperlaneAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x{}", hex::encode(self.0))
+    }
+}
+
+impl serde::Serialize for HyperlaneAddress {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for HyperlaneAddress {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl From<[u8; 32]> for HyperlaneAddress {
+    fn from(value: [u8; 32]) -> Self {
+        Self(value)
+    }
+}
+
+impl From<Address> for HyperlaneAddress {
+    fn from(value: Address) -> Self {
+        let mut address = [0u8; 32];
+        address[12..].copy_from_slice(value.as_bytes());
+        Self(address)
+    }
+}
+
+impl From<FieldElement> for HyperlaneAddress {
+    fn from(value: FieldElement) -> Self {
+        Self(value.to_bytes_be())
+    }
+}
+
+pub(crate) fn u256_to_bytes(value: &U256) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    bytes[..16].copy_from_slice(&value.high.to_be_bytes());
+    bytes[16..].copy_from_slice(&value.low.to_be_bytes());
+    bytes
+}
@@ -0,0 +1,365 @@
+use std::future::Future;
+
+use starknet::{
+    accounts::{Account, AccountError, Call, ConnectedAccount},
+    core::types::{FieldElement, StarknetError},
+    providers::{Provider, ProviderError},
+};
+
+use crate::{
+    contracts::strk::{
+        mailbox::{mailbox, Message},
+        to_strk_message_bytes, StarknetAccount,
+    },
+    tx_watcher::{TxError, TxWatcher},
+};
+
+/// Mailbox reverts of an already delivered message.
+const ALREADY_DELIVERED_ERRORS: &[&str] = &["Mailbox: already delivered"];
+/// Mailbox reverts of a message the destination mailbox cannot process.
+const MESSAGE_ERRORS: &[&str] = &[
+    "Wrong hyperlane version",
+    "Unexpected destination",
+    "Size does not match msg body",
+];
+/// Mailbox and ISM reverts of metadata rejected by the recipient ISM, Starknet and EVM ones.
+const METADATA_ERRORS: &[&str] = &[
+    "Mailbox:ism verification failed",
+    "Mailbox: ISM verification failed",
+    "Size does not match metadata",
+    "Empty metadata",
+    "Invalid merkle index metadata",
+    "No MultisigISM treshold present",
+    "No match for given signature",
+    "Threshold not reached",
+    "Verification failed",
+    "Origin not found",
+    "Domain not found",
+    "Pausable: paused",
+];
+
+/// Fee policy of the `process` transactions.
+#[derive(Clone, Debug, PartialEq, serde::Deserialize)]
+pub struct FeeSettings {
+    /// Factor applied to the estimated fee to set the max fee of the transaction, a positive
+    /// number.
+    #[serde(
+        default = "default_fee_multiplier",
+        deserialize_with = "deserialize_fee_multiplier"
+    )]
+    pub multiplier: f64,
+    /// Upper bound of the max fee, in wei. Deliveries estimated above it are postponed.
+    #[serde(default)]
+    pub max_fee: Option<u128>,
+}
+
+fn default_fee_multiplier() -> f64 {
+    1.5
+}
+
+fn deserialize_fee_multiplier<'de, D>(deserializer: D) -> Result<f64, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let multiplier = <f64 as serde::Deserialize>::deserialize(deserializer)?;
+    check_fee_multiplier(multiplier).map_err(serde::de::Error::custom)
+}
+
+/// Checks that `multiplier` is a usable fee multiplier: finite and positive.
+pub fn check_fee_multiplier(multiplier: f64) -> Result<f64, String> {
+    if multiplier.is_finite() && multiplier > 0.0 {
+        Ok(multiplier)
+    } else {
+        Err(format!(
+            "fee multiplier must be a positive number, got {multiplier}"
+        ))
+    }
+}
+
+/// Parses a fee multiplier given on the command line, see [`check_fee_multiplier`].
+pub fn parse_fee_multiplier(value: &str) -> Result<f64, String> {
+    let multiplier = value
+        .parse()
+        .map_err(|e| format!("invalid fee multiplier {value}: {e}"))?;
+    check_fee_multiplier(multiplier)
+}
+
+impl Default for FeeSettings {
+    fn default() -> Self {
+        Self {
+            multiplier: default_fee_multiplier(),
+            max_fee: None,
+        }
+    }
+}
+
+impl FeeSettings {
+    /// Max fee of a transaction estimated at `estimated`, capped to the configured max fee.
+    pub fn max_fee(&self, estimated: u128) -> Result<u128, ProcessError> {
+        let max_fee = (estimated as f64 * self.multiplier).ceil() as u128;
+        match self.max_fee {
+            Some(cap) if estimated > cap => Err(ProcessError::FeeAboveCap { estimated, cap }),
+            Some(cap) => Ok(max_fee.min(cap)),
+            None => Ok(max_fee),
+        }
+    }
+}
+
+/// Failure of a delivery, either permanent or worth retrying, see [`ProcessError::is_permanent`].
+#[derive(Debug, thiserror::Error)]
+pub enum ProcessError {
+    #[error("message already delivered")]
+    AlreadyDelivered,
+    #[error("message rejected by the mailbox: {0}")]
+    InvalidMessage(String),
+    #[error("metadata rejected by the ISM: {0}")]
+    InvalidMetadata(String),
+    #[error("recipient reverted: {0}")]
+    RecipientReverted(String),
+    #[error("estimated fee {estimated} is above the {cap} max fee")]
+    FeeAboveCap { estimated: u128, cap: u128 },
+    #[error("fee too low: {0}")]
+    FeeTooLow(String),
+    #[error("invalid nonce: {0}")]
+    InvalidNonce(String),
+    #[error(transparent)]
+    Other(#[from] eyre::Report),
+}
+
+impl ProcessError {
+    /// Whether submitting the same delivery again cannot succeed.
+    pub fn is_permanent(&self) -> bool {
+        matches!(
+            self,
+            Self::AlreadyDelivered
+                | Self::InvalidMessage(_)
+                | Self::InvalidMetadata(_)
+                | Self::RecipientReverted(_)
+        )
+    }
+
+    /// Classifies the revert of a `process` execution, given its revert reason.
+    ///
+    /// Cairo reverts report the short string of the failed assertion either as is or hex encoded,
+    /// reverts not raised by the mailbox or the ISMs being attributed to the recipient.
+    pub fn from_revert(reason: &str) -> Self {
+        if contains_any(reason, ALREADY_DELIVERED_ERRORS) {
+            Self::AlreadyDelivered
+        } else if contains_any(reason, MESSAGE_ERRORS) {
+            Self::InvalidMessage(reason.to_string())
+        } else if contains_any(reason, METADATA_ERRORS) {
+            Self::InvalidMetadata(reason.to_string())
+        } else {
+            Self::RecipientReverted(reason.to_string())
+        }
+    }
+}
+
+fn contains_any(reason: &str, errors: &[&str]) -> bool {
+    errors
+        .iter()
+        .any(|error| reason.contains(error) || reason.contains(&hex::encode(error.as_bytes())))
+}
+
+impl<S> From<AccountError<S>> for ProcessError
+where
+    S: std::error::Error + Send + Sync + 'static,
+{
+    fn from(error: AccountError<S>) -> Self {
+        match error {
+            AccountError::Provider(ProviderError::StarknetError(error)) => match error {
+                StarknetError::InsufficientMaxFee | StarknetError::InsufficientAccountBalance => {
+                    Self::FeeTooLow(error.to_string())
+                }
+                StarknetError::InvalidTransactionNonce => Self::InvalidNonce(error.to_string()),
+                StarknetError::ContractError(_) | StarknetError::TransactionExecutionError(_) => {
+                    Self::from_revert(&format!("{error:?}"))
+                }
+                error => Self::Other(eyre::eyre!("{error}")),
+            },
+            error => Self::Other(eyre::eyre!("{error}")),
+        }
+    }
+}
+
+/// Fee estimated for a `process` transaction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProcessEstimate {
+    /// Overall fee estimated by the node, in wei.
+    pub overall_fee: u128,
+    /// Max fee of the transaction, after the fee policy.
+    pub max_fee: u128,
+}
+
+/// Simulates `process` with `estimate_fee` and derives the max fee of its transaction, failing
+/// with the classified revert when the execution fails.
+pub async fn estimate_process(
+    account: &StarknetAccount,
+    mailbox: FieldElement,
+    metadata: &[u8],
+    message: &Message,
+    fee: &FeeSettings,
+) -> Result<ProcessEstimate, ProcessError> {
+    estimate_process_batch(
+        account,
+        mailbox,
+        &[(metadata.to_vec(), message.clone())],
+        fee,
+    )
+    .await
+}
+
+/// Simulates the `process` of every `(metadata, message)` delivery in a single multicall
+/// transaction, see [`estimate_process`].
+pub async fn estimate_process_batch(
+    account: &StarknetAccount,
+    mailbox: FieldElement,
+    deliveries: &[(Vec<u8>, Message)],
+    fee: &FeeSettings,
+) -> Result<ProcessEstimate, ProcessError> {
+    let calls = process_calls(account, mailbox, deliveries);
+    let estimate = account.execute_v1(calls).estimate_fee().await?;
+
+    let overall_fee = u128::try_from(estimate.overall_fee)
+        .map_err(|_| eyre::eyre!("estimated fee {} overflows", estimate.overall_fee))?;
+    Ok(ProcessEstimate {
+        overall_fee,
+        max_fee: fee.max_fee(overall_fee)?,
+    })
+}
+
+/// Submits `process` with the max fee of its `estimate`, see [`estimate_process`], without
+/// waiting for its execution.
+///
+/// Returns the hash of the transaction.
+pub async fn submit_process(
+    account: &StarknetAccount,
+    mailbox: FieldElement,
+    metadata: &[u8],
+    message: &Message,
+    estimate: &ProcessEstimate,
+) -> Result<FieldElement, ProcessError> {
+    let deliveries = [(metadata.to_vec(), message.clone())];
+    submit_process_batch(account, mailbox, &deliveries, estimate).await
+}
+
+/// Submits the `process` of every `(metadata, message)` delivery in a single multicall
+/// transaction, with the max fee of its `estimate`.
+///
+/// Returns the hash of the transaction.
+pub async fn submit_process_batch(
+    account: &StarknetAccount,
+    mailbox: FieldElement,
+    deliveries: &[(Vec<u8>, Message)],
+    estimate: &ProcessEstimate,
+) -> Result<FieldElement, ProcessError> {
+    let res = account
+        .execute_v1(process_calls(account, mailbox, deliveries))
+        .max_fee(FieldElement::from(estimate.max_fee))
+        .send()
+        .await?;
+
+    Ok(res.transaction_hash)
+}
+
+fn process_calls(
+    account: &StarknetAccount,
+    mailbox: FieldElement,
+    deliveries: &[(Vec<u8>, Message)],
+) -> Vec<Call> {
+    let mailbox = mailbox::new(mailbox, account);
+    deliveries
+        .iter()
+        .map(|(metadata, message)| {
+            mailbox.process_getcall(&to_strk_message_bytes(metadata), message)
+        })
+        .collect()
+}
+
+/// Items of a batch split by its simulation: the sub-batches whose simulation succeeded along
+/// with its result, and the failed items.
+#[derive(Debug)]
+pub struct BatchPlan<T, R> {
+    pub batches: Vec<(Vec<T>, R)>,
+    pub failed: Vec<(T, ProcessError)>,
+}
+
+/// Simulates `items` as a single batch, bisecting the batches whose simulation fails permanently
+/// until the failing items are isolated. Items keep their order across the planned batches.
+///
+/// A failure worth retrying, such as an RPC outage or a fee too low, says nothing of the items
+/// simulated: the items left are failed with it instead of being simulated again.
+pub async fn bisect_batch<T, R, F, Fut>(items: Vec<T>, mut simulate: F) -> BatchPlan<T, R>
+where
+    T: Clone,
+    F: FnMut(Vec<T>) -> Fut,
+    Fut: Future<Output = Result<R, ProcessError>>,
+{
+    let mut plan = BatchPlan {
+        batches: vec![],
+        failed: vec![],
+    };
+
+    let mut remaining = vec![items];
+    while let Some(mut batch) = remaining.pop() {
+        if batch.is_empty() {
+            continue;
+        }
+
+        match simulate(batch.clone()).await {
+            Ok(result) => plan.batches.push((batch, result)),
+            Err(e) if !e.is_permanent() => {
+                let mut left: Vec<T> = batch
+                    .into_iter()
+                    .chain(remaining.drain(..).rev().flatten())
+                    .collect();
+                if left.len() == 1 {
+                    plan.failed.push((left.remove(0), e));
+                } else {
+                    let count = left.len();
+                    for item in left {
+                        let e = eyre::eyre!("batch of {count} items failed: {e}");
+                        plan.failed.push((item, e.into()));
+                    }
+                }
+                break;
+            }
+            Err(e) if batch.len() == 1 => plan.failed.push((batch.remove(0), e)),
+            Err(_) => {
+                let second = batch.split_off(batch.len() / 2);
+                remaining.push(second);
+                remaining.push(batch);
+            }
+        }
+    }
+
+    plan
+}
+
+/// Waits for the execution of a submitted `process` transaction, classifying its revert.
+pub async fn wait_process<P>(provider: &P, hash: FieldElement) -> Result<(), ProcessError>
+where
+    P: Provider + Sync,
+{
+    match TxWatcher::new(provider).wait(hash).await {
+        Ok(_) => Ok(()),
+        Err(TxError::Reverted { reason, .. }) => Err(ProcessError::from_revert(&reason)),
+        Err(e) => Err(ProcessError::Other(e.into())),
+    }
+}
+
+/// Submits `process` with the max fee of its `estimate`, then waits for its execution.
+///
+/// Returns the hash of the transaction.
+pub async fn process(
+    account: &StarknetAccount,
+    mailbox: FieldElement,
+    metadata: &[u8],
+    message: &Message,
+    estimate: &ProcessEstimate,
+) -> Result<FieldElement, ProcessError> {
+    let hash = submit_process(account, mailbox, metadata, message, estimate).await?;
+    wait_process(account.provider(), hash).await?;
+
+    Ok(hash)
+}
@@ -0,0 +1,317 @@
+use std::sync::Arc;
+
+use cainome::cairo_serde::U256;
+use ethers::{
+    providers::{Middleware, PendingTransaction},
+    types::{Address, H256},
+};
+use starknet::{
+    accounts::Account,
+    core::types::FieldElement,
+    providers::{AnyProvider, Provider},
+};
+
+use crate::{
+    config::{ChainConfig, EvmClient, Protocol},
+    contracts::{
+        eth,
+        strk::{
+            mailbox::{mailboxReader, Message},
+            StarknetAccount,
+        },
+    },
+    indexer::{evm_dispatched_messages, starknet_dispatched_messages},
+    ism::{inspect_evm_ism, inspect_recipient_ism, simulate_verify, IsmNode},
+    message::HyperlaneMessage,
+    process::{
+        bisect_batch, estimate_process_batch, submit_process_batch, wait_process, BatchPlan,
+        FeeSettings, ProcessError, ProcessEstimate,
+    },
+};
+
+use super::metadata::MetadataBuilders;
+
+/// Mailbox of a chain the relayer indexes messages from and delivers messages to.
+pub enum Chain {
+    Starknet(StarknetChain),
+    Ethereum(EvmChain),
+}
+
+pub struct StarknetChain {
+    pub domain: u32,
+    pub provider: AnyProvider,
+    /// Account processing the messages, unset for chains only relayed from.
+    pub account: Option<StarknetAccount>,
+    pub mailbox: FieldElement,
+    pub start_block: Option<u64>,
+    pub fee: FeeSettings,
+}
+
+pub struct EvmChain {
+    pub domain: u32,
+    pub client: Arc<EvmClient>,
+    pub mailbox: eth::mailbox::Mailbox<EvmClient>,
+    pub start_block: Option<u64>,
+}
+
+impl Chain {
+    /// Connects to the chain, with the configured account when there is one.
+    pub async fn from_config(config: &ChainConfig) -> eyre::Result<Self> {
+        Ok(match config.protocol {
+            Protocol::Starknet => Self::Starknet(StarknetChain {
+                domain: config.domain,
+                provider: config.provider()?,
+                account: match config.account {
+                    Some(_) => Some(config.account().await?),
+                    None => None,
+                },
+                mailbox: config.mailbox()?,
+                start_block: config.start_block,
+                fee: config.fee.clone(),
+            }),
+            Protocol::Ethereum => {
+                let client = Arc::new(config.evm_client().await?);
+                Self::Ethereum(EvmChain {
+                    domain: config.domain,
+                    mailbox: eth::mailbox::Mailbox::new(
+                        config.evm_address("mailbox")?,
+                        client.clone(),
+                    ),
+                    client,
+                    start_block: config.start_block,
+                })
+            }
+        })
+    }
+
+    pub fn domain(&self) -> u32 {
+        match self {
+            Self::Starknet(chain) => chain.domain,
+            Self::Ethereum(chain) => chain.domain,
+        }
+    }
+
+    pub fn start_block(&self) -> Option<u64> {
+        match self {
+            Self::Starknet(chain) => chain.start_block,
+            Self::Ethereum(chain) => chain.start_block,
+        }
+    }
+
+    pub async fn latest_block(&self) -> eyre::Result<u64> {
+        Ok(match self {
+            Self::Starknet(chain) => chain.provider.block_number().await?,
+            Self::Ethereum(chain) => chain.client.get_block_number().await?.as_u64(),
+        })
+    }
+
+    /// Messages dispatched by the mailbox between blocks `from` and `to`, both included.
+    pub async fn dispatched_messages(
+        &self,
+        from: u64,
+        to: u64,
+    ) -> eyre::Result<Vec<HyperlaneMessage>> {
+        match self {
+            Self::Starknet(chain) => {
+                starknet_dispatched_messages(&chain.provider, chain.mailbox, from, to).await
+            }
+            Self::Ethereum(chain) => evm_dispatched_messages(&chain.mailbox, from, to).await,
+        }
+    }
+
+    pub async fn delivered(&self, id: [u8; 32]) -> eyre::Result<bool> {
+        Ok(match self {
+            Self::Starknet(chain) => {
+                mailboxReader::new(chain.mailbox, &chain.provider)
+                    .delivered(&U256::from_bytes_be(&id))
+                    .call()
+                    .await?
+            }
+            Self::Ethereum(chain) => chain.mailbox.delivered(id).call().await?,
+        })
+    }
+
+    /// Builds the metadata of `message` for the recipient ISM and checks that the ISM would
+    /// accept it.
+    ///
+    /// Starknet ISMs are evaluated offline with the ISM simulator, while EVM deliveries are
+    /// simulated with an `eth_call` of `process`.
+    pub async fn prepare(
+        &self,
+        builders: &MetadataBuilders,
+        message: &HyperlaneMessage,
+    ) -> eyre::Result<Vec<u8>> {
+        let ism = match self {
+            Self::Starknet(chain) => chain.recipient_ism(message).await?,
+            Self::Ethereum(chain) => chain.recipient_ism(message).await?,
+        };
+        let metadata = builders.build(&ism, message).await?.ok_or(eyre::eyre!(
+            "metadata of {} ISM {:#064x} not available yet",
+            ism.module_type,
+            ism.address
+        ))?;
+
+        match self {
+            Self::Starknet(chain) => {
+                simulate_verify(&ism, &metadata, message, chain.account()?.address())?;
+            }
+            Self::Ethereum(chain) => {
+                chain
+                    .mailbox
+                    .process(metadata.clone().into(), message.to_bytes().into())
+                    .call()
+                    .await?;
+            }
+        }
+
+        Ok(metadata)
+    }
+
+    /// Splits `deliveries` into the batches submitted as a single transaction, designating
+    /// deliveries by their index.
+    ///
+    /// Starknet deliveries are simulated as one multicall, bisected while its simulation fails
+    /// so that the failing deliveries are isolated and the other ones still batched. EVM
+    /// mailboxes process a single message per transaction.
+    pub async fn plan_batch(
+        &self,
+        deliveries: &[(Vec<u8>, HyperlaneMessage)],
+    ) -> BatchPlan<usize, Option<ProcessEstimate>> {
+        match self {
+            Self::Starknet(chain) => {
+                bisect_batch(
+                    (0..deliveries.len()).collect(),
+                    move |indices: Vec<usize>| async move {
+                        let batch: Vec<(Vec<u8>, Message)> = indices
+                            .iter()
+                            .map(|i| (deliveries[*i].0.clone(), (&deliveries[*i].1).into()))
+                            .collect();
+                        let estimate = estimate_process_batch(
+                            chain.account()?,
+                            chain.mailbox,
+                            &batch,
+                            &chain.fee,
+                        )
+                        .await?;
+
+                        Ok(Some(estimate))
+                    },
+                )
+                .await
+            }
+            Self::Ethereum(_) => BatchPlan {
+                batches: (0..deliveries.len()).map(|i| (vec![i], None)).collect(),
+                failed: vec![],
+            },
+        }
+    }
+
+    /// Submits `process` for every delivery in a single transaction without waiting for its
+    /// execution, returning the transaction hash.
+    ///
+    /// Starknet transactions are simulated first unless `estimate` is given, their max fee
+    /// following the fee policy of the chain.
+    pub async fn submit_batch(
+        &self,
+        deliveries: &[(Vec<u8>, HyperlaneMessage)],
+        estimate: Option<ProcessEstimate>,
+    ) -> Result<[u8; 32], ProcessError> {
+        match self {
+            Self::Starknet(chain) => {
+                let account = chain.account()?;
+                let deliveries: Vec<(Vec<u8>, Message)> = deliveries
+                    .iter()
+                    .map(|(metadata, message)| (metadata.clone(), message.into()))
+                    .collect();
+                let estimate = match estimate {
+                    Some(estimate) => estimate,
+                    None => {
+                        estimate_process_batch(account, chain.mailbox, &deliveries, &chain.fee)
+                            .await?
+                    }
+                };
+                let hash =
+                    submit_process_batch(account, chain.mailbox, &deliveries, &estimate).await?;
+
+                Ok(hash.to_bytes_be())
+            }
+            Self::Ethereum(chain) => {
+                let [(metadata, message)] = deliveries else {
+                    return Err(eyre::eyre!(
+                        "EVM mailboxes process a single message per transaction, got {}",
+                        deliveries.len()
+                    )
+                    .into());
+                };
+                let call = chain
+                    .mailbox
+                    .process(metadata.to_vec().into(), message.to_bytes().into());
+                let pending = call
+                    .send()
+                    .await
+                    .map_err(|e| match e.decode_revert::<String>() {
+                        Some(reason) => ProcessError::from_revert(&reason),
+                        None => ProcessError::Other(e.into()),
+                    })?;
+
+                Ok(pending.tx_hash().0)
+            }
+        }
+    }
+
+    /// Waits for the execution of the `process` transaction `hash`.
+    pub async fn confirm(&self, hash: [u8; 32]) -> Result<(), ProcessError> {
+        match self {
+            Self::Starknet(chain) => {
+                let hash = FieldElement::from_bytes_be(&hash)
+                    .map_err(|_| eyre::eyre!("invalid transaction hash {}", hex::encode(hash)))?;
+                wait_process(&chain.provider, hash).await
+            }
+            Self::Ethereum(chain) => {
+                let receipt = PendingTransaction::new(H256(hash), chain.client.inner())
+                    .await
+                    .map_err(|e| ProcessError::Other(e.into()))?
+                    .ok_or(eyre::eyre!("process transaction dropped"))?;
+                if receipt.status != Some(1.into()) {
+                    return Err(ProcessError::Other(eyre::eyre!(
+                        "process transaction {:?} reverted",
+                        receipt.transaction_hash
+                    )));
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+impl StarknetChain {
+    fn account(&self) -> eyre::Result<&StarknetAccount> {
+        self.account.as_ref().ok_or(eyre::eyre!(
+            "no account configured to process messages on domain {}",
+            self.domain
+        ))
+    }
+
+    async fn recipient_ism(&self, message: &HyperlaneMessage) -> eyre::Result<IsmNode> {
+        inspect_recipient_ism(
+            &self.provider,
+            self.mailbox,
+            &U256::from_bytes_be(&message.recipient),
+            Some(message.origin),
+        )
+        .await
+    }
+}
+
+impl EvmChain {
+    async fn recipient_ism(&self, message: &HyperlaneMessage) -> eyre::Result<IsmNode> {
+        let ism = self
+            .mailbox
+            .recipient_ism(Address::from_slice(&message.recipient[12..]))
+            .call()
+            .await?;
+
+        inspect_evm_ism(self.client.clone(), ism, message).await
+    }
+}
@@ -0,0 +1,161 @@
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+use ethers::types::Address;
+use starknet::{core::types::FieldElement, providers::AnyProvider};
+
+use crate::{
+    checkpoint::SignedCheckpoint,
+    checkpoint_syncer::{syncer_from_location, CheckpointSyncer},
+    utils::BoxFuture,
+    validator::announced_storage_locations,
+};
+
+use super::metadata::CheckpointSource;
+
+/// Storage locations announced by the validators of an origin.
+pub trait StorageLocationSource: Send + Sync {
+    /// Storage locations `validator` announced, oldest first.
+    fn announced_locations(&self, validator: Address) -> BoxFuture<'_, eyre::Result<Vec<String>>>;
+}
+
+/// `validator_announce` contract of a Starknet origin.
+pub struct StarknetValidatorAnnounce {
+    pub provider: AnyProvider,
+    pub address: FieldElement,
+}
+
+impl StarknetValidatorAnnounce {
+    pub fn new(provider: AnyProvider, address: FieldElement) -> Self {
+        Self { provider, address }
+    }
+}
+
+impl StorageLocationSource for StarknetValidatorAnnounce {
+    fn announced_locations(&self, validator: Address) -> BoxFuture<'_, eyre::Result<Vec<String>>> {
+        Box::pin(announced_storage_locations(
+            &self.provider,
+            self.address,
+            validator,
+        ))
+    }
+}
+
+/// Checkpoints of the validators, read from the storage they announced in the
+/// `validator_announce` contract of their origin.
+///
+/// The latest announced location a backend exists for is used, `file://` and `s3://` ones being
+/// supported. The storage of each validator is resolved once, then kept for the next reads.
+#[derive(Default)]
+pub struct AnnouncedCheckpoints {
+    announces: BTreeMap<u32, Box<dyn StorageLocationSource>>,
+    s3_endpoint: Option<String>,
+    syncers: Mutex<BTreeMap<(u32, Address), Arc<dyn CheckpointSyncer>>>,
+}
+
+impl AnnouncedCheckpoints {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Resolves the validators of `origin` with its `validator_announce` at `address`.
+    pub fn with_validator_announce(
+        self,
+        origin: u32,
+        provider: AnyProvider,
+        address: FieldElement,
+    ) -> Self {
+        self.with_announced_locations(origin, StarknetValidatorAnnounce::new(provider, address))
+    }
+
+    /// Resolves the validators of `origin` with the storage locations `source` reports.
+    pub fn with_announced_locations(
+        mut self,
+        origin: u32,
+        source: impl StorageLocationSource + 'static,
+    ) -> Self {
+        self.announces.insert(origin, Box::new(source));
+        self
+    }
+
+    /// Sends the S3 requests to `endpoint`, e.g. a local S3 compatible server, instead of AWS.
+    pub fn with_s3_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.s3_endpoint = Some(endpoint.into());
+        self
+    }
+
+    /// Reads the checkpoints of `validator` on `origin` from `syncer`, whatever it announced.
+    pub fn with_syncer(
+        self,
+        origin: u32,
+        validator: Address,
+        syncer: Arc<dyn CheckpointSyncer>,
+    ) -> Self {
+        self.syncers
+            .lock()
+            .unwrap()
+            .insert((origin, validator), syncer);
+        self
+    }
+
+    /// Storage of `validator` on `origin`, unset while it announced none.
+    pub async fn syncer(
+        &self,
+        origin: u32,
+        validator: Address,
+    ) -> eyre::Result<Option<Arc<dyn CheckpointSyncer>>> {
+        if let Some(syncer) = self.syncers.lock().unwrap().get(&(origin, validator)) {
+            return Ok(Some(syncer.clone()));
+        }
+
+        let announce = self.announces.get(&origin).ok_or(eyre::eyre!(
+            "no validator_announce configured for origin {origin}"
+        ))?;
+        let locations = announce.announced_locations(validator).await?;
+        for location in locations.iter().rev() {
+            match syncer_from_location(location, self.s3_endpoint.as_deref()) {
+                Ok(syncer) => {
+                    self.syncers
+                        .lock()
+                        .unwrap()
+                        .insert((origin, validator), syncer.clone());
+                    return Ok(Some(syncer));
+                }
+                Err(e) => println!("Skipping storage location of validator {validator:?}: {e}"),
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+impl CheckpointSource for AnnouncedCheckpoints {
+    fn latest_index(
+        &self,
+        origin: u32,
+        validator: Address,
+    ) -> BoxFuture<'_, eyre::Result<Option<u32>>> {
+        Box::pin(async move {
+            match self.syncer(origin, validator).await? {
+                Some(syncer) => syncer.latest_index().await,
+                None => Ok(None),
+            }
+        })
+    }
+
+    fn fetch_checkpoint(
+        &self,
+        origin: u32,
+        validator: Address,
+        index: u32,
+    ) -> BoxFuture<'_, eyre::Result<Option<SignedCheckpoint>>> {
+        Box::pin(async move {
+            match self.syncer(origin, validator).await? {
+                Some(syncer) => syncer.fetch_checkpoint(index).await,
+                None => Ok(None),
+            }
+        })
+    }
+}
@@ -0,0 +1,451 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use ethers::types::Address;
+use starknet::core::types::FieldElement;
+
+use crate::{
+    checkpoint::{branch_root, CheckpointWithMessageId, SignedCheckpoint, TREE_DEPTH},
+    ecdsa::{normalize_signature, recover_signer, SIGNATURE_LENGTH},
+    ism::{IsmKind, IsmNode, ModuleType},
+    message::HyperlaneMessage,
+    utils::BoxFuture,
+};
+
+/// Builds the metadata an ISM verifies a message with.
+///
+/// Builders return `None` when the metadata is not available yet, e.g. before enough validators
+/// signed the checkpoint of the message, and an error when it can never be built.
+pub trait MetadataBuilder: Send + Sync {
+    /// Builds the metadata of `message` for `ism`, building the metadata of the nested ISMs
+    /// with `builders`.
+    fn build<'a>(
+        &'a self,
+        builders: &'a MetadataBuilders,
+        ism: &'a IsmNode,
+        message: &'a HyperlaneMessage,
+    ) -> BoxFuture<'a, eyre::Result<Option<Vec<u8>>>>;
+}
+
+/// Checkpoints signed by the validators of the origin chains.
+pub trait CheckpointSource: Send + Sync {
+    /// Highest checkpoint index `validator` signed on `origin`, unset when it signed none.
+    fn latest_index(
+        &self,
+        origin: u32,
+        validator: Address,
+    ) -> BoxFuture<'_, eyre::Result<Option<u32>>>;
+
+    /// Checkpoint `validator` signed at `index` on `origin`, unset when it did not sign it.
+    fn fetch_checkpoint(
+        &self,
+        origin: u32,
+        validator: Address,
+        index: u32,
+    ) -> BoxFuture<'_, eyre::Result<Option<SignedCheckpoint>>>;
+}
+
+/// Merkle trees of the messages dispatched by the origin mailboxes.
+pub trait MerkleTreeSource: Send + Sync {
+    /// Index of the leaf of message `id` in the tree of `origin`, unset when not inserted yet.
+    fn leaf_index(&self, origin: u32, id: [u8; 32]) -> BoxFuture<'_, eyre::Result<Option<u32>>>;
+
+    /// Proof of leaf `leaf_index` against the root of the tree of `origin` when it held the
+    /// leaves up to `root_index` included.
+    fn proof(
+        &self,
+        origin: u32,
+        leaf_index: u32,
+        root_index: u32,
+    ) -> BoxFuture<'_, eyre::Result<[[u8; 32]; TREE_DEPTH]>>;
+}
+
+/// Metadata builders, selected by ISM address first, then by the module type of the ISM.
+pub struct MetadataBuilders {
+    by_module_type: BTreeMap<ModuleType, Arc<dyn MetadataBuilder>>,
+    by_ism: BTreeMap<FieldElement, Arc<dyn MetadataBuilder>>,
+}
+
+impl Default for MetadataBuilders {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MetadataBuilders {
+    /// Builders of the ISMs that do not read validator signatures: routing, aggregation and the
+    /// ISMs of the null module type (noop, pausable and trusted relayer).
+    pub fn new() -> Self {
+        let mut builders = Self {
+            by_module_type: BTreeMap::new(),
+            by_ism: BTreeMap::new(),
+        };
+        builders.register(ModuleType::Routing, Arc::new(RoutingMetadataBuilder));
+        builders.register(
+            ModuleType::Aggregation,
+            Arc::new(AggregationMetadataBuilder),
+        );
+        builders.register(ModuleType::Null, Arc::new(NullMetadataBuilder));
+        builders
+    }
+
+    /// Adds the builders of the message id and merkle root multisig ISMs, reading the validator
+    /// signatures from `checkpoints` and the message leaves from `trees`.
+    pub fn with_multisig(
+        mut self,
+        checkpoints: Arc<dyn CheckpointSource>,
+        trees: Arc<dyn MerkleTreeSource>,
+    ) -> Self {
+        self.register(
+            ModuleType::MessageIdMultisig,
+            Arc::new(MessageIdMultisigMetadataBuilder {
+                checkpoints: checkpoints.clone(),
+                trees: trees.clone(),
+            }),
+        );
+        self.register(
+            ModuleType::MerkleRootMultisig,
+            Arc::new(MerkleRootMultisigMetadataBuilder { checkpoints, trees }),
+        );
+        self
+    }
+
+    /// Sets the builder of the ISMs of `module_type`.
+    pub fn register(&mut self, module_type: ModuleType, builder: Arc<dyn MetadataBuilder>) {
+        self.by_module_type.insert(module_type, builder);
+    }
+
+    /// Sets the builder of the ISM deployed at `address`, taking precedence over the builder of
+    /// its module type.
+    pub fn register_ism(&mut self, address: FieldElement, builder: Arc<dyn MetadataBuilder>) {
+        self.by_ism.insert(address, builder);
+    }
+
+    /// Builds the metadata of `message` for `ism` with the builder registered for it.
+    pub fn build<'a>(
+        &'a self,
+        ism: &'a IsmNode,
+        message: &'a HyperlaneMessage,
+    ) -> BoxFuture<'a, eyre::Result<Option<Vec<u8>>>> {
+        Box::pin(async move {
+            let builder = self
+                .by_ism
+                .get(&ism.address)
+                .or(self.by_module_type.get(&ism.module_type))
+                .ok_or(eyre::eyre!(
+                    "no metadata builder for {} ISM {:#064x}",
+                    ism.module_type,
+                    ism.address
+                ))?;
+
+            builder.build(self, ism, message).await
+        })
+    }
+}
+
+/// Builds the metadata of the ISM routing the origin of the message.
+pub struct RoutingMetadataBuilder;
+
+impl MetadataBuilder for RoutingMetadataBuilder {
+    fn build<'a>(
+        &'a self,
+        builders: &'a MetadataBuilders,
+        ism: &'a IsmNode,
+        message: &'a HyperlaneMessage,
+    ) -> BoxFuture<'a, eyre::Result<Option<Vec<u8>>>> {
+        Box::pin(async move {
+            let IsmKind::Routing { routes, .. } = &ism.kind else {
+                eyre::bail!("ISM {:#064x} is not a routing ISM", ism.address);
+            };
+            let route = routes
+                .iter()
+                .find(|route| route.origin == message.origin)
+                .ok_or(eyre::eyre!(
+                    "routing ISM {:#064x} has no route for origin {}",
+                    ism.address,
+                    message.origin
+                ))?;
+
+            builders.build(&route.ism, message).await
+        })
+    }
+}
+
+/// Builds the metadata of the first modules of an aggregation ISM reaching its threshold.
+///
+/// The module metadata is laid out after a table of `(start, end)` ranges, one per module, and
+/// right padded to 16 bytes as the contract reads it by chunks of 16 bytes.
+pub struct AggregationMetadataBuilder;
+
+impl MetadataBuilder for AggregationMetadataBuilder {
+    fn build<'a>(
+        &'a self,
+        builders: &'a MetadataBuilders,
+        ism: &'a IsmNode,
+        message: &'a HyperlaneMessage,
+    ) -> BoxFuture<'a, eyre::Result<Option<Vec<u8>>>> {
+        Box::pin(async move {
+            let IsmKind::Aggregation { threshold, modules } = &ism.kind else {
+                eyre::bail!("ISM {:#064x} is not an aggregation ISM", ism.address);
+            };
+
+            let mut built = vec![None; modules.len()];
+            let mut count = 0;
+            let mut first_error = None;
+            for (index, module) in modules.iter().enumerate() {
+                if count == *threshold as usize {
+                    break;
+                }
+
+                // the other modules may still reach the threshold
+                match builders.build(module, message).await {
+                    Ok(Some(metadata)) => {
+                        built[index] = Some(metadata);
+                        count += 1;
+                    }
+                    Ok(None) => {}
+                    Err(e) => {
+                        first_error.get_or_insert(e);
+                    }
+                }
+            }
+
+            if count < *threshold as usize {
+                return match first_error {
+                    Some(e) => Err(e),
+                    None => Ok(None),
+                };
+            }
+
+            let mut ranges = Vec::with_capacity(modules.len() * 8);
+            let mut data = vec![];
+            let table_size = (modules.len() * 8) as u32;
+            for metadata in built {
+                match metadata {
+                    Some(mut metadata) => {
+                        metadata.resize(metadata.len().next_multiple_of(16), 0);
+                        let start = table_size + data.len() as u32;
+                        let end = start + metadata.len() as u32;
+                        ranges.extend_from_slice(&start.to_be_bytes());
+                        ranges.extend_from_slice(&end.to_be_bytes());
+                        data.extend(metadata);
+                    }
+                    None => ranges.extend_from_slice(&[0u8; 8]),
+                }
+            }
+
+            ranges.extend(data);
+            Ok(Some(ranges))
+        })
+    }
+}
+
+/// Builds the empty metadata of the ISMs of the null module type, which do not read it.
+pub struct NullMetadataBuilder;
+
+impl MetadataBuilder for NullMetadataBuilder {
+    fn build<'a>(
+        &'a self,
+        _builders: &'a MetadataBuilders,
+        _ism: &'a IsmNode,
+        _message: &'a HyperlaneMessage,
+    ) -> BoxFuture<'a, eyre::Result<Option<Vec<u8>>>> {
+        Box::pin(async { Ok(Some(vec![])) })
+    }
+}
+
+/// Builds the `messageid_multisig_ism` metadata: merkle tree hook, root, index and the signatures
+/// of the checkpoint of the message.
+pub struct MessageIdMultisigMetadataBuilder {
+    pub checkpoints: Arc<dyn CheckpointSource>,
+    pub trees: Arc<dyn MerkleTreeSource>,
+}
+
+impl MetadataBuilder for MessageIdMultisigMetadataBuilder {
+    fn build<'a>(
+        &'a self,
+        _builders: &'a MetadataBuilders,
+        ism: &'a IsmNode,
+        message: &'a HyperlaneMessage,
+    ) -> BoxFuture<'a, eyre::Result<Option<Vec<u8>>>> {
+        Box::pin(async move {
+            let (threshold, validators) = multisig_config(ism)?;
+            let id = message.id();
+            let Some(leaf_index) = self.trees.leaf_index(message.origin, id).await? else {
+                return Ok(None);
+            };
+
+            let quorum = signed_quorum(
+                self.checkpoints.as_ref(),
+                message.origin,
+                leaf_index,
+                validators,
+                threshold,
+                |value| value.message_id == id,
+            )
+            .await;
+            let Some((value, signatures)) = quorum else {
+                return Ok(None);
+            };
+
+            let mut metadata = Vec::with_capacity(68 + SIGNATURE_LENGTH * signatures.len());
+            metadata.extend_from_slice(&value.checkpoint.merkle_tree_hook);
+            metadata.extend_from_slice(&value.checkpoint.root);
+            metadata.extend_from_slice(&value.checkpoint.index.to_be_bytes());
+            for signature in signatures {
+                metadata.extend_from_slice(&signature);
+            }
+            Ok(Some(metadata))
+        })
+    }
+}
+
+/// Builds the `merkleroot_multisig_ism` metadata: merkle tree hook, message index, signed message
+/// id, proof of the message against the signed root, signed index and signatures.
+///
+/// Signs with the highest checkpoint signed by a threshold of validators, falling back to the
+/// checkpoint of the message itself.
+pub struct MerkleRootMultisigMetadataBuilder {
+    pub checkpoints: Arc<dyn CheckpointSource>,
+    pub trees: Arc<dyn MerkleTreeSource>,
+}
+
+impl MetadataBuilder for MerkleRootMultisigMetadataBuilder {
+    fn build<'a>(
+        &'a self,
+        _builders: &'a MetadataBuilders,
+        ism: &'a IsmNode,
+        message: &'a HyperlaneMessage,
+    ) -> BoxFuture<'a, eyre::Result<Option<Vec<u8>>>> {
+        Box::pin(async move {
+            let (threshold, validators) = multisig_config(ism)?;
+            let id = message.id();
+            let Some(leaf_index) = self.trees.leaf_index(message.origin, id).await? else {
+                return Ok(None);
+            };
+
+            let mut latest = vec![];
+            for validator in validators {
+                if let Ok(Some(index)) = self
+                    .checkpoints
+                    .latest_index(message.origin, *validator)
+                    .await
+                {
+                    latest.push(index);
+                }
+            }
+            latest.retain(|index| *index >= leaf_index);
+            latest.sort_unstable_by(|a, b| b.cmp(a));
+
+            let mut candidates = vec![];
+            if let Some(index) = latest.get(threshold - 1) {
+                candidates.push(*index);
+            }
+            if !candidates.contains(&leaf_index) {
+                candidates.push(leaf_index);
+            }
+
+            for signed_index in candidates {
+                let quorum = signed_quorum(
+                    self.checkpoints.as_ref(),
+                    message.origin,
+                    signed_index,
+                    validators,
+                    threshold,
+                    |value| signed_index != leaf_index || value.message_id == id,
+                )
+                .await;
+                let Some((value, signatures)) = quorum else {
+                    continue;
+                };
+
+                let proof = self
+                    .trees
+                    .proof(message.origin, leaf_index, signed_index)
+                    .await?;
+                if branch_root(&id, &proof, leaf_index) != value.checkpoint.root {
+                    eyre::bail!(
+                        "proof of message {} does not match the root signed at index {signed_index}",
+                        hex::encode(id)
+                    );
+                }
+
+                let mut metadata = Vec::with_capacity(1096 + SIGNATURE_LENGTH * signatures.len());
+                metadata.extend_from_slice(&value.checkpoint.merkle_tree_hook);
+                metadata.extend_from_slice(&leaf_index.to_be_bytes());
+                metadata.extend_from_slice(&value.message_id);
+                for node in proof {
+                    metadata.extend_from_slice(&node);
+                }
+                metadata.extend_from_slice(&signed_index.to_be_bytes());
+                for signature in signatures {
+                    metadata.extend_from_slice(&signature);
+                }
+                return Ok(Some(metadata));
+            }
+
+            Ok(None)
+        })
+    }
+}
+
+fn multisig_config(ism: &IsmNode) -> eyre::Result<(usize, &[Address])> {
+    match &ism.kind {
+        IsmKind::Multisig {
+            threshold,
+            validators,
+        } if *threshold > 0 => Ok((*threshold as usize, validators.as_slice())),
+        IsmKind::Multisig { .. } => {
+            eyre::bail!("multisig ISM {:#064x} has no threshold", ism.address)
+        }
+        _ => eyre::bail!("ISM {:#064x} is not a multisig ISM", ism.address),
+    }
+}
+
+/// Collects, in the order of `validators`, the signatures of the first checkpoint at `index`
+/// signed by `threshold` of them. Validators whose checkpoint cannot be fetched, is rejected by
+/// `accept` or is not signed by them are skipped.
+async fn signed_quorum<F>(
+    checkpoints: &dyn CheckpointSource,
+    origin: u32,
+    index: u32,
+    validators: &[Address],
+    threshold: usize,
+    accept: F,
+) -> Option<(CheckpointWithMessageId, Vec<[u8; SIGNATURE_LENGTH]>)>
+where
+    F: Fn(&CheckpointWithMessageId) -> bool,
+{
+    let mut signed: Vec<(CheckpointWithMessageId, Vec<[u8; SIGNATURE_LENGTH]>)> = vec![];
+    for validator in validators {
+        let Ok(Some(checkpoint)) = checkpoints
+            .fetch_checkpoint(origin, *validator, index)
+            .await
+        else {
+            continue;
+        };
+        let value = checkpoint.value;
+        if value.checkpoint.origin != origin || value.checkpoint.index != index || !accept(&value) {
+            continue;
+        }
+        let Ok(signature) = normalize_signature(&checkpoint.signature) else {
+            continue;
+        };
+        if recover_signer(&value.digest(), &signature) != Ok(*validator) {
+            continue;
+        }
+
+        let position = match signed.iter().position(|(v, _)| *v == value) {
+            Some(position) => position,
+            None => {
+                signed.push((value, vec![]));
+                signed.len() - 1
+            }
+        };
+        signed[position].1.push(signature);
+        if signed[position].1.len() == threshold {
+            return Some(signed.swap_remove(position));
+        }
+    }
+
+    None
+}
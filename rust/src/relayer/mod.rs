@@ -0,0 +1,432 @@
+mod chain;
+mod checkpoints;
+mod metadata;
+mod policy;
+mod store;
+mod trees;
+
+use std::{
+    collections::BTreeMap,
+    future::Future,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    message::HyperlaneMessage,
+    process::{ProcessError, ProcessEstimate},
+};
+
+pub use chain::*;
+pub use checkpoints::*;
+pub use metadata::*;
+pub use policy::*;
+pub use store::*;
+pub use trees::*;
+
+/// Tuning of the relayer loop.
+#[derive(Clone, Debug)]
+pub struct RelayerSettings {
+    /// Delay between two rounds of indexing and delivery.
+    pub poll_interval: Duration,
+    /// Maximum number of blocks indexed per round and chain, at least 1.
+    pub max_block_range: u64,
+    /// Delay before the first retry of a failed delivery, doubled after each failure.
+    pub retry_initial_delay: Duration,
+    /// Upper bound of the delay between two delivery attempts.
+    pub retry_max_delay: Duration,
+    /// Failed attempts after which a message is given up, unlimited when unset.
+    pub max_attempts: Option<u32>,
+    /// Maximum number of messages processed in a single Starknet transaction.
+    pub max_batch_size: usize,
+}
+
+impl Default for RelayerSettings {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_secs(5),
+            max_block_range: 1000,
+            retry_initial_delay: Duration::from_secs(10),
+            retry_max_delay: Duration::from_secs(3600),
+            max_attempts: None,
+            max_batch_size: 16,
+        }
+    }
+}
+
+impl RelayerSettings {
+    /// Delay before the next attempt of a delivery that failed `attempts` times.
+    pub fn retry_delay(&self, attempts: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempts.saturating_sub(1));
+        self.retry_initial_delay
+            .saturating_mul(factor)
+            .min(self.retry_max_delay)
+    }
+}
+
+/// Outcome of a delivery attempt.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Delivery {
+    /// Processed by the relayer in the transaction of this hash.
+    Processed([u8; 32]),
+    /// Already processed, by this relayer or another one.
+    AlreadyDelivered,
+}
+
+/// Relays the messages dispatched between the mailboxes of a set of chains: indexes the
+/// dispatched messages of every chain, then delivers the stored messages to their destination.
+///
+/// The indexing cursors and the delivery state of the messages are kept in a [`RelayerStore`],
+/// so that a restarted relayer resumes where it stopped.
+pub struct Relayer {
+    chains: BTreeMap<u32, Chain>,
+    store: RelayerStore,
+    settings: RelayerSettings,
+    builders: MetadataBuilders,
+    policy: RelayPolicy,
+}
+
+impl Relayer {
+    pub fn new(chains: Vec<Chain>, store: RelayerStore, settings: RelayerSettings) -> Self {
+        Self {
+            chains: chains.into_iter().map(|c| (c.domain(), c)).collect(),
+            store,
+            settings,
+            builders: MetadataBuilders::new(),
+            policy: RelayPolicy::default(),
+        }
+    }
+
+    /// Restricts the relayed messages to the ones accepted by `policy`.
+    pub fn with_policy(mut self, policy: RelayPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Replaces the metadata builders, which by default only cover the ISMs not reading
+    /// validator signatures.
+    pub fn with_metadata_builders(mut self, builders: MetadataBuilders) -> Self {
+        self.builders = builders;
+        self
+    }
+
+    pub fn store(&self) -> &RelayerStore {
+        &self.store
+    }
+
+    /// Relays messages forever, retrying the failed rounds at the next poll.
+    pub async fn run(&mut self) {
+        loop {
+            if let Err(e) = self.tick().await {
+                println!("Relayer round failed: {e}");
+            }
+            tokio::time::sleep(self.settings.poll_interval).await;
+        }
+    }
+
+    /// Runs a single round of indexing and delivery.
+    pub async fn tick(&mut self) -> eyre::Result<()> {
+        self.index().await;
+        self.deliver_due().await
+    }
+
+    /// Stores the messages dispatched since the last round to the known destinations, when the
+    /// relay policy accepts them. Chains failing to be indexed are retried at the next round
+    /// without holding back the other ones.
+    async fn index(&mut self) {
+        for (domain, chain) in &self.chains {
+            if let Err(e) = self.index_chain(*domain, chain).await {
+                println!("Indexing domain {domain} failed: {e}");
+            }
+        }
+    }
+
+    async fn index_chain(&self, domain: u32, chain: &Chain) -> eyre::Result<()> {
+        let latest = chain.latest_block().await?;
+        let from = match self.store.cursor(domain)? {
+            Some(cursor) => cursor,
+            None => chain.start_block().unwrap_or(latest),
+        };
+        if from > latest {
+            return Ok(());
+        }
+
+        let to = latest.min(from + self.settings.max_block_range.max(1) - 1);
+        let mut messages = vec![];
+        for message in chain.dispatched_messages(from, to).await? {
+            let id = format_id(&message.id());
+            if !self.chains.contains_key(&message.destination) {
+                println!(
+                    "Skipping message {id} from domain {domain} to unknown domain {}",
+                    message.destination
+                );
+                continue;
+            }
+
+            let decision = self.policy.decide(&message);
+            if !decision.is_relayed() {
+                println!("Skipping message {id} from domain {domain}: {decision}");
+                continue;
+            }
+
+            println!(
+                "Queued message {id} from domain {domain} to domain {}: {decision}",
+                message.destination
+            );
+            messages.push(message);
+        }
+
+        self.store.record_indexed(domain, &messages, to + 1)?;
+        Ok(())
+    }
+
+    /// Attempts the delivery of the messages whose next attempt is due, scheduling the retries
+    /// of the failed ones with an exponential backoff.
+    ///
+    /// The messages ready to be submitted are batched per destination, up to
+    /// `max_batch_size` messages per transaction.
+    async fn deliver_due(&mut self) -> eyre::Result<()> {
+        let now = unix_now();
+        let mut ready: BTreeMap<u32, Vec<(StoredMessage, Vec<u8>)>> = BTreeMap::new();
+        for stored in self.store.due_messages(now)? {
+            match self.prepare(&stored).await {
+                Ok(Prepared::Ready(metadata)) => ready
+                    .entry(stored.message.destination)
+                    .or_default()
+                    .push((stored, metadata)),
+                Ok(Prepared::Done(delivery)) => self.record(&stored, Ok(delivery), now)?,
+                Err(e) => self.record(&stored, Err(e), now)?,
+            }
+        }
+
+        for (domain, messages) in ready {
+            let Some(destination) = self.chains.get(&domain) else {
+                continue;
+            };
+            for chunk in messages.chunks(self.settings.max_batch_size.max(1)) {
+                let deliveries: Vec<_> = chunk
+                    .iter()
+                    .map(|(stored, metadata)| (metadata.clone(), stored.message.clone()))
+                    .collect();
+
+                let plan = destination.plan_batch(&deliveries).await;
+                for (index, e) in plan.failed {
+                    self.record(&chunk[index].0, Err(e), now)?;
+                }
+
+                // each batch is confirmed before submitting the next one, so that their
+                // transactions do not compete for the same nonce
+                for (indices, estimate) in plan.batches {
+                    let batch: Vec<_> = indices.iter().map(|i| deliveries[*i].clone()).collect();
+                    let results = self.deliver_batch(destination, &batch, estimate).await;
+                    for (i, result) in indices.into_iter().zip(results) {
+                        self.record(&chunk[i].0, result, now)?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Records the outcome of a delivery attempt, scheduling the retry of transient failures.
+    fn record(
+        &self,
+        stored: &StoredMessage,
+        result: Result<Delivery, ProcessError>,
+        now: u64,
+    ) -> eyre::Result<()> {
+        let id = stored.message.id();
+        match result {
+            Ok(Delivery::Processed(tx)) => {
+                self.store.set_delivered(&id, Some(&tx))?;
+                println!(
+                    "Delivered message {} in transaction {}",
+                    format_id(&id),
+                    format_id(&tx)
+                );
+            }
+            Ok(Delivery::AlreadyDelivered) | Err(ProcessError::AlreadyDelivered) => {
+                self.store.set_delivered(&id, None)?;
+                println!("Message {} already delivered", format_id(&id));
+            }
+            Err(e) if e.is_permanent() => {
+                self.store.set_failed(&id, &e.to_string())?;
+                println!("Dropping message {}: {e}", format_id(&id));
+            }
+            Err(e) => {
+                let attempts = stored.attempts + 1;
+                if self
+                    .settings
+                    .max_attempts
+                    .is_some_and(|max| attempts >= max)
+                {
+                    self.store.set_failed(&id, &e.to_string())?;
+                    println!(
+                        "Giving up message {} after {attempts} attempts: {e}",
+                        format_id(&id)
+                    );
+                    return Ok(());
+                }
+
+                let delay = self.settings.retry_delay(attempts);
+                self.store
+                    .schedule_retry(&id, &e.to_string(), now + delay.as_secs())?;
+                println!(
+                    "Failed to deliver message {} (attempt {attempts}), retrying in {}s: {e}",
+                    format_id(&id),
+                    delay.as_secs()
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Delivers a stored message to its destination mailbox: builds its metadata, checks it
+    /// against the recipient ISM, then submits `process` and waits for its confirmation.
+    ///
+    /// The progress is recorded in the store, so that the metadata already built is reused and a
+    /// transaction submitted before a restart is confirmed instead of being submitted again.
+    pub async fn deliver(&self, stored: &StoredMessage) -> Result<Delivery, ProcessError> {
+        let metadata = match self.prepare(stored).await? {
+            Prepared::Ready(metadata) => metadata,
+            Prepared::Done(delivery) => return Ok(delivery),
+        };
+
+        let destination = self.destination(&stored.message)?;
+        let mut results = self
+            .deliver_batch(destination, &[(metadata, stored.message.clone())], None)
+            .await;
+        results.remove(0)
+    }
+
+    /// Brings a stored message up to its submission: confirms the transaction submitted by a
+    /// previous attempt, checks whether it was delivered meanwhile, then builds its metadata.
+    async fn prepare(&self, stored: &StoredMessage) -> Result<Prepared, ProcessError> {
+        let message = &stored.message;
+        let id = message.id();
+        let destination = self.destination(message)?;
+
+        if let (MessageStatus::Submitted, Some(tx)) = (stored.status, stored.tx_hash) {
+            if destination.confirm(tx).await.is_ok() {
+                return Ok(Prepared::Done(Delivery::Processed(tx)));
+            }
+        }
+
+        if destination.delivered(id).await? {
+            return Ok(Prepared::Done(Delivery::AlreadyDelivered));
+        }
+
+        let metadata = match (&stored.status, &stored.metadata) {
+            (MessageStatus::MetadataReady | MessageStatus::Submitted, Some(metadata)) => {
+                metadata.clone()
+            }
+            _ => {
+                let metadata = destination.prepare(&self.builders, message).await?;
+                self.store.set_metadata(&id, &metadata)?;
+                metadata
+            }
+        };
+
+        Ok(Prepared::Ready(metadata))
+    }
+
+    /// Submits the `process` of a batch of messages in a single transaction, then waits for its
+    /// confirmation, returning the outcome of each delivery.
+    async fn deliver_batch(
+        &self,
+        destination: &Chain,
+        deliveries: &[(Vec<u8>, HyperlaneMessage)],
+        estimate: Option<ProcessEstimate>,
+    ) -> Vec<Result<Delivery, ProcessError>> {
+        let outcome = self.submit_batch(destination, deliveries, estimate).await;
+        batch_deliveries(deliveries, outcome, |(_, message)| {
+            destination.delivered(message.id())
+        })
+        .await
+    }
+
+    async fn submit_batch(
+        &self,
+        destination: &Chain,
+        deliveries: &[(Vec<u8>, HyperlaneMessage)],
+        estimate: Option<ProcessEstimate>,
+    ) -> Result<Delivery, ProcessError> {
+        let tx = destination.submit_batch(deliveries, estimate).await?;
+        for (_, message) in deliveries {
+            self.store.set_submitted(&message.id(), &tx)?;
+        }
+        destination.confirm(tx).await?;
+
+        Ok(Delivery::Processed(tx))
+    }
+
+    fn destination(&self, message: &HyperlaneMessage) -> eyre::Result<&Chain> {
+        self.chains.get(&message.destination).ok_or(eyre::eyre!(
+            "unknown destination domain {}",
+            message.destination
+        ))
+    }
+}
+
+/// Outcome of each delivery of a batch submitted as a single transaction, given the `outcome` of
+/// the transaction.
+///
+/// The failure of a batch cannot be attributed to one of its items, which are retried rather than
+/// given up. A batch reverting on an already delivered message does not tell which one either:
+/// the items `delivered` reports as processed are settled, the other ones being retried.
+pub async fn batch_deliveries<T, F, Fut>(
+    items: &[T],
+    outcome: Result<Delivery, ProcessError>,
+    mut delivered: F,
+) -> Vec<Result<Delivery, ProcessError>>
+where
+    F: FnMut(&T) -> Fut,
+    Fut: Future<Output = eyre::Result<bool>>,
+{
+    let e = match outcome {
+        Ok(delivery) => return items.iter().map(|_| Ok(delivery)).collect(),
+        Err(ProcessError::AlreadyDelivered) if items.len() == 1 => {
+            return vec![Ok(Delivery::AlreadyDelivered)]
+        }
+        Err(e) if items.len() == 1 => return vec![Err(e)],
+        Err(e) => e,
+    };
+
+    let mut results = vec![];
+    for item in items {
+        let result = match e {
+            ProcessError::AlreadyDelivered => match delivered(item).await {
+                Ok(true) => Ok(Delivery::AlreadyDelivered),
+                Ok(false) => Err(eyre::eyre!(
+                    "batch of {} messages reverted on another already delivered message",
+                    items.len()
+                )
+                .into()),
+                Err(e) => Err(e.into()),
+            },
+            _ => Err(eyre::eyre!("batch of {} messages failed: {e}", items.len()).into()),
+        };
+        results.push(result);
+    }
+    results
+}
+
+/// State of a due message once brought up to its submission.
+enum Prepared {
+    /// Metadata of the message, ready to be submitted.
+    Ready(Vec<u8>),
+    /// Delivered by a previous attempt or by another relayer.
+    Done(Delivery),
+}
+
+/// Current unix timestamp, in seconds.
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+fn format_id(id: &[u8; 32]) -> String {
+    format!("0x{}", hex::encode(id))
+}
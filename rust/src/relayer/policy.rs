@@ -0,0 +1,133 @@
+use std::{fmt, path::Path};
+
+use crate::message::{HyperlaneAddress, HyperlaneMessage};
+
+/// Set of messages, matching the messages whose fields are all listed. Unset fields match any
+/// value.
+///
+/// ```json
+/// { "origin_domains": [23448594], "senders": ["0x0000000000000000000000001234..."] }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MessageMatcher {
+    #[serde(default)]
+    pub origin_domains: Option<Vec<u32>>,
+    #[serde(default)]
+    pub destination_domains: Option<Vec<u32>>,
+    #[serde(default)]
+    pub senders: Option<Vec<HyperlaneAddress>>,
+    #[serde(default)]
+    pub recipients: Option<Vec<HyperlaneAddress>>,
+}
+
+impl MessageMatcher {
+    pub fn matches(&self, message: &HyperlaneMessage) -> bool {
+        fn listed<T: PartialEq>(list: &Option<Vec<T>>, value: &T) -> bool {
+            list.as_ref().is_none_or(|list| list.contains(value))
+        }
+
+        listed(&self.origin_domains, &message.origin)
+            && listed(&self.destination_domains, &message.destination)
+            && listed(&self.senders, &HyperlaneAddress(message.sender))
+            && listed(&self.recipients, &HyperlaneAddress(message.recipient))
+    }
+}
+
+/// Messages the relayer delivers, loaded from a JSON file:
+///
+/// ```json
+/// {
+///   "whitelist": [{ "recipients": ["0x..."] }],
+///   "blacklist": [{ "origin_domains": [1], "senders": ["0x..."] }],
+///   "max_body_size": 4096
+/// }
+/// ```
+///
+/// Blacklisted messages are skipped, then the ones whose body exceeds `max_body_size`. When a
+/// whitelist is set, only the messages it matches are relayed.
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RelayPolicy {
+    /// Messages relayed, all of them when unset.
+    #[serde(default)]
+    pub whitelist: Option<Vec<MessageMatcher>>,
+    /// Messages never relayed.
+    #[serde(default)]
+    pub blacklist: Vec<MessageMatcher>,
+    /// Maximum size of the relayed message bodies, in bytes.
+    #[serde(default)]
+    pub max_body_size: Option<usize>,
+}
+
+/// Decision of a [`RelayPolicy`] on a message, along with its reason.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PolicyDecision {
+    /// Relayed, matching the whitelist rule of this index, or without whitelist.
+    Relay {
+        rule: Option<usize>,
+    },
+    /// Matching the blacklist rule of this index.
+    Blacklisted {
+        rule: usize,
+    },
+    BodyTooLarge {
+        size: usize,
+        max: usize,
+    },
+    NotWhitelisted,
+}
+
+impl PolicyDecision {
+    pub fn is_relayed(&self) -> bool {
+        matches!(self, Self::Relay { .. })
+    }
+}
+
+impl fmt::Display for PolicyDecision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Relay { rule: Some(rule) } => write!(f, "matches whitelist rule {rule}"),
+            Self::Relay { rule: None } => write!(f, "no whitelist"),
+            Self::Blacklisted { rule } => write!(f, "matches blacklist rule {rule}"),
+            Self::BodyTooLarge { size, max } => {
+                write!(f, "body of {size} bytes exceeds the {max} bytes limit")
+            }
+            Self::NotWhitelisted => write!(f, "matches no whitelist rule"),
+        }
+    }
+}
+
+impl RelayPolicy {
+    /// Loads the policy from a JSON file.
+    pub fn load(path: impl AsRef<Path>) -> eyre::Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| eyre::eyre!("failed to read {}: {e}", path.display()))?;
+        serde_json::from_str(&content)
+            .map_err(|e| eyre::eyre!("invalid relay policy {}: {e}", path.display()))
+    }
+
+    pub fn decide(&self, message: &HyperlaneMessage) -> PolicyDecision {
+        if let Some(rule) = self.blacklist.iter().position(|m| m.matches(message)) {
+            return PolicyDecision::Blacklisted { rule };
+        }
+
+        if let Some(max) = self.max_body_size {
+            if message.body.len() > max {
+                return PolicyDecision::BodyTooLarge {
+                    size: message.body.len(),
+                    max,
+                };
+            }
+        }
+
+        match &self.whitelist {
+            Some(whitelist) => match whitelist.iter().position(|m| m.matches(message)) {
+                Some(rule) => PolicyDecision::Relay { rule: Some(rule) },
+                None => PolicyDecision::NotWhitelisted,
+            },
+            None => PolicyDecision::Relay { rule: None },
+        }
+    }
+}
@@ -0,0 +1,310 @@
+use std::{collections::BTreeMap, fmt, path::Path};
+
+use rusqlite::{params, Connection, OptionalExtension, Row};
+
+use crate::message::HyperlaneMessage;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS cursors (
+    domain INTEGER PRIMARY KEY,
+    next_block INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS messages (
+    id BLOB PRIMARY KEY,
+    origin INTEGER NOT NULL,
+    destination INTEGER NOT NULL,
+    nonce INTEGER NOT NULL,
+    message BLOB NOT NULL,
+    status TEXT NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt INTEGER NOT NULL DEFAULT 0,
+    metadata BLOB,
+    tx_hash BLOB,
+    last_error TEXT
+);
+CREATE INDEX IF NOT EXISTS messages_due ON messages (status, next_attempt);
+";
+
+const MESSAGE_COLUMNS: &str =
+    "message, status, attempts, next_attempt, metadata, tx_hash, last_error";
+
+/// Delivery state of a relayed message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MessageStatus {
+    /// Indexed, its metadata not built yet.
+    Pending,
+    /// Metadata built, not submitted yet.
+    MetadataReady,
+    /// `process` submitted, its execution not confirmed yet.
+    Submitted,
+    /// Processed by the destination mailbox.
+    Delivered,
+    /// Failed permanently, no more attempts are made.
+    Failed,
+}
+
+impl MessageStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::MetadataReady => "metadata_ready",
+            Self::Submitted => "submitted",
+            Self::Delivered => "delivered",
+            Self::Failed => "failed",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        Some(match value {
+            "pending" => Self::Pending,
+            "metadata_ready" => Self::MetadataReady,
+            "submitted" => Self::Submitted,
+            "delivered" => Self::Delivered,
+            "failed" => Self::Failed,
+            _ => return None,
+        })
+    }
+}
+
+impl fmt::Display for MessageStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Message tracked by the relayer along with its delivery state.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StoredMessage {
+    pub message: HyperlaneMessage,
+    pub status: MessageStatus,
+    /// Failed delivery attempts.
+    pub attempts: u32,
+    /// Unix timestamp, in seconds, before which the message is not retried.
+    pub next_attempt: u64,
+    pub metadata: Option<Vec<u8>>,
+    /// Hash of the last `process` transaction submitted.
+    pub tx_hash: Option<[u8; 32]>,
+    pub last_error: Option<String>,
+}
+
+impl StoredMessage {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        let bytes: Vec<u8> = row.get(0)?;
+        let status: String = row.get(1)?;
+        let tx_hash: Option<Vec<u8>> = row.get(5)?;
+
+        let invalid = |e: String| {
+            rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Blob, e.into())
+        };
+        Ok(Self {
+            message: HyperlaneMessage::from_bytes(&bytes).map_err(|e| invalid(e.to_string()))?,
+            status: MessageStatus::parse(&status)
+                .ok_or_else(|| invalid(format!("unknown message status {status}")))?,
+            attempts: row.get(2)?,
+            next_attempt: row.get(3)?,
+            metadata: row.get(4)?,
+            tx_hash: tx_hash
+                .map(|hash| hash.try_into())
+                .transpose()
+                .map_err(|_| invalid("transaction hash is not 32 bytes".to_string()))?,
+            last_error: row.get(6)?,
+        })
+    }
+}
+
+/// Durable state of the relayer, in a sqlite database: the next block to index per origin domain,
+/// and the indexed messages with their delivery state.
+pub struct RelayerStore {
+    conn: Connection,
+}
+
+impl RelayerStore {
+    /// Opens the database at `path`, creating it when missing.
+    pub fn open(path: impl AsRef<Path>) -> eyre::Result<Self> {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    /// Opens a database living in memory, lost when dropped.
+    pub fn in_memory() -> eyre::Result<Self> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(conn: Connection) -> eyre::Result<Self> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    /// Next block to index on `domain`, unset before its first indexing.
+    pub fn cursor(&self, domain: u32) -> eyre::Result<Option<u64>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT next_block FROM cursors WHERE domain = ?1",
+                params![domain],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    /// Stores the messages indexed on `domain` and moves its cursor to `next_block`, atomically so
+    /// that a restart neither skips nor duplicates messages.
+    ///
+    /// Returns the number of messages that were not known yet.
+    pub fn record_indexed(
+        &self,
+        domain: u32,
+        messages: &[HyperlaneMessage],
+        next_block: u64,
+    ) -> eyre::Result<usize> {
+        let tx = self.conn.unchecked_transaction()?;
+        let mut inserted = 0;
+        for message in messages {
+            inserted += tx.execute(
+                "INSERT OR IGNORE INTO messages (id, origin, destination, nonce, message, status)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    message.id().to_vec(),
+                    message.origin,
+                    message.destination,
+                    message.nonce,
+                    message.to_bytes(),
+                    MessageStatus::Pending.as_str(),
+                ],
+            )?;
+        }
+        tx.execute(
+            "INSERT INTO cursors (domain, next_block) VALUES (?1, ?2)
+             ON CONFLICT (domain) DO UPDATE SET next_block = excluded.next_block",
+            params![domain, next_block],
+        )?;
+        tx.commit()?;
+
+        Ok(inserted)
+    }
+
+    pub fn message(&self, id: &[u8; 32]) -> eyre::Result<Option<StoredMessage>> {
+        Ok(self
+            .conn
+            .query_row(
+                &format!("SELECT {MESSAGE_COLUMNS} FROM messages WHERE id = ?1"),
+                params![id.to_vec()],
+                StoredMessage::from_row,
+            )
+            .optional()?)
+    }
+
+    /// Messages waiting for their delivery whose next attempt is due at `now`, oldest first.
+    pub fn due_messages(&self, now: u64) -> eyre::Result<Vec<StoredMessage>> {
+        let mut statement = self.conn.prepare(&format!(
+            "SELECT {MESSAGE_COLUMNS} FROM messages
+             WHERE status IN (?1, ?2, ?3) AND next_attempt <= ?4
+             ORDER BY next_attempt, origin, nonce"
+        ))?;
+        let messages = statement
+            .query_map(
+                params![
+                    MessageStatus::Pending.as_str(),
+                    MessageStatus::MetadataReady.as_str(),
+                    MessageStatus::Submitted.as_str(),
+                    now,
+                ],
+                StoredMessage::from_row,
+            )?
+            .collect::<Result<_, _>>()?;
+
+        Ok(messages)
+    }
+
+    /// Number of messages in each status.
+    pub fn status_counts(&self) -> eyre::Result<BTreeMap<MessageStatus, u64>> {
+        let mut statement = self
+            .conn
+            .prepare("SELECT status, COUNT(*) FROM messages GROUP BY status")?;
+        let mut counts = BTreeMap::new();
+        for row in statement.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, u64>(1)?))
+        })? {
+            let (status, count) = row?;
+            if let Some(status) = MessageStatus::parse(&status) {
+                counts.insert(status, count);
+            }
+        }
+
+        Ok(counts)
+    }
+
+    pub fn set_metadata(&self, id: &[u8; 32], metadata: &[u8]) -> eyre::Result<()> {
+        self.update(
+            id,
+            "status = ?2, metadata = ?3",
+            params![id.to_vec(), MessageStatus::MetadataReady.as_str(), metadata],
+        )
+    }
+
+    pub fn set_submitted(&self, id: &[u8; 32], tx_hash: &[u8; 32]) -> eyre::Result<()> {
+        self.update(
+            id,
+            "status = ?2, tx_hash = ?3",
+            params![
+                id.to_vec(),
+                MessageStatus::Submitted.as_str(),
+                tx_hash.to_vec()
+            ],
+        )
+    }
+
+    /// Marks the message delivered, by the transaction `tx_hash` when relayed by this relayer.
+    pub fn set_delivered(&self, id: &[u8; 32], tx_hash: Option<&[u8; 32]>) -> eyre::Result<()> {
+        self.update(
+            id,
+            "status = ?2, tx_hash = COALESCE(?3, tx_hash), last_error = NULL",
+            params![
+                id.to_vec(),
+                MessageStatus::Delivered.as_str(),
+                tx_hash.map(|hash| hash.to_vec())
+            ],
+        )
+    }
+
+    /// Marks the message permanently failed.
+    pub fn set_failed(&self, id: &[u8; 32], error: &str) -> eyre::Result<()> {
+        self.update(
+            id,
+            "status = ?2, attempts = attempts + 1, last_error = ?3",
+            params![id.to_vec(), MessageStatus::Failed.as_str(), error],
+        )
+    }
+
+    /// Records a failed attempt and postpones the next one to `next_attempt`, keeping the metadata
+    /// already built.
+    pub fn schedule_retry(
+        &self,
+        id: &[u8; 32],
+        error: &str,
+        next_attempt: u64,
+    ) -> eyre::Result<()> {
+        self.update(
+            id,
+            "status = CASE WHEN metadata IS NULL THEN ?2 ELSE ?3 END,
+             attempts = attempts + 1, next_attempt = ?4, last_error = ?5",
+            params![
+                id.to_vec(),
+                MessageStatus::Pending.as_str(),
+                MessageStatus::MetadataReady.as_str(),
+                next_attempt,
+                error
+            ],
+        )
+    }
+
+    fn update(&self, id: &[u8; 32], set: &str, params: impl rusqlite::Params) -> eyre::Result<()> {
+        let updated = self
+            .conn
+            .execute(&format!("UPDATE messages SET {set} WHERE id = ?1"), params)?;
+        if updated == 0 {
+            eyre::bail!("unknown message 0x{}", hex::encode(id));
+        }
+
+        Ok(())
+    }
+}
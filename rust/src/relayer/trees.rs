@@ -0,0 +1,62 @@
+use std::collections::BTreeMap;
+
+use tokio::sync::Mutex;
+
+use crate::{checkpoint::TREE_DEPTH, indexer::MerkleTreeIndexer, utils::BoxFuture};
+
+use super::metadata::MerkleTreeSource;
+
+/// Merkle trees of the Starknet origins, indexed on demand: a message or root index not known
+/// yet triggers the indexing of the tree up to the latest block.
+#[derive(Default)]
+pub struct IndexedMerkleTrees {
+    indexers: BTreeMap<u32, Mutex<MerkleTreeIndexer>>,
+}
+
+impl IndexedMerkleTrees {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Serves the tree of the origin of `indexer`.
+    pub fn with_indexer(mut self, indexer: MerkleTreeIndexer) -> Self {
+        self.indexers
+            .insert(indexer.hook().origin(), Mutex::new(indexer));
+        self
+    }
+
+    fn indexer(&self, origin: u32) -> eyre::Result<&Mutex<MerkleTreeIndexer>> {
+        self.indexers.get(&origin).ok_or(eyre::eyre!(
+            "no merkle tree hook indexed for origin {origin}"
+        ))
+    }
+}
+
+impl MerkleTreeSource for IndexedMerkleTrees {
+    fn leaf_index(&self, origin: u32, id: [u8; 32]) -> BoxFuture<'_, eyre::Result<Option<u32>>> {
+        Box::pin(async move {
+            let mut indexer = self.indexer(origin)?.lock().await;
+            if indexer.tree().leaf_index(&id).is_none() {
+                indexer.sync().await?;
+            }
+
+            Ok(indexer.tree().leaf_index(&id))
+        })
+    }
+
+    fn proof(
+        &self,
+        origin: u32,
+        leaf_index: u32,
+        root_index: u32,
+    ) -> BoxFuture<'_, eyre::Result<[[u8; 32]; TREE_DEPTH]>> {
+        Box::pin(async move {
+            let mut indexer = self.indexer(origin)?.lock().await;
+            if root_index >= indexer.tree().count() {
+                indexer.sync().await?;
+            }
+
+            indexer.tree().proof(leaf_index, root_index)
+        })
+    }
+}
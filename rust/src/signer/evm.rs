@@ -0,0 +1,102 @@
+use std::{fmt, sync::Arc};
+
+use async_trait::async_trait;
+use ethers::{
+    signers::to_eip155_v,
+    types::{
+        transaction::{eip2718::TypedTransaction, eip712::Eip712},
+        Address, Signature, H256,
+    },
+    utils::hash_message,
+};
+
+use super::{Curve, PublicKey, Signer, SignerError};
+
+/// secp256k1 [`Signer`] signing EVM transactions, checkpoints and announcements.
+#[derive(Clone)]
+pub struct EvmSigner {
+    signer: Arc<dyn Signer>,
+    address: Address,
+    chain_id: u64,
+}
+
+impl EvmSigner {
+    /// Wraps `signer`, resolving its address.
+    pub async fn new(signer: Arc<dyn Signer>) -> eyre::Result<Self> {
+        if signer.curve() != Curve::Secp256k1 {
+            eyre::bail!(
+                "EVM signers sign with secp256k1 keys, got a {} key",
+                signer.curve()
+            );
+        }
+        let PublicKey::Secp256k1(address) = signer.public_key().await? else {
+            eyre::bail!("expected the address of a secp256k1 key");
+        };
+
+        Ok(Self {
+            signer,
+            address,
+            chain_id: 1,
+        })
+    }
+
+    /// Signs `hash` as is, `v` being 27 or 28.
+    pub async fn sign_hash(&self, hash: H256) -> eyre::Result<Signature> {
+        self.signer.sign_hash(hash.0).await?.into_secp256k1()
+    }
+}
+
+impl fmt::Debug for EvmSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EvmSigner")
+            .field("address", &self.address)
+            .field("chain_id", &self.chain_id)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl ethers::signers::Signer for EvmSigner {
+    type Error = SignerError;
+
+    async fn sign_message<S: Send + Sync + AsRef<[u8]>>(
+        &self,
+        message: S,
+    ) -> Result<Signature, Self::Error> {
+        Ok(self.sign_hash(hash_message(message)).await?)
+    }
+
+    async fn sign_transaction(&self, tx: &TypedTransaction) -> Result<Signature, Self::Error> {
+        // the sighash must commit to the chain id `v` is derived from
+        let chain_id = tx.chain_id().map_or(self.chain_id, |id| id.as_u64());
+        let mut tx = tx.clone();
+        tx.set_chain_id(chain_id);
+
+        let mut signature = self.sign_hash(tx.sighash()).await?;
+        signature.v = to_eip155_v(signature.v as u8 - 27, chain_id);
+        Ok(signature)
+    }
+
+    async fn sign_typed_data<T: Eip712 + Send + Sync>(
+        &self,
+        payload: &T,
+    ) -> Result<Signature, Self::Error> {
+        let encoded = payload
+            .encode_eip712()
+            .map_err(|e| eyre::eyre!("failed to encode typed data: {e}"))?;
+        Ok(self.sign_hash(H256(encoded)).await?)
+    }
+
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    fn with_chain_id<T: Into<u64>>(mut self, chain_id: T) -> Self {
+        self.chain_id = chain_id.into();
+        self
+    }
+}
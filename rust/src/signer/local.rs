@@ -0,0 +1,92 @@
+use std::path::Path;
+
+use ethers::{signers::LocalWallet as EvmWallet, types::H256};
+use starknet::{core::types::FieldElement, signers::SigningKey};
+
+use crate::utils::BoxFuture;
+
+use super::{Curve, PublicKey, Signature, Signer};
+
+/// Private key held in memory.
+pub enum LocalSigner {
+    Stark(SigningKey),
+    Secp256k1(EvmWallet),
+}
+
+impl LocalSigner {
+    /// Key of `curve` given as hex, with or without `0x` prefix.
+    pub fn from_hex(curve: Curve, key: &str) -> eyre::Result<Self> {
+        let key = key.trim();
+        Ok(match curve {
+            Curve::Stark => Self::Stark(SigningKey::from_secret_scalar(FieldElement::from_hex_be(
+                key,
+            )?)),
+            Curve::Secp256k1 => Self::Secp256k1(key.trim_start_matches("0x").parse()?),
+        })
+    }
+
+    /// Key of `curve` held as hex by the environment variable `var`.
+    pub fn from_env(curve: Curve, var: &str) -> eyre::Result<Self> {
+        let key =
+            std::env::var(var).map_err(|_| eyre::eyre!("environment variable {var} is not set"))?;
+
+        Self::from_hex(curve, &key)
+    }
+
+    /// Key of `curve` encrypted in the JSON keystore at `path`, the same format holding the keys
+    /// of both curves.
+    pub fn from_keystore(
+        curve: Curve,
+        path: impl AsRef<Path>,
+        password: &str,
+    ) -> eyre::Result<Self> {
+        let path = path.as_ref();
+        let unlocked = match curve {
+            Curve::Stark => SigningKey::from_keystore(path, password)
+                .map(Self::Stark)
+                .map_err(|e| eyre::eyre!("{e}")),
+            Curve::Secp256k1 => EvmWallet::decrypt_keystore(path, password)
+                .map(Self::Secp256k1)
+                .map_err(|e| eyre::eyre!("{e}")),
+        };
+
+        unlocked.map_err(|e| eyre::eyre!("failed to unlock keystore {}: {e}", path.display()))
+    }
+
+    fn sign(&self, hash: [u8; 32]) -> eyre::Result<Signature> {
+        Ok(match self {
+            Self::Stark(key) => {
+                let signature = key.sign(&FieldElement::from_bytes_be(&hash)?)?;
+                Signature::Stark {
+                    r: signature.r,
+                    s: signature.s,
+                }
+            }
+            Self::Secp256k1(wallet) => Signature::Secp256k1(wallet.sign_hash(H256(hash))?),
+        })
+    }
+}
+
+impl Signer for LocalSigner {
+    fn curve(&self) -> Curve {
+        match self {
+            Self::Stark(_) => Curve::Stark,
+            Self::Secp256k1(_) => Curve::Secp256k1,
+        }
+    }
+
+    fn public_key(&self) -> BoxFuture<'_, eyre::Result<PublicKey>> {
+        let public_key = match self {
+            Self::Stark(key) => PublicKey::Stark(key.verifying_key().scalar()),
+            Self::Secp256k1(wallet) => {
+                PublicKey::Secp256k1(ethers::signers::Signer::address(wallet))
+            }
+        };
+
+        Box::pin(async move { Ok(public_key) })
+    }
+
+    fn sign_hash(&self, hash: [u8; 32]) -> BoxFuture<'_, eyre::Result<Signature>> {
+        Box::pin(async move { self.sign(hash) })
+    }
+}
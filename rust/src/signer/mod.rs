@@ -0,0 +1,96 @@
+mod evm;
+mod local;
+mod remote;
+mod stark;
+
+use std::fmt;
+
+use ethers::types::Address;
+use starknet::core::types::FieldElement;
+
+use crate::utils::BoxFuture;
+
+pub use evm::*;
+pub use local::*;
+pub use remote::*;
+pub use stark::*;
+
+/// Curve of a signing key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Curve {
+    /// Stark curve, signing the Starknet transactions.
+    Stark,
+    /// secp256k1, signing the EVM transactions and the validator checkpoints and announcements.
+    Secp256k1,
+}
+
+impl fmt::Display for Curve {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Stark => write!(f, "stark"),
+            Self::Secp256k1 => write!(f, "secp256k1"),
+        }
+    }
+}
+
+/// Public identity of a signing key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PublicKey {
+    /// Public key of a Stark key, the x coordinate of its point.
+    Stark(FieldElement),
+    /// Address derived from a secp256k1 key.
+    Secp256k1(Address),
+}
+
+/// Signature of a hash.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Signature {
+    Stark {
+        r: FieldElement,
+        s: FieldElement,
+    },
+    /// Recoverable signature, `v` being 27 or 28.
+    Secp256k1(ethers::types::Signature),
+}
+
+impl Signature {
+    pub fn into_stark(self) -> eyre::Result<starknet::core::crypto::Signature> {
+        match self {
+            Self::Stark { r, s } => Ok(starknet::core::crypto::Signature { r, s }),
+            Self::Secp256k1(_) => eyre::bail!("expected a stark signature, got a secp256k1 one"),
+        }
+    }
+
+    pub fn into_secp256k1(self) -> eyre::Result<ethers::types::Signature> {
+        match self {
+            Self::Secp256k1(signature) => Ok(signature),
+            Self::Stark { .. } => eyre::bail!("expected a secp256k1 signature, got a stark one"),
+        }
+    }
+}
+
+/// Key signing hashes on either curve, wherever it lives: in memory for the local keys read from
+/// the environment or from an encrypted keystore, behind a remote signing service otherwise.
+///
+/// Accounts sign through the [`StarknetSigner`] and [`EvmSigner`] adapters, so that no plaintext
+/// private key has to be configured.
+pub trait Signer: Send + Sync {
+    fn curve(&self) -> Curve;
+
+    fn public_key(&self) -> BoxFuture<'_, eyre::Result<PublicKey>>;
+
+    /// Signs a 32 bytes hash as is, without prefixing it. Stark hashes must be valid felts.
+    fn sign_hash(&self, hash: [u8; 32]) -> BoxFuture<'_, eyre::Result<Signature>>;
+}
+
+/// Failure of a [`Signer`], in the error types of the account traits.
+#[derive(Debug, thiserror::Error)]
+#[error("{0}")]
+pub struct SignerError(String);
+
+impl From<eyre::Report> for SignerError {
+    fn from(error: eyre::Report) -> Self {
+        Self(format!("{error:#}"))
+    }
+}
@@ -0,0 +1,173 @@
+use ethers::types::{Address, H256};
+use starknet::core::{crypto::ecdsa_verify, types::FieldElement};
+use tokio::sync::OnceCell;
+
+use crate::utils::BoxFuture;
+
+use super::{Curve, PublicKey, Signature, Signer};
+
+#[derive(serde::Deserialize)]
+struct KeyResponse {
+    curve: Curve,
+    public_key: String,
+}
+
+#[derive(serde::Serialize)]
+struct SignRequest {
+    hash: String,
+}
+
+#[derive(serde::Deserialize)]
+struct SignResponse {
+    signature: String,
+}
+
+/// Key held by a remote signing service, speaking a minimal HTTP protocol:
+///
+/// - `GET {url}/keys/{key_id}` returns `{"curve": "stark" | "secp256k1", "public_key": "0x..."}`,
+///   the public key of a Stark key or the address of a secp256k1 one.
+/// - `POST {url}/keys/{key_id}/sign` with `{"hash": "0x..."}` returns `{"signature": "0x..."}`,
+///   `r || s` for a Stark key and `r || s || v` for a secp256k1 one.
+///
+/// Requests carry the bearer token of the signer when set. Every signature returned is verified
+/// against the public key of the key before being used.
+pub struct RemoteSigner {
+    client: reqwest::Client,
+    url: String,
+    key_id: String,
+    curve: Curve,
+    token: Option<String>,
+    public_key: OnceCell<PublicKey>,
+}
+
+impl RemoteSigner {
+    /// Signs with the key `key_id` of `curve` held by the service at `url`.
+    pub fn new(url: &str, key_id: &str, curve: Curve) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: url.trim_end_matches('/').to_string(),
+            key_id: key_id.to_string(),
+            curve,
+            token: None,
+            public_key: OnceCell::new(),
+        }
+    }
+
+    /// Authenticates the requests with the bearer `token`.
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    fn request(&self, builder: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match &self.token {
+            Some(token) => builder.bearer_auth(token),
+            None => builder,
+        }
+    }
+
+    async fn fetch_public_key(&self) -> eyre::Result<PublicKey> {
+        let url = format!("{}/keys/{}", self.url, self.key_id);
+        let response: KeyResponse = self
+            .request(self.client.get(&url))
+            .send()
+            .await?
+            .error_for_status()
+            .map_err(|e| eyre::eyre!("remote signer rejected {url}: {e}"))?
+            .json()
+            .await?;
+
+        if response.curve != self.curve {
+            eyre::bail!(
+                "remote key {} is a {} key, expected a {} key",
+                self.key_id,
+                response.curve,
+                self.curve
+            );
+        }
+
+        Ok(match self.curve {
+            Curve::Stark => PublicKey::Stark(FieldElement::from_hex_be(&response.public_key)?),
+            Curve::Secp256k1 => PublicKey::Secp256k1(response.public_key.parse::<Address>()?),
+        })
+    }
+
+    async fn sign(&self, hash: [u8; 32]) -> eyre::Result<Signature> {
+        let public_key = *self
+            .public_key
+            .get_or_try_init(|| self.fetch_public_key())
+            .await?;
+
+        let url = format!("{}/keys/{}/sign", self.url, self.key_id);
+        let response: SignResponse = self
+            .request(self.client.post(&url))
+            .json(&SignRequest {
+                hash: format!("0x{}", hex::encode(hash)),
+            })
+            .send()
+            .await?
+            .error_for_status()
+            .map_err(|e| eyre::eyre!("remote signer rejected {url}: {e}"))?
+            .json()
+            .await?;
+        let bytes = hex::decode(response.signature.trim_start_matches("0x"))?;
+
+        match public_key {
+            PublicKey::Stark(public_key) => {
+                if bytes.len() != 64 {
+                    eyre::bail!(
+                        "expected a 64 bytes stark signature, got {} bytes",
+                        bytes.len()
+                    );
+                }
+                let signature = starknet::core::crypto::Signature {
+                    r: FieldElement::from_byte_slice_be(&bytes[..32])?,
+                    s: FieldElement::from_byte_slice_be(&bytes[32..])?,
+                };
+                if !ecdsa_verify(
+                    &public_key,
+                    &FieldElement::from_bytes_be(&hash)?,
+                    &signature,
+                )? {
+                    eyre::bail!("remote signer returned an invalid signature");
+                }
+
+                Ok(Signature::Stark {
+                    r: signature.r,
+                    s: signature.s,
+                })
+            }
+            PublicKey::Secp256k1(address) => {
+                let mut signature = ethers::types::Signature::try_from(bytes.as_slice())?;
+                // services returning the bare recovery id
+                if signature.v < 27 {
+                    signature.v += 27;
+                }
+                if signature.recover(H256(hash))? != address {
+                    eyre::bail!("remote signer returned a signature of another key");
+                }
+
+                Ok(Signature::Secp256k1(signature))
+            }
+        }
+    }
+}
+
+impl Signer for RemoteSigner {
+    fn curve(&self) -> Curve {
+        self.curve
+    }
+
+    fn public_key(&self) -> BoxFuture<'_, eyre::Result<PublicKey>> {
+        Box::pin(async move {
+            Ok(*self
+                .public_key
+                .get_or_try_init(|| self.fetch_public_key())
+                .await?)
+        })
+    }
+
+    fn sign_hash(&self, hash: [u8; 32]) -> BoxFuture<'_, eyre::Result<Signature>> {
+        Box::pin(self.sign(hash))
+    }
+}
@@ -0,0 +1,55 @@
+use std::{fmt, sync::Arc};
+
+use async_trait::async_trait;
+use starknet::{
+    core::{crypto::Signature, types::FieldElement},
+    signers::VerifyingKey,
+};
+
+use super::{Curve, PublicKey, Signer, SignerError};
+
+/// Stark [`Signer`] signing the transactions of a Starknet account.
+#[derive(Clone)]
+pub struct StarknetSigner {
+    signer: Arc<dyn Signer>,
+}
+
+impl StarknetSigner {
+    pub fn new(signer: Arc<dyn Signer>) -> eyre::Result<Self> {
+        if signer.curve() != Curve::Stark {
+            eyre::bail!(
+                "starknet accounts sign with stark keys, got a {} key",
+                signer.curve()
+            );
+        }
+
+        Ok(Self { signer })
+    }
+}
+
+impl fmt::Debug for StarknetSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StarknetSigner").finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl starknet::signers::Signer for StarknetSigner {
+    type GetPublicKeyError = SignerError;
+    type SignError = SignerError;
+
+    async fn get_public_key(&self) -> Result<VerifyingKey, Self::GetPublicKeyError> {
+        match self.signer.public_key().await? {
+            PublicKey::Stark(key) => Ok(VerifyingKey::from_scalar(key)),
+            PublicKey::Secp256k1(_) => Err(eyre::eyre!("expected a stark public key").into()),
+        }
+    }
+
+    async fn sign_hash(&self, hash: &FieldElement) -> Result<Signature, Self::SignError> {
+        Ok(self
+            .signer
+            .sign_hash(hash.to_bytes_be())
+            .await?
+            .into_stark()?)
+    }
+}
@@ -0,0 +1,284 @@
+use std::time::Duration;
+
+use starknet::{
+    core::types::{
+        EmittedEvent, Event, ExecutionResult, FieldElement, MaybePendingTransactionReceipt,
+        StarknetError, TransactionFinalityStatus, TransactionReceipt, TransactionStatus,
+    },
+    providers::{Provider, ProviderError},
+};
+use tokio::time::Instant;
+
+use crate::{contracts::strk::receipt_events, utils::BoxFuture};
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+const DEFAULT_INITIAL_INTERVAL: Duration = Duration::from_millis(100);
+const DEFAULT_MAX_INTERVAL: Duration = Duration::from_secs(2);
+const DEFAULT_BACKOFF_FACTOR: u32 = 2;
+
+/// Level of finality a transaction must reach before [`TxWatcher::wait`] returns.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Finality {
+    /// The transaction is part of the pending block.
+    Pending,
+    /// The transaction is part of a block accepted on L2.
+    AcceptedOnL2,
+    /// The block including the transaction has been proven on L1.
+    AcceptedOnL1,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum TxError {
+    #[error("transaction {hash:#064x} reverted: {reason}")]
+    Reverted {
+        hash: FieldElement,
+        reason: String,
+        receipt: Box<MaybePendingTransactionReceipt>,
+    },
+
+    #[error("transaction {0:#064x} was rejected by the sequencer")]
+    Rejected(FieldElement),
+
+    #[error("transaction {hash:#064x} did not reach {finality:?} within {timeout:?}")]
+    Timeout {
+        hash: FieldElement,
+        finality: Finality,
+        timeout: Duration,
+    },
+
+    #[error(transparent)]
+    Provider(#[from] ProviderError),
+}
+
+/// Receipt of a transaction that executed successfully, along with its events.
+#[derive(Debug)]
+pub struct TxOutcome {
+    pub hash: FieldElement,
+    pub finality: Finality,
+    pub receipt: MaybePendingTransactionReceipt,
+}
+
+impl TxOutcome {
+    /// Raw events emitted by the transaction.
+    pub fn raw_events(&self) -> &[Event] {
+        receipt_events(&self.receipt)
+    }
+
+    /// Events emitted by the transaction decoded with a generated binding event type,
+    /// skipping the ones that do not belong to it.
+    pub fn events<E>(&self) -> Vec<E>
+    where
+        E: TryFrom<EmittedEvent>,
+    {
+        self.events_from_iter(self.raw_events().iter())
+    }
+
+    /// Same as [`TxOutcome::events`], restricted to the events emitted by `address`.
+    pub fn events_from<E>(&self, address: FieldElement) -> Vec<E>
+    where
+        E: TryFrom<EmittedEvent>,
+    {
+        self.events_from_iter(
+            self.raw_events()
+                .iter()
+                .filter(|e| e.from_address == address),
+        )
+    }
+
+    fn events_from_iter<'a, E>(&self, events: impl Iterator<Item = &'a Event>) -> Vec<E>
+    where
+        E: TryFrom<EmittedEvent>,
+    {
+        let (block_hash, block_number) = match &self.receipt {
+            MaybePendingTransactionReceipt::Receipt(receipt) => {
+                let (hash, number) = receipt_block(receipt);
+                (Some(hash), Some(number))
+            }
+            MaybePendingTransactionReceipt::PendingReceipt(_) => (None, None),
+        };
+
+        events
+            .filter_map(|e| {
+                E::try_from(EmittedEvent {
+                    from_address: e.from_address,
+                    keys: e.keys.clone(),
+                    data: e.data.clone(),
+                    block_hash,
+                    block_number,
+                    transaction_hash: self.hash,
+                })
+                .ok()
+            })
+            .collect()
+    }
+}
+
+/// Node queries polled by [`TxWatcher`], implemented for every starknet [`Provider`].
+pub trait TxStatusSource: Sync {
+    fn transaction_receipt(
+        &self,
+        hash: FieldElement,
+    ) -> BoxFuture<'_, Result<MaybePendingTransactionReceipt, ProviderError>>;
+
+    fn transaction_status(
+        &self,
+        hash: FieldElement,
+    ) -> BoxFuture<'_, Result<TransactionStatus, ProviderError>>;
+}
+
+impl<P> TxStatusSource for P
+where
+    P: Provider + Sync,
+{
+    fn transaction_receipt(
+        &self,
+        hash: FieldElement,
+    ) -> BoxFuture<'_, Result<MaybePendingTransactionReceipt, ProviderError>> {
+        Box::pin(async move { self.get_transaction_receipt(hash).await })
+    }
+
+    fn transaction_status(
+        &self,
+        hash: FieldElement,
+    ) -> BoxFuture<'_, Result<TransactionStatus, ProviderError>> {
+        Box::pin(async move { self.get_transaction_status(hash).await })
+    }
+}
+
+/// Waits for transactions to reach a given finality, polling the node with an exponential backoff.
+///
+/// ```ignore
+/// let outcome = TxWatcher::new(account.provider())
+///     .with_finality(Finality::AcceptedOnL2)
+///     .with_timeout(Duration::from_secs(30))
+///     .wait(res.transaction_hash)
+///     .await?;
+/// let events = outcome.events::<mailbox::Event>();
+/// ```
+pub struct TxWatcher<'a, P> {
+    provider: &'a P,
+    finality: Finality,
+    timeout: Duration,
+    initial_interval: Duration,
+    max_interval: Duration,
+    backoff_factor: u32,
+}
+
+impl<'a, P> TxWatcher<'a, P>
+where
+    P: TxStatusSource,
+{
+    pub fn new(provider: &'a P) -> Self {
+        Self {
+            provider,
+            finality: Finality::AcceptedOnL2,
+            timeout: DEFAULT_TIMEOUT,
+            initial_interval: DEFAULT_INITIAL_INTERVAL,
+            max_interval: DEFAULT_MAX_INTERVAL,
+            backoff_factor: DEFAULT_BACKOFF_FACTOR,
+        }
+    }
+
+    pub fn with_finality(mut self, finality: Finality) -> Self {
+        self.finality = finality;
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Polls every `initial` first, multiplying the interval by `factor` after each attempt
+    /// without exceeding `max`.
+    pub fn with_backoff(mut self, initial: Duration, max: Duration, factor: u32) -> Self {
+        self.initial_interval = initial;
+        self.max_interval = max;
+        self.backoff_factor = factor.max(1);
+        self
+    }
+
+    /// Waits for the transaction to reach the configured finality.
+    ///
+    /// Fails with [`TxError::Reverted`] as soon as the execution is known to have reverted,
+    /// with [`TxError::Rejected`] if the sequencer rejected it and with [`TxError::Timeout`]
+    /// if the finality is not reached in time.
+    pub async fn wait(&self, hash: FieldElement) -> Result<TxOutcome, TxError> {
+        let deadline = Instant::now() + self.timeout;
+        let mut interval = self.initial_interval;
+
+        loop {
+            match self.provider.transaction_receipt(hash).await {
+                Ok(receipt) => {
+                    if let ExecutionResult::Reverted { reason } = receipt.execution_result() {
+                        return Err(TxError::Reverted {
+                            hash,
+                            reason: reason.clone(),
+                            receipt: Box::new(receipt),
+                        });
+                    }
+
+                    let finality = receipt_finality(&receipt);
+                    if finality >= self.finality {
+                        return Ok(TxOutcome {
+                            hash,
+                            finality,
+                            receipt,
+                        });
+                    }
+                }
+                // not yet known by the node, or known but not executed yet
+                Err(ProviderError::StarknetError(StarknetError::TransactionHashNotFound)) => {
+                    if let Ok(TransactionStatus::Rejected) =
+                        self.provider.transaction_status(hash).await
+                    {
+                        return Err(TxError::Rejected(hash));
+                    }
+                }
+                Err(err) => return Err(err.into()),
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(TxError::Timeout {
+                    hash,
+                    finality: self.finality,
+                    timeout: self.timeout,
+                });
+            }
+
+            tokio::time::sleep(interval.min(deadline - now)).await;
+            interval = (interval * self.backoff_factor).min(self.max_interval);
+        }
+    }
+}
+
+fn receipt_finality(receipt: &MaybePendingTransactionReceipt) -> Finality {
+    let receipt = match receipt {
+        MaybePendingTransactionReceipt::Receipt(receipt) => receipt,
+        MaybePendingTransactionReceipt::PendingReceipt(_) => return Finality::Pending,
+    };
+
+    let status = match receipt {
+        TransactionReceipt::Invoke(receipt) => &receipt.finality_status,
+        TransactionReceipt::L1Handler(receipt) => &receipt.finality_status,
+        TransactionReceipt::Declare(receipt) => &receipt.finality_status,
+        TransactionReceipt::Deploy(receipt) => &receipt.finality_status,
+        TransactionReceipt::DeployAccount(receipt) => &receipt.finality_status,
+    };
+
+    match status {
+        TransactionFinalityStatus::AcceptedOnL2 => Finality::AcceptedOnL2,
+        TransactionFinalityStatus::AcceptedOnL1 => Finality::AcceptedOnL1,
+    }
+}
+
+fn receipt_block(receipt: &TransactionReceipt) -> (FieldElement, u64) {
+    match receipt {
+        TransactionReceipt::Invoke(receipt) => (receipt.block_hash, receipt.block_number),
+        TransactionReceipt::L1Handler(receipt) => (receipt.block_hash, receipt.block_number),
+        TransactionReceipt::Declare(receipt) => (receipt.block_hash, receipt.block_number),
+        TransactionReceipt::Deploy(receipt) => (receipt.block_hash, receipt.block_number),
+        TransactionReceipt::DeployAccount(receipt) => (receipt.block_hash, receipt.block_number),
+    }
+}
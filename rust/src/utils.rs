@@ -0,0 +1,4 @@
+use std::{future::Future, pin::Pin};
+
+/// Future returned by the object safe async traits of the crate.
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
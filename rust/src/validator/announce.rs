@@ -0,0 +1,197 @@
+use cainome::cairo_serde::{EthAddress, U256};
+use ethers::types::{Address, H256};
+use starknet::{accounts::ConnectedAccount, core::types::FieldElement, providers::AnyProvider};
+
+use crate::{
+    checkpoint::{Announcement, SignedAnnouncement},
+    checkpoint_syncer::{storage_location_from_felts, storage_location_to_felts, CheckpointSyncer},
+    contracts::strk::{
+        to_strk_message_bytes,
+        validator_announce::{validator_announce, validator_announceReader, Bytes},
+        StarknetAccount,
+    },
+    message::u256_to_bytes,
+    signer::EvmSigner,
+    tx_watcher::TxWatcher,
+    utils::BoxFuture,
+};
+
+/// Outcome of [`announce`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Announced {
+    /// The storage location was already announced, nothing was submitted.
+    Already,
+    /// Announced by the transaction of this hash.
+    Submitted(FieldElement),
+}
+
+/// Signs the digest of an announcement, see [`Announcement::digest`].
+pub async fn sign_announcement(
+    signer: &EvmSigner,
+    value: Announcement,
+) -> eyre::Result<SignedAnnouncement> {
+    let signature = signer.sign_hash(H256(value.digest())).await?;
+
+    Ok(SignedAnnouncement {
+        value,
+        signature: signature.to_vec(),
+    })
+}
+
+fn eth_address(address: Address) -> eyre::Result<EthAddress> {
+    Ok(EthAddress(FieldElement::from_byte_slice_be(
+        address.as_bytes(),
+    )?))
+}
+
+/// Storage locations announced by `validator` in the `validator_announce` at `address`, oldest
+/// first.
+pub async fn announced_storage_locations(
+    provider: &AnyProvider,
+    address: FieldElement,
+    validator: Address,
+) -> eyre::Result<Vec<String>> {
+    let locations = validator_announceReader::new(address, provider)
+        .get_announced_storage_locations(&vec![eth_address(validator)?])
+        .call()
+        .await?;
+
+    Ok(locations
+        .into_iter()
+        .next()
+        .unwrap_or_default()
+        .iter()
+        .map(|location| storage_location_from_felts(location))
+        .collect())
+}
+
+/// Digest the `validator_announce` at `address` expects to be signed for `storage_location`.
+pub async fn announcement_digest_onchain(
+    provider: &AnyProvider,
+    address: FieldElement,
+    storage_location: &str,
+) -> eyre::Result<[u8; 32]> {
+    let words = storage_location_to_felts(storage_location)
+        .iter()
+        .map(|felt| U256::from_bytes_be(&felt.to_bytes_be()))
+        .collect();
+    let digest = validator_announceReader::new(address, provider)
+        .get_announcement_digest(&words)
+        .call()
+        .await?;
+
+    Ok(u256_to_bytes(&digest))
+}
+
+/// `validator_announce` contract a validator announces the storage location of its checkpoints
+/// in.
+pub trait ValidatorAnnounceContract: Send + Sync {
+    /// Storage locations announced by `validator`, oldest first.
+    fn announced_locations(&self, validator: Address) -> BoxFuture<'_, eyre::Result<Vec<String>>>;
+
+    /// Digest the contract expects to be signed for `storage_location`.
+    fn announcement_digest<'a>(
+        &'a self,
+        storage_location: &'a str,
+    ) -> BoxFuture<'a, eyre::Result<[u8; 32]>>;
+
+    /// Submits `signed`, returning the hash of its accepted transaction.
+    fn submit<'a>(
+        &'a self,
+        signed: &'a SignedAnnouncement,
+    ) -> BoxFuture<'a, eyre::Result<FieldElement>>;
+}
+
+/// `validator_announce` of a Starknet chain, announcements being submitted by `account`.
+pub struct StarknetValidatorAnnouncer {
+    pub account: StarknetAccount,
+    pub address: FieldElement,
+}
+
+impl StarknetValidatorAnnouncer {
+    pub fn new(account: StarknetAccount, address: FieldElement) -> Self {
+        Self { account, address }
+    }
+}
+
+impl ValidatorAnnounceContract for StarknetValidatorAnnouncer {
+    fn announced_locations(&self, validator: Address) -> BoxFuture<'_, eyre::Result<Vec<String>>> {
+        Box::pin(announced_storage_locations(
+            self.account.provider(),
+            self.address,
+            validator,
+        ))
+    }
+
+    fn announcement_digest<'a>(
+        &'a self,
+        storage_location: &'a str,
+    ) -> BoxFuture<'a, eyre::Result<[u8; 32]>> {
+        Box::pin(announcement_digest_onchain(
+            self.account.provider(),
+            self.address,
+            storage_location,
+        ))
+    }
+
+    fn submit<'a>(
+        &'a self,
+        signed: &'a SignedAnnouncement,
+    ) -> BoxFuture<'a, eyre::Result<FieldElement>> {
+        Box::pin(async move {
+            let value = &signed.value;
+            let signature = to_strk_message_bytes(&signed.signature);
+            let res = validator_announce::new(self.address, &self.account)
+                .announce(
+                    &eth_address(value.validator)?,
+                    &storage_location_to_felts(&value.storage_location),
+                    &Bytes {
+                        size: signature.size,
+                        data: signature.data,
+                    },
+                )
+                .send()
+                .await?;
+            TxWatcher::new(self.account.provider())
+                .wait(res.transaction_hash)
+                .await?;
+
+            Ok(res.transaction_hash)
+        })
+    }
+}
+
+/// Announces the storage location of a signed announcement in `contract`, writing the
+/// announcement next to the checkpoints of `syncer` for the relayers of the upstream agents.
+///
+/// Nothing is written nor submitted when the location was already announced by the validator,
+/// the contract rejecting replayed announcements. Otherwise the digest signed is first checked
+/// against the one of the contract, so that an announcement signed for another mailbox or domain
+/// is neither written nor submitted.
+pub async fn announce(
+    contract: &dyn ValidatorAnnounceContract,
+    signed: &SignedAnnouncement,
+    syncer: &dyn CheckpointSyncer,
+) -> eyre::Result<Announced> {
+    let value = &signed.value;
+
+    let locations = contract.announced_locations(value.validator).await?;
+    if locations.contains(&value.storage_location) {
+        return Ok(Announced::Already);
+    }
+
+    let expected = contract
+        .announcement_digest(&value.storage_location)
+        .await?;
+    if value.digest() != expected {
+        eyre::bail!(
+            "announcement digest 0x{} does not match the validator_announce digest 0x{}, check \
+             the mailbox and domain of the announcement",
+            hex::encode(value.digest()),
+            hex::encode(expected)
+        );
+    }
+
+    syncer.write_announcement(signed).await?;
+    Ok(Announced::Submitted(contract.submit(signed).await?))
+}
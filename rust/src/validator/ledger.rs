@@ -0,0 +1,239 @@
+use std::{
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use rusqlite::{params, Connection, OptionalExtension, Row};
+
+use crate::checkpoint::{Checkpoint, CheckpointWithMessageId, SignedCheckpoint};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS signed_checkpoints (
+    origin INTEGER NOT NULL,
+    merkle_tree_hook BLOB NOT NULL,
+    idx INTEGER NOT NULL,
+    root BLOB NOT NULL,
+    message_id BLOB NOT NULL,
+    signature BLOB NOT NULL,
+    signed_at INTEGER NOT NULL,
+    PRIMARY KEY (origin, merkle_tree_hook, idx)
+);
+CREATE TABLE IF NOT EXISTS halts (
+    id INTEGER PRIMARY KEY CHECK (id = 0),
+    reason TEXT NOT NULL,
+    halted_at INTEGER NOT NULL
+);
+";
+
+/// Refusal to sign a checkpoint conflicting with the one already signed at its index.
+#[derive(Debug, thiserror::Error)]
+#[error(
+    "checkpoint {} of domain {} already signed with root 0x{} and message 0x{}, refusing root 0x{} and message 0x{}",
+    .signed.checkpoint.index,
+    .signed.checkpoint.origin,
+    hex::encode(.signed.checkpoint.root),
+    hex::encode(.signed.message_id),
+    hex::encode(.requested.checkpoint.root),
+    hex::encode(.requested.message_id)
+)]
+pub struct Equivocation {
+    pub signed: CheckpointWithMessageId,
+    pub requested: CheckpointWithMessageId,
+}
+
+/// Halt of the validator, refusing to sign until an operator resumes it.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("validator halted at {halted_at}: {reason}")]
+pub struct Halted {
+    pub reason: String,
+    /// Unix timestamp of the halt, in seconds.
+    pub halted_at: u64,
+}
+
+/// Checkpoint recorded in the ledger along with the time it was signed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LedgerEntry {
+    pub checkpoint: SignedCheckpoint,
+    /// Unix timestamp of the signature, in seconds.
+    pub signed_at: u64,
+}
+
+/// Persistent history of the checkpoints signed by a validator, in a sqlite database.
+///
+/// Checkpoints are recorded before their signature leaves the validator, so that it never signs
+/// two different roots or message ids for the same index of a merkle tree hook, even across
+/// restarts. The ledger also records the halt of the validator, which survives restarts too.
+pub struct SigningLedger {
+    conn: Connection,
+}
+
+impl SigningLedger {
+    /// Opens the database at `path`, creating it when missing.
+    pub fn open(path: impl AsRef<Path>) -> eyre::Result<Self> {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    /// Opens a database living in memory, lost when dropped.
+    pub fn in_memory() -> eyre::Result<Self> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(conn: Connection) -> eyre::Result<Self> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    /// Checkpoint signed at the index of `checkpoint` on its merkle tree hook.
+    pub fn signed(&self, checkpoint: &Checkpoint) -> eyre::Result<Option<LedgerEntry>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT origin, merkle_tree_hook, idx, root, message_id, signature, signed_at
+                 FROM signed_checkpoints
+                 WHERE origin = ?1 AND merkle_tree_hook = ?2 AND idx = ?3",
+                params![
+                    checkpoint.origin,
+                    checkpoint.merkle_tree_hook.to_vec(),
+                    checkpoint.index
+                ],
+                LedgerEntry::from_row,
+            )
+            .optional()?)
+    }
+
+    /// Checks `value` against the checkpoint signed at its index.
+    ///
+    /// Returns the checkpoint already signed when it is the same one, so that its signature is
+    /// reused, and fails with [`Equivocation`] when it conflicts with it.
+    pub fn check(&self, value: &CheckpointWithMessageId) -> eyre::Result<Option<SignedCheckpoint>> {
+        match self.signed(&value.checkpoint)? {
+            Some(entry) if entry.checkpoint.value == *value => Ok(Some(entry.checkpoint)),
+            Some(entry) => Err(Equivocation {
+                signed: entry.checkpoint.value,
+                requested: *value,
+            }
+            .into()),
+            None => Ok(None),
+        }
+    }
+
+    /// Records a signed checkpoint, failing with [`Equivocation`] when a different one was
+    /// signed at its index.
+    pub fn record(&self, signed: &SignedCheckpoint) -> eyre::Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        if self.check(&signed.value)?.is_some() {
+            return Ok(());
+        }
+
+        let checkpoint = &signed.value.checkpoint;
+        tx.execute(
+            "INSERT INTO signed_checkpoints
+             (origin, merkle_tree_hook, idx, root, message_id, signature, signed_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                checkpoint.origin,
+                checkpoint.merkle_tree_hook.to_vec(),
+                checkpoint.index,
+                checkpoint.root.to_vec(),
+                signed.value.message_id.to_vec(),
+                signed.signature,
+                unix_now(),
+            ],
+        )?;
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// Signed checkpoints of the merkle tree hook of `origin` from index `from`, in index order,
+    /// at most `limit` of them.
+    pub fn entries(
+        &self,
+        origin: u32,
+        merkle_tree_hook: &[u8; 32],
+        from: u32,
+        limit: u32,
+    ) -> eyre::Result<Vec<LedgerEntry>> {
+        let mut statement = self.conn.prepare(
+            "SELECT origin, merkle_tree_hook, idx, root, message_id, signature, signed_at
+             FROM signed_checkpoints
+             WHERE origin = ?1 AND merkle_tree_hook = ?2 AND idx >= ?3
+             ORDER BY idx LIMIT ?4",
+        )?;
+        let entries = statement
+            .query_map(
+                params![origin, merkle_tree_hook.to_vec(), from, limit],
+                LedgerEntry::from_row,
+            )?
+            .collect::<Result<_, _>>()?;
+
+        Ok(entries)
+    }
+
+    /// Halts the validator, keeping the first reason when it is already halted.
+    pub fn halt(&self, reason: &str) -> eyre::Result<Halted> {
+        self.conn.execute(
+            "INSERT OR IGNORE INTO halts (id, reason, halted_at) VALUES (0, ?1, ?2)",
+            params![reason, unix_now()],
+        )?;
+
+        Ok(self.halted()?.expect("halt just recorded"))
+    }
+
+    /// Halt of the validator, unset while it signs.
+    pub fn halted(&self) -> eyre::Result<Option<Halted>> {
+        Ok(self
+            .conn
+            .query_row("SELECT reason, halted_at FROM halts", [], |row| {
+                Ok(Halted {
+                    reason: row.get(0)?,
+                    halted_at: row.get(1)?,
+                })
+            })
+            .optional()?)
+    }
+
+    /// Lifts the halt of the validator, once an operator checked the origin chain.
+    pub fn resume(&self) -> eyre::Result<()> {
+        self.conn.execute("DELETE FROM halts", [])?;
+        Ok(())
+    }
+}
+
+impl LedgerEntry {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        let bytes32 = |index: usize| -> rusqlite::Result<[u8; 32]> {
+            let bytes: Vec<u8> = row.get(index)?;
+            bytes.try_into().map_err(|_| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    index,
+                    rusqlite::types::Type::Blob,
+                    "expected 32 bytes".into(),
+                )
+            })
+        };
+
+        Ok(Self {
+            checkpoint: SignedCheckpoint {
+                value: CheckpointWithMessageId {
+                    checkpoint: Checkpoint {
+                        merkle_tree_hook: bytes32(1)?,
+                        origin: row.get(0)?,
+                        root: bytes32(3)?,
+                        index: row.get(2)?,
+                    },
+                    message_id: bytes32(4)?,
+                },
+                signature: row.get(5)?,
+            },
+            signed_at: row.get(6)?,
+        })
+    }
+}
+
+/// Current unix timestamp, in seconds.
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
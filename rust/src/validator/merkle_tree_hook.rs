@@ -0,0 +1,149 @@
+use starknet::{
+    core::types::{BlockId, EventFilter, FieldElement},
+    macros::selector,
+    providers::{AnyProvider, Provider},
+};
+
+use crate::{
+    checkpoint::Checkpoint,
+    contracts::strk::merkle_tree_hook::{merkle_tree_hookReader, Event as MerkleTreeHookEvent},
+    message::u256_to_bytes,
+    utils::BoxFuture,
+};
+
+/// Maximum number of events fetched per `starknet_getEvents` page.
+const EVENTS_CHUNK_SIZE: u64 = 100;
+
+/// Message id inserted in the merkle tree hook at `index`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InsertedIntoTree {
+    pub id: [u8; 32],
+    pub index: u32,
+}
+
+/// Merkle tree hook the checkpoints of an origin are read from.
+pub trait MerkleTreeHookSource: Send + Sync {
+    /// Hyperlane domain of the origin chain.
+    fn origin(&self) -> u32;
+
+    /// Address of the hook, as signed in the checkpoints.
+    fn merkle_tree_hook(&self) -> [u8; 32];
+
+    fn latest_block(&self) -> BoxFuture<'_, eyre::Result<u64>>;
+
+    /// Checkpoint of the last message inserted as of `block`, unset while the tree is empty.
+    fn latest_checkpoint(&self, block: u64) -> BoxFuture<'_, eyre::Result<Option<Checkpoint>>>;
+
+    /// Message ids inserted in the tree between blocks `from` and `to`, both included, in their
+    /// insertion order.
+    fn inserted_ids(
+        &self,
+        from: u64,
+        to: u64,
+    ) -> BoxFuture<'_, eyre::Result<Vec<InsertedIntoTree>>>;
+}
+
+/// Merkle tree hook of a Starknet origin mailbox, read by its validators.
+pub struct MerkleTreeHook {
+    pub provider: AnyProvider,
+    pub address: FieldElement,
+    /// Hyperlane domain of the origin chain.
+    pub origin: u32,
+}
+
+impl MerkleTreeHook {
+    pub fn new(provider: AnyProvider, address: FieldElement, origin: u32) -> Self {
+        Self {
+            provider,
+            address,
+            origin,
+        }
+    }
+
+    pub async fn latest_block(&self) -> eyre::Result<u64> {
+        Ok(self.provider.block_number().await?)
+    }
+
+    /// Checkpoint of the last message inserted as of `block`, unset while the tree is empty.
+    pub async fn latest_checkpoint(&self, block: u64) -> eyre::Result<Option<Checkpoint>> {
+        let reader = merkle_tree_hookReader::new(self.address, &self.provider);
+        let block = BlockId::Number(block);
+
+        // `latest_checkpoint` underflows on an empty tree
+        if reader.count().block_id(block).call().await? == 0 {
+            return Ok(None);
+        }
+
+        let (root, index) = reader.latest_checkpoint().block_id(block).call().await?;
+        Ok(Some(Checkpoint {
+            merkle_tree_hook: self.address.to_bytes_be(),
+            origin: self.origin,
+            root: u256_to_bytes(&root),
+            index,
+        }))
+    }
+
+    /// Message ids inserted in the tree between blocks `from` and `to`, both included, in their
+    /// insertion order.
+    pub async fn inserted_ids(&self, from: u64, to: u64) -> eyre::Result<Vec<InsertedIntoTree>> {
+        let filter = EventFilter {
+            from_block: Some(BlockId::Number(from)),
+            to_block: Some(BlockId::Number(to)),
+            address: Some(self.address),
+            keys: Some(vec![vec![selector!("InsertedIntoTree")]]),
+        };
+
+        let mut inserted = vec![];
+        let mut continuation_token = None;
+        loop {
+            let page = self
+                .provider
+                .get_events(filter.clone(), continuation_token, EVENTS_CHUNK_SIZE)
+                .await?;
+
+            for event in page.events {
+                if let Ok(MerkleTreeHookEvent::InsertedIntoTree(event)) =
+                    MerkleTreeHookEvent::try_from(event)
+                {
+                    inserted.push(InsertedIntoTree {
+                        id: u256_to_bytes(&event.id),
+                        index: event.index,
+                    });
+                }
+            }
+
+            match page.continuation_token {
+                Some(token) => continuation_token = Some(token),
+                None => break,
+            }
+        }
+
+        Ok(inserted)
+    }
+}
+
+impl MerkleTreeHookSource for MerkleTreeHook {
+    fn origin(&self) -> u32 {
+        self.origin
+    }
+
+    fn merkle_tree_hook(&self) -> [u8; 32] {
+        self.address.to_bytes_be()
+    }
+
+    fn latest_block(&self) -> BoxFuture<'_, eyre::Result<u64>> {
+        Box::pin(MerkleTreeHook::latest_block(self))
+    }
+
+    fn latest_checkpoint(&self, block: u64) -> BoxFuture<'_, eyre::Result<Option<Checkpoint>>> {
+        Box::pin(MerkleTreeHook::latest_checkpoint(self, block))
+    }
+
+    fn inserted_ids(
+        &self,
+        from: u64,
+        to: u64,
+    ) -> BoxFuture<'_, eyre::Result<Vec<InsertedIntoTree>>> {
+        Box::pin(MerkleTreeHook::inserted_ids(self, from, to))
+    }
+}
@@ -0,0 +1,246 @@
+mod announce;
+mod ledger;
+mod merkle_tree_hook;
+
+use std::{sync::Arc, time::Duration};
+
+use ethers::{
+    signers::Signer,
+    types::{Address, H256},
+};
+
+use crate::{
+    checkpoint::{Checkpoint, CheckpointWithMessageId, SignedCheckpoint},
+    checkpoint_syncer::CheckpointSyncer,
+    indexer::MerkleTreeIndexer,
+    signer::EvmSigner,
+};
+
+pub use announce::*;
+pub use ledger::*;
+pub use merkle_tree_hook::*;
+
+/// Signs the digest of a checkpoint, see [`CheckpointWithMessageId::digest`].
+pub async fn sign_checkpoint(
+    signer: &EvmSigner,
+    value: CheckpointWithMessageId,
+) -> eyre::Result<SignedCheckpoint> {
+    let signature = signer.sign_hash(H256(value.digest())).await?;
+
+    Ok(SignedCheckpoint {
+        value,
+        signature: signature.to_vec(),
+    })
+}
+
+/// Tuning of the validator loop.
+#[derive(Clone, Debug)]
+pub struct ValidatorSettings {
+    /// Delay between two rounds of indexing and signing.
+    pub poll_interval: Duration,
+    /// Maximum number of blocks indexed per `starknet_getEvents` query.
+    pub max_block_range: u64,
+    /// Block the merkle tree hook is indexed from, at or before its deployment.
+    pub start_block: u64,
+}
+
+impl Default for ValidatorSettings {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_secs(5),
+            max_block_range: 1000,
+            start_block: 0,
+        }
+    }
+}
+
+/// Signs the checkpoints of the merkle tree hook of an origin mailbox.
+///
+/// The tree of the hook is rebuilt by a [`MerkleTreeIndexer`], giving the root after each
+/// insertion. Once the local tree matches the `latest_checkpoint` of the hook, every checkpoint
+/// not written yet is signed, so that relayers find a checkpoint for the index of each message.
+///
+/// Every signature is recorded in a [`SigningLedger`] before being written, and a checkpoint
+/// conflicting with the one signed at its index is never signed: the validator halts instead,
+/// as it does when the leaf or the root at a signed index changed after a reorg of the origin,
+/// the leaves indexed again after a reorg being checked against the checkpoints signed.
+pub struct Validator {
+    indexer: MerkleTreeIndexer,
+    signer: EvmSigner,
+    syncer: Arc<dyn CheckpointSyncer>,
+    ledger: SigningLedger,
+    settings: ValidatorSettings,
+    /// Number of leaves checked against the checkpoints signed.
+    verified: u32,
+}
+
+impl Validator {
+    pub fn new(
+        hook: impl MerkleTreeHookSource + 'static,
+        signer: EvmSigner,
+        syncer: Arc<dyn CheckpointSyncer>,
+        ledger: SigningLedger,
+        settings: ValidatorSettings,
+    ) -> Self {
+        Self {
+            indexer: MerkleTreeIndexer::new(hook, settings.start_block)
+                .with_max_block_range(settings.max_block_range),
+            signer,
+            syncer,
+            ledger,
+            settings,
+            verified: 0,
+        }
+    }
+
+    /// Address of the validator, as enrolled in the multisig ISMs.
+    pub fn address(&self) -> Address {
+        self.signer.address()
+    }
+
+    /// History of the checkpoints signed, for audits.
+    pub fn ledger(&self) -> &SigningLedger {
+        &self.ledger
+    }
+
+    /// Signs checkpoints until the validator halts, retrying the failed rounds at the next poll.
+    pub async fn run(&mut self) -> eyre::Result<()> {
+        loop {
+            if let Err(e) = self.tick().await {
+                if e.downcast_ref::<Halted>().is_some() {
+                    return Err(e);
+                }
+                println!("Validator round failed: {e}");
+            }
+            tokio::time::sleep(self.settings.poll_interval).await;
+        }
+    }
+
+    /// Runs a single round of indexing and signing, returning the checkpoints signed.
+    ///
+    /// Fails with [`Halted`] once the validator halted, without signing anything.
+    pub async fn tick(&mut self) -> eyre::Result<Vec<SignedCheckpoint>> {
+        if let Some(halted) = self.ledger.halted()? {
+            return Err(halted.into());
+        }
+
+        if let Some(kept) = self.indexer.sync().await? {
+            println!(
+                "Merkle tree hook of domain {} reorganized, indexing it again after its first {kept} \
+                 messages",
+                self.hook().origin()
+            );
+            self.verified = self.verified.min(kept);
+        }
+        self.verify_signed()?;
+
+        let Some(latest) = self.indexer.tree().count().checked_sub(1) else {
+            return Ok(vec![]);
+        };
+        let from = match self.syncer.latest_index().await? {
+            Some(index) => index + 1,
+            None => 0,
+        };
+        let mut signed = vec![];
+        for index in from..=latest {
+            let (message_id, root) = self.indexed_leaf(index)?;
+            let value = CheckpointWithMessageId {
+                checkpoint: Checkpoint {
+                    merkle_tree_hook: self.hook().merkle_tree_hook(),
+                    origin: self.hook().origin(),
+                    root,
+                    index,
+                },
+                message_id,
+            };
+            let checkpoint = match self.ledger.check(&value) {
+                // written before a failure, or lost by the storage
+                Ok(Some(checkpoint)) => checkpoint,
+                Ok(None) => {
+                    let checkpoint = sign_checkpoint(&self.signer, value).await?;
+                    self.ledger.record(&checkpoint)?;
+                    checkpoint
+                }
+                Err(e) if e.downcast_ref::<Equivocation>().is_some() => {
+                    return Err(self.halt(&e.to_string()));
+                }
+                Err(e) => return Err(e),
+            };
+            self.syncer.write_checkpoint(&checkpoint).await?;
+            signed.push(checkpoint);
+        }
+
+        if !signed.is_empty() {
+            println!(
+                "Signed checkpoints {from} to {latest} of domain {}",
+                self.hook().origin()
+            );
+        }
+
+        Ok(signed)
+    }
+
+    /// Halts the validator, returning the error stopping it.
+    fn halt(&self, reason: &str) -> eyre::Report {
+        match self.ledger.halt(reason) {
+            Ok(halted) => {
+                println!(
+                    "ALERT: validator {:?} of domain {} halted: {}",
+                    self.address(),
+                    self.hook().origin(),
+                    halted.reason
+                );
+                halted.into()
+            }
+            Err(e) => e.wrap_err(format!("failed to halt the validator: {reason}")),
+        }
+    }
+
+    /// Checks the leaves indexed since the last round against the checkpoints signed at their
+    /// index, halting when the message id or the root of a signed index changed.
+    fn verify_signed(&mut self) -> eyre::Result<()> {
+        let count = self.indexer.tree().count();
+        if self.verified < count {
+            let entries = self.ledger.entries(
+                self.hook().origin(),
+                &self.hook().merkle_tree_hook(),
+                self.verified,
+                count - self.verified,
+            )?;
+            // signed indices above the indexed leaves are checked once they are indexed
+            for entry in entries
+                .iter()
+                .filter(|entry| entry.checkpoint.value.checkpoint.index < count)
+            {
+                let signed = &entry.checkpoint.value;
+                let index = signed.checkpoint.index;
+                let (message_id, root) = self.indexed_leaf(index)?;
+                if (message_id, root) != (signed.message_id, signed.checkpoint.root) {
+                    return Err(self.halt(&format!(
+                        "origin reorg: merkle tree hook message 0x{} with root 0x{} at index \
+                         {index} replaced by message 0x{} with root 0x{} after it was signed",
+                        hex::encode(signed.message_id),
+                        hex::encode(signed.checkpoint.root),
+                        hex::encode(message_id),
+                        hex::encode(root)
+                    )));
+                }
+            }
+            self.verified = count;
+        }
+
+        Ok(())
+    }
+
+    /// Message id indexed at `index`, along with the tree root after its insertion.
+    fn indexed_leaf(&self, index: u32) -> eyre::Result<([u8; 32], [u8; 32])> {
+        let tree = self.indexer.tree();
+        tree.leaf(index)
+            .zip(tree.root_at(index))
+            .ok_or(eyre::eyre!("no leaf at index {index} in the indexed tree"))
+    }
+
+    fn hook(&self) -> &dyn MerkleTreeHookSource {
+        self.indexer.hook()
+    }
+}
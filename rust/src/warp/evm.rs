@@ -0,0 +1,114 @@
+use ethers::{providers::Middleware, types::Address};
+
+use crate::{
+    contracts::eth::{
+        fast_hyp_erc20::{FastHypERC20, FastHypERC20Events},
+        mailbox::Mailbox,
+    },
+    indexer::{evm_block_timestamp, evm_mailbox_events},
+    message::HyperlaneAddress,
+    utils::BoxFuture,
+};
+
+use super::{
+    match_transfers_with_timestamps, IndexedTransfer, RouteBalance, RouteToken, TransferEvent,
+    WarpRouteSource,
+};
+
+/// Router of a warp route on an EVM chain, its events read with `eth_getLogs`.
+pub struct EvmWarpRoute<M> {
+    pub mailbox: Mailbox<M>,
+    pub router: FastHypERC20<M>,
+    pub token: RouteToken<Address>,
+}
+
+impl<M: Middleware + 'static> EvmWarpRoute<M> {
+    /// The events of any `TokenRouter` are decoded through the `FastHypERC20` bindings, which also
+    /// read the ERC20 `token`.
+    pub fn new(mailbox: Mailbox<M>, router: Address, token: RouteToken<Address>) -> Self {
+        Self {
+            router: FastHypERC20::new(router, mailbox.client()),
+            mailbox,
+            token,
+        }
+    }
+
+    async fn transfers_between(&self, from: u64, to: u64) -> eyre::Result<Vec<IndexedTransfer>> {
+        let mut logs = self
+            .router
+            .events()
+            .from_block(from)
+            .to_block(to)
+            .query_with_meta()
+            .await?;
+        logs.sort_by_key(|(_, meta)| (meta.block_number, meta.log_index));
+
+        let transfers = logs
+            .into_iter()
+            .filter_map(|(log, meta)| {
+                let event = match log {
+                    FastHypERC20Events::SentTransferRemoteFilter(sent) => TransferEvent::Sent {
+                        destination: sent.destination,
+                        recipient: sent.recipient,
+                        amount: sent.amount,
+                    },
+                    FastHypERC20Events::ReceivedTransferRemoteFilter(received) => {
+                        TransferEvent::Received {
+                            origin: received.origin,
+                            recipient: received.recipient,
+                            amount: received.amount,
+                        }
+                    }
+                    _ => return None,
+                };
+                Some((meta.transaction_hash.0, event))
+            })
+            .collect();
+
+        let mailbox_events = evm_mailbox_events(&self.mailbox, from, to).await?;
+        match_transfers_with_timestamps(
+            HyperlaneAddress::from(self.router.address()).0,
+            transfers,
+            mailbox_events,
+            |block_hash| evm_block_timestamp(self.mailbox.client_ref(), block_hash),
+        )
+        .await
+    }
+
+    async fn balance_at(&self, block: u64) -> eyre::Result<RouteBalance> {
+        let client = self.router.client();
+
+        Ok(match self.token {
+            RouteToken::Collateral { token, holder } => {
+                let locked = FastHypERC20::new(token, client)
+                    .balance_of(holder)
+                    .block(block)
+                    .call()
+                    .await?;
+                RouteBalance::Collateral(locked)
+            }
+            RouteToken::Synthetic { token } => {
+                let supply = FastHypERC20::new(token, client)
+                    .total_supply()
+                    .block(block)
+                    .call()
+                    .await?;
+                RouteBalance::Synthetic(supply)
+            }
+        })
+    }
+}
+
+impl<M: Middleware + 'static> WarpRouteSource for EvmWarpRoute<M> {
+    fn latest_block(&self) -> BoxFuture<'_, eyre::Result<u64>> {
+        Box::pin(async move { Ok(self.router.client().get_block_number().await?.as_u64()) })
+    }
+
+    fn transfers(&self, from: u64, to: u64) -> BoxFuture<'_, eyre::Result<Vec<IndexedTransfer>>> {
+        Box::pin(self.transfers_between(from, to))
+    }
+
+    fn balance(&self, block: u64) -> BoxFuture<'_, eyre::Result<RouteBalance>> {
+        Box::pin(self.balance_at(block))
+    }
+}
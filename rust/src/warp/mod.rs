@@ -0,0 +1,514 @@
+mod evm;
+mod stark;
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    fmt,
+    future::Future,
+    time::Duration,
+};
+
+use ethers::types::U256;
+
+use crate::{
+    indexer::{resolve_block_timestamps, IndexedEvent, MailboxEvent},
+    utils::BoxFuture,
+};
+
+pub use evm::*;
+pub use stark::*;
+
+/// Transfer event of a warp route, emitted by its `TokenRouter`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransferEvent {
+    /// `SentTransferRemote`: `amount` left the route towards `recipient` on `destination`.
+    Sent {
+        destination: u32,
+        recipient: [u8; 32],
+        amount: U256,
+    },
+    /// `ReceivedTransferRemote`: `amount` was transferred to `recipient` from `origin`.
+    Received {
+        origin: u32,
+        recipient: [u8; 32],
+        amount: U256,
+    },
+}
+
+/// Transfer event of a warp route, along with the message carrying the transfer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IndexedTransfer {
+    pub message_id: [u8; 32],
+    pub block_number: u64,
+    /// Unix timestamp of the block, in seconds.
+    pub block_timestamp: u64,
+    pub transaction_hash: [u8; 32],
+    pub event: TransferEvent,
+}
+
+/// Id, block number and block timestamp of the messages of a router, by transaction.
+type RouterMessages = HashMap<[u8; 32], VecDeque<([u8; 32], u64, Option<u64>)>>;
+
+/// Matches the transfer events of `router`, given with the hash of their transaction in the order
+/// of the chain, with the message dispatched or processed for them by the mailbox in the same
+/// transaction.
+///
+/// The transfer events carry no message id: the n-th transfer sent by a transaction is the n-th
+/// message the router dispatched in it, the n-th transfer received the n-th message processed for
+/// the router. The block timestamp of the mailbox events of the transfers must be resolved.
+pub fn match_transfers(
+    router: [u8; 32],
+    transfers: Vec<([u8; 32], TransferEvent)>,
+    mailbox_events: &[IndexedEvent],
+) -> eyre::Result<Vec<IndexedTransfer>> {
+    let mut dispatched = RouterMessages::new();
+    let mut processed = RouterMessages::new();
+    let mut processing = false;
+    for indexed in mailbox_events {
+        let message = |id| (id, indexed.block_number, indexed.block_timestamp);
+        match &indexed.event {
+            MailboxEvent::Dispatch(dispatch) if dispatch.sender == router => dispatched
+                .entry(indexed.transaction_hash)
+                .or_default()
+                .push_back(message(dispatch.id())),
+            MailboxEvent::Process { recipient, .. } => processing = *recipient == router,
+            MailboxEvent::ProcessId(id) if processing => {
+                processing = false;
+                processed
+                    .entry(indexed.transaction_hash)
+                    .or_default()
+                    .push_back(message(*id));
+            }
+            _ => {}
+        }
+    }
+
+    transfers
+        .into_iter()
+        .map(|(transaction_hash, event)| {
+            let (messages, kind) = match event {
+                TransferEvent::Sent { .. } => (&mut dispatched, "dispatch"),
+                TransferEvent::Received { .. } => (&mut processed, "delivery"),
+            };
+            let (message_id, block_number, block_timestamp) = messages
+                .get_mut(&transaction_hash)
+                .and_then(VecDeque::pop_front)
+                .ok_or(eyre::eyre!(
+                    "no {kind} of a message of router 0x{} in transaction 0x{} for its transfer",
+                    hex::encode(router),
+                    hex::encode(transaction_hash)
+                ))?;
+            let block_timestamp = block_timestamp.ok_or(eyre::eyre!(
+                "timestamp of block {block_number} not resolved"
+            ))?;
+
+            Ok(IndexedTransfer {
+                message_id,
+                block_number,
+                block_timestamp,
+                transaction_hash,
+                event,
+            })
+        })
+        .collect()
+}
+
+/// Matches the transfer events of `router` with their messages as [`match_transfers`] does,
+/// looking up with `block_timestamp` the timestamp of the blocks of the transactions of the
+/// transfers only, the other mailbox events being dropped.
+pub async fn match_transfers_with_timestamps<F, Fut>(
+    router: [u8; 32],
+    transfers: Vec<([u8; 32], TransferEvent)>,
+    mut mailbox_events: Vec<IndexedEvent>,
+    block_timestamp: F,
+) -> eyre::Result<Vec<IndexedTransfer>>
+where
+    F: Fn([u8; 32]) -> Fut,
+    Fut: Future<Output = eyre::Result<u64>>,
+{
+    let transactions: HashSet<[u8; 32]> = transfers.iter().map(|(hash, _)| *hash).collect();
+    mailbox_events.retain(|indexed| transactions.contains(&indexed.transaction_hash));
+    resolve_block_timestamps(&mut mailbox_events, block_timestamp).await?;
+
+    match_transfers(router, transfers, &mailbox_events)
+}
+
+/// Token of a warp route backing its transfers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RouteToken<A> {
+    /// ERC20 `token` locked in `holder`, the router itself or the lockbox it deposits into.
+    Collateral { token: A, holder: A },
+    /// ERC20 `token` minted and burned by the router.
+    Synthetic { token: A },
+}
+
+/// Balance backing the transfers of a warp route at some block.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RouteBalance {
+    /// Amount of the collateral locked by the route.
+    Collateral(U256),
+    /// Total supply of the synthetic token of the route.
+    Synthetic(U256),
+}
+
+/// Router of a warp route on one chain.
+pub trait WarpRouteSource: Send + Sync {
+    fn latest_block(&self) -> BoxFuture<'_, eyre::Result<u64>>;
+
+    /// Transfers sent and received by the router between blocks `from` and `to`, both included,
+    /// in the order of the chain.
+    fn transfers(&self, from: u64, to: u64) -> BoxFuture<'_, eyre::Result<Vec<IndexedTransfer>>>;
+
+    /// Balance backing the transfers of the router as of `block`.
+    fn balance(&self, block: u64) -> BoxFuture<'_, eyre::Result<RouteBalance>>;
+}
+
+/// Transfer event located in the chain of the router that emitted it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TransferLeg {
+    pub block_number: u64,
+    pub transaction_hash: [u8; 32],
+    /// Unix timestamp of the block, in seconds.
+    pub timestamp: u64,
+}
+
+impl From<&IndexedTransfer> for TransferLeg {
+    fn from(transfer: &IndexedTransfer) -> Self {
+        Self {
+            block_number: transfer.block_number,
+            transaction_hash: transfer.transaction_hash,
+            timestamp: transfer.block_timestamp,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransferStatus {
+    /// Sent by the origin router, not received yet.
+    InFlight,
+    /// Sent and received.
+    Delivered,
+    /// Received, its sending not indexed: sent before the start block of the origin router, or
+    /// by an untracked router.
+    ReceivedOnly,
+}
+
+/// Transfer of a warp route, correlated across its origin and destination routers through the
+/// id of the message carrying it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WarpTransfer {
+    pub message_id: [u8; 32],
+    pub origin: u32,
+    pub destination: u32,
+    pub recipient: [u8; 32],
+    pub amount: U256,
+    pub sent: Option<TransferLeg>,
+    pub received: Option<TransferLeg>,
+}
+
+impl WarpTransfer {
+    pub fn status(&self) -> TransferStatus {
+        match (self.sent, self.received) {
+            (_, None) => TransferStatus::InFlight,
+            (Some(_), Some(_)) => TransferStatus::Delivered,
+            (None, Some(_)) => TransferStatus::ReceivedOnly,
+        }
+    }
+
+    /// Time between the blocks of the sending and the receipt of a delivered transfer.
+    pub fn latency(&self) -> Option<Duration> {
+        match (self.sent, self.received) {
+            (Some(sent), Some(received)) => Some(Duration::from_secs(
+                received.timestamp.saturating_sub(sent.timestamp),
+            )),
+            _ => None,
+        }
+    }
+}
+
+/// Amounts transferred from an origin to a destination of a warp route.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RouteTotals {
+    /// Sum of the amounts sent by the origin router.
+    pub sent: U256,
+    /// Sum of the amounts received by the destination router.
+    pub received: U256,
+    /// Sum of the amounts sent and not received yet.
+    pub in_flight: U256,
+}
+
+/// Divergence between the collateral locked by a warp route and the supply of its synthetic
+/// tokens, once the transfers in flight are accounted for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CollateralAlert {
+    /// Domain of the collateral router.
+    pub collateral: u32,
+    pub locked: U256,
+    /// Sum of the total supplies of the synthetic tokens.
+    pub synthetic_supply: U256,
+    /// Sum of the amounts in flight, locked but not minted yet or burned but not unlocked yet.
+    pub in_flight: U256,
+}
+
+impl fmt::Display for CollateralAlert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "collateral of {} locked on domain {} diverges from the synthetic supply of {} with {} \
+             in flight",
+            self.locked, self.collateral, self.synthetic_supply, self.in_flight
+        )
+    }
+}
+
+struct TrackedRoute {
+    source: Box<dyn WarpRouteSource>,
+    next_block: u64,
+}
+
+/// Tracks the transfers of a warp route from the events of its routers, joining the
+/// `SentTransferRemote` of the origin with the `ReceivedTransferRemote` of the destination
+/// through the id of the message carrying the transfer.
+///
+/// Routers are indexed `confirmations` blocks behind the head of their chain, reorgs deeper than
+/// that being left undetected.
+pub struct WarpTracker {
+    routes: BTreeMap<u32, TrackedRoute>,
+    transfers: HashMap<[u8; 32], WarpTransfer>,
+    totals: BTreeMap<(u32, u32), RouteTotals>,
+    max_block_range: u64,
+    confirmations: u64,
+    tolerance: U256,
+}
+
+impl Default for WarpTracker {
+    fn default() -> Self {
+        Self {
+            routes: BTreeMap::new(),
+            transfers: HashMap::new(),
+            totals: BTreeMap::new(),
+            max_block_range: 1000,
+            confirmations: 0,
+            tolerance: U256::zero(),
+        }
+    }
+}
+
+impl WarpTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Tracks the router of `domain` from `start_block`.
+    pub fn with_route(
+        mut self,
+        domain: u32,
+        source: impl WarpRouteSource + 'static,
+        start_block: u64,
+    ) -> Self {
+        self.routes.insert(
+            domain,
+            TrackedRoute {
+                source: Box::new(source),
+                next_block: start_block,
+            },
+        );
+        self
+    }
+
+    /// Maximum number of blocks indexed per range, 1000 by default and at least 1.
+    pub fn with_max_block_range(mut self, max_block_range: u64) -> Self {
+        self.max_block_range = max_block_range.max(1);
+        self
+    }
+
+    /// Blocks left unindexed behind the head of each chain, 0 by default.
+    pub fn with_confirmations(mut self, confirmations: u64) -> Self {
+        self.confirmations = confirmations;
+        self
+    }
+
+    /// Divergence between the collateral and the synthetic supply tolerated by
+    /// [`WarpTracker::check_collateral`], such as transfers sent before the start blocks.
+    pub fn with_tolerance(mut self, tolerance: U256) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Indexes the transfers of every router up to the confirmed head of its chain.
+    pub async fn sync(&mut self) -> eyre::Result<()> {
+        let domains: Vec<u32> = self.routes.keys().copied().collect();
+        for domain in domains {
+            let latest_block = self.routes[&domain].source.latest_block().await?;
+            let Some(confirmed) = latest_block.checked_sub(self.confirmations) else {
+                continue;
+            };
+
+            while self.routes[&domain].next_block <= confirmed {
+                let route = &self.routes[&domain];
+                let from = route.next_block;
+                let to = confirmed.min(from + self.max_block_range - 1);
+                let transfers = route.source.transfers(from, to).await?;
+
+                for transfer in &transfers {
+                    self.ingest(domain, transfer);
+                }
+                self.routes
+                    .get_mut(&domain)
+                    .expect("tracked route")
+                    .next_block = to + 1;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Records a transfer event of the router of `domain`.
+    pub fn ingest(&mut self, domain: u32, transfer: &IndexedTransfer) {
+        let leg = TransferLeg::from(transfer);
+        match transfer.event {
+            TransferEvent::Sent {
+                destination,
+                recipient,
+                amount,
+            } => {
+                let tracked = self
+                    .transfers
+                    .entry(transfer.message_id)
+                    .or_insert(WarpTransfer {
+                        message_id: transfer.message_id,
+                        origin: domain,
+                        destination,
+                        recipient,
+                        amount,
+                        sent: None,
+                        received: None,
+                    });
+                if tracked.sent.is_some() {
+                    return;
+                }
+                tracked.sent = Some(leg);
+
+                let totals = self.totals.entry((domain, destination)).or_default();
+                totals.sent += amount;
+                if tracked.received.is_none() {
+                    totals.in_flight += amount;
+                }
+            }
+            TransferEvent::Received {
+                origin,
+                recipient,
+                amount,
+            } => {
+                let tracked = self
+                    .transfers
+                    .entry(transfer.message_id)
+                    .or_insert(WarpTransfer {
+                        message_id: transfer.message_id,
+                        origin,
+                        destination: domain,
+                        recipient,
+                        amount,
+                        sent: None,
+                        received: None,
+                    });
+                if tracked.received.is_some() {
+                    return;
+                }
+                tracked.received = Some(leg);
+
+                let totals = self
+                    .totals
+                    .entry((tracked.origin, tracked.destination))
+                    .or_default();
+                totals.received += amount;
+                if tracked.sent.is_some() {
+                    totals.in_flight -= tracked.amount;
+                }
+            }
+        }
+    }
+
+    /// Transfer carried by message `message_id`.
+    pub fn transfer(&self, message_id: &[u8; 32]) -> Option<&WarpTransfer> {
+        self.transfers.get(message_id)
+    }
+
+    /// Transfers tracked, in no particular order.
+    pub fn transfers(&self) -> impl Iterator<Item = &WarpTransfer> {
+        self.transfers.values()
+    }
+
+    /// Transfers sent more than `age` before unix timestamp `now` and not received yet, oldest
+    /// first.
+    pub fn in_flight_older_than(&self, now: u64, age: Duration) -> Vec<&WarpTransfer> {
+        let before = now.saturating_sub(age.as_secs());
+        let mut transfers: Vec<&WarpTransfer> = self
+            .transfers()
+            .filter(|transfer| {
+                transfer.status() == TransferStatus::InFlight
+                    && transfer.sent.is_some_and(|sent| sent.timestamp < before)
+            })
+            .collect();
+        transfers.sort_by_key(|transfer| transfer.sent.map(|sent| sent.timestamp));
+        transfers
+    }
+
+    /// Totals of the transfers from each origin to each destination, keyed by `(origin,
+    /// destination)`.
+    pub fn totals(&self) -> &BTreeMap<(u32, u32), RouteTotals> {
+        &self.totals
+    }
+
+    /// Checks the collateral locked by the route against the supply of its synthetic tokens, each
+    /// read at the last block indexed on its chain, and returns an alert when they diverge by more
+    /// than the tolerance.
+    ///
+    /// Every amount sent by a router and not received yet was either locked without being minted
+    /// or burned without being unlocked, so that the collateral matches the synthetic supply plus
+    /// the amounts in flight.
+    pub async fn check_collateral(&self) -> eyre::Result<Option<CollateralAlert>> {
+        let mut collateral = None;
+        let mut synthetic_supply = U256::zero();
+        for (domain, route) in &self.routes {
+            let Some(block) = route.next_block.checked_sub(1) else {
+                continue;
+            };
+            match route.source.balance(block).await? {
+                RouteBalance::Collateral(locked) => {
+                    if let Some((other, _)) = collateral {
+                        eyre::bail!(
+                            "warp route is collateralized on both domains {other} and {domain}"
+                        );
+                    }
+                    collateral = Some((*domain, locked));
+                }
+                RouteBalance::Synthetic(supply) => synthetic_supply += supply,
+            }
+        }
+        let Some((domain, locked)) = collateral else {
+            return Ok(None);
+        };
+
+        let in_flight = self
+            .totals
+            .values()
+            .fold(U256::zero(), |sum, totals| sum + totals.in_flight);
+        let expected = synthetic_supply + in_flight;
+        let divergence = if locked > expected {
+            locked - expected
+        } else {
+            expected - locked
+        };
+        if divergence <= self.tolerance {
+            return Ok(None);
+        }
+
+        Ok(Some(CollateralAlert {
+            collateral: domain,
+            locked,
+            synthetic_supply,
+            in_flight,
+        }))
+    }
+}
@@ -0,0 +1,145 @@
+use cainome::cairo_serde::{CairoSerde, ContractAddress, U256 as CairoU256};
+use ethers::types::U256;
+use starknet::{
+    core::types::{BlockId, EventFilter, FieldElement},
+    macros::selector,
+    providers::{AnyProvider, Provider},
+};
+
+use crate::{
+    contracts::strk::test_erc20::TestERC20Reader,
+    indexer::{starknet_block_timestamp, starknet_mailbox_events},
+    message::u256_to_bytes,
+    utils::BoxFuture,
+};
+
+use super::{
+    match_transfers_with_timestamps, IndexedTransfer, RouteBalance, RouteToken, TransferEvent,
+    WarpRouteSource,
+};
+
+/// Maximum number of events fetched per `starknet_getEvents` page.
+const EVENTS_CHUNK_SIZE: u64 = 100;
+
+/// Router of a warp route on a Starknet chain, embedding the `TokenRouterComponent` events flat.
+pub struct StarknetWarpRoute {
+    pub provider: AnyProvider,
+    pub mailbox: FieldElement,
+    pub router: FieldElement,
+    pub token: RouteToken<FieldElement>,
+}
+
+impl StarknetWarpRoute {
+    pub fn new(
+        provider: AnyProvider,
+        mailbox: FieldElement,
+        router: FieldElement,
+        token: RouteToken<FieldElement>,
+    ) -> Self {
+        Self {
+            provider,
+            mailbox,
+            router,
+            token,
+        }
+    }
+
+    async fn transfers_between(&self, from: u64, to: u64) -> eyre::Result<Vec<IndexedTransfer>> {
+        let filter = EventFilter {
+            from_block: Some(BlockId::Number(from)),
+            to_block: Some(BlockId::Number(to)),
+            address: Some(self.router),
+            keys: Some(vec![vec![
+                selector!("SentTransferRemote"),
+                selector!("ReceivedTransferRemote"),
+            ]]),
+        };
+
+        let mut transfers = vec![];
+        let mut continuation_token = None;
+        loop {
+            let page = self
+                .provider
+                .get_events(filter.clone(), continuation_token, EVENTS_CHUNK_SIZE)
+                .await?;
+
+            for emitted in page.events {
+                // keys: selector, domain, recipient (u256); data: amount (u256)
+                let domain = u32::cairo_deserialize(&emitted.keys, 1)?;
+                let recipient = u256_to_bytes(&CairoU256::cairo_deserialize(&emitted.keys, 2)?);
+                let amount = U256::from_big_endian(&u256_to_bytes(&CairoU256::cairo_deserialize(
+                    &emitted.data,
+                    0,
+                )?));
+
+                let event = if emitted.keys[0] == selector!("SentTransferRemote") {
+                    TransferEvent::Sent {
+                        destination: domain,
+                        recipient,
+                        amount,
+                    }
+                } else {
+                    TransferEvent::Received {
+                        origin: domain,
+                        recipient,
+                        amount,
+                    }
+                };
+                transfers.push((emitted.transaction_hash.to_bytes_be(), event));
+            }
+
+            match page.continuation_token {
+                Some(token) => continuation_token = Some(token),
+                None => break,
+            }
+        }
+
+        let mailbox_events =
+            starknet_mailbox_events(&self.provider, self.mailbox, from, to).await?;
+        match_transfers_with_timestamps(
+            self.router.to_bytes_be(),
+            transfers,
+            mailbox_events,
+            |block_hash| starknet_block_timestamp(&self.provider, block_hash),
+        )
+        .await
+    }
+
+    async fn balance_at(&self, block: u64) -> eyre::Result<RouteBalance> {
+        let block = BlockId::Number(block);
+        let amount = |value: CairoU256| U256::from_big_endian(&u256_to_bytes(&value));
+
+        Ok(match self.token {
+            RouteToken::Collateral { token, holder } => {
+                let locked = TestERC20Reader::new(token, &self.provider)
+                    .balance_of(&ContractAddress(holder))
+                    .block_id(block)
+                    .call()
+                    .await?;
+                RouteBalance::Collateral(amount(locked))
+            }
+            RouteToken::Synthetic { token } => {
+                let supply = TestERC20Reader::new(token, &self.provider)
+                    .total_supply()
+                    .block_id(block)
+                    .call()
+                    .await?;
+                RouteBalance::Synthetic(amount(supply))
+            }
+        })
+    }
+}
+
+impl WarpRouteSource for StarknetWarpRoute {
+    fn latest_block(&self) -> BoxFuture<'_, eyre::Result<u64>> {
+        Box::pin(async move { Ok(self.provider.block_number().await?) })
+    }
+
+    fn transfers(&self, from: u64, to: u64) -> BoxFuture<'_, eyre::Result<Vec<IndexedTransfer>>> {
+        Box::pin(self.transfers_between(from, to))
+    }
+
+    fn balance(&self, block: u64) -> BoxFuture<'_, eyre::Result<RouteBalance>> {
+        Box::pin(self.balance_at(block))
+    }
+}
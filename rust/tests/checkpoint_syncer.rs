@@ -0,0 +1,323 @@
+#[allow(dead_code)]
+mod fixtures;
+#[allow(dead_code)]
+mod http;
+
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use ethers::{
+    signers::{LocalWallet, Signer},
+    types::Address,
+};
+use hyperlane_starknet::{
+    checkpoint::{Announcement, SignedAnnouncement},
+    checkpoint_syncer::{
+        storage_location_from_felts, storage_location_to_felts, syncer_from_location,
+        syncer_from_storage, CheckpointSyncer, LocalCheckpointSyncer, S3CheckpointSyncer,
+    },
+    relayer::{AnnouncedCheckpoints, CheckpointSource, StorageLocationSource},
+    utils::BoxFuture,
+    validator::sign_checkpoint,
+};
+
+use fixtures::{checkpoint, evm_signer, wallet};
+use http::{mock_server, Request};
+
+type Objects = Arc<Mutex<BTreeMap<String, Vec<u8>>>>;
+
+/// S3 stand-in serving the path style object requests from memory, whatever their signature.
+async fn mock_s3() -> (String, Objects) {
+    let objects = Objects::default();
+
+    let served = objects.clone();
+    let endpoint = mock_server(move |request: Request| {
+        let objects = served.clone();
+        async move {
+            let key = request.path().to_string();
+            match request.method.as_str() {
+                "PUT" => {
+                    objects.lock().unwrap().insert(key, request.body);
+                    ("200 OK", vec![])
+                }
+                "GET" => match objects.lock().unwrap().get(&key) {
+                    Some(object) => ("200 OK", object.clone()),
+                    None => (
+                        "404 Not Found",
+                        b"<Error><Code>NoSuchKey</Code></Error>".to_vec(),
+                    ),
+                },
+                _ => ("405 Method Not Allowed", vec![]),
+            }
+        }
+    })
+    .await;
+
+    (endpoint, objects)
+}
+
+/// `validator_announce` stand-in, counting the lookups of the announced locations.
+#[derive(Clone, Default)]
+struct MockAnnounce {
+    locations: BTreeMap<Address, Vec<String>>,
+    lookups: Arc<AtomicUsize>,
+}
+
+impl StorageLocationSource for MockAnnounce {
+    fn announced_locations(&self, validator: Address) -> BoxFuture<'_, eyre::Result<Vec<String>>> {
+        self.lookups.fetch_add(1, Ordering::SeqCst);
+        let locations = self.locations.get(&validator).cloned().unwrap_or_default();
+        Box::pin(async move { Ok(locations) })
+    }
+}
+
+fn temp_dir(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("{name}-{}", std::process::id()))
+}
+
+#[tokio::test]
+async fn test_local_checkpoints() {
+    let path = temp_dir("local-checkpoints");
+    let syncer = LocalCheckpointSyncer::new(&path).unwrap();
+    assert_eq!(syncer.latest_index().await.unwrap(), None);
+
+    let signer = evm_signer(wallet(7)).await;
+    for index in [0, 2, 1] {
+        let signed = sign_checkpoint(&signer, checkpoint(index)).await.unwrap();
+        syncer.write_checkpoint(&signed).await.unwrap();
+        assert_eq!(syncer.fetch_checkpoint(index).await.unwrap(), Some(signed));
+    }
+
+    // the latest index does not move back
+    assert_eq!(syncer.latest_index().await.unwrap(), Some(2));
+    assert_eq!(syncer.fetch_checkpoint(5).await.unwrap(), None);
+
+    // a relayer reads the storage from its announced location
+    let location = syncer.announcement_location();
+    assert!(location.starts_with("file:///"));
+    let reader = LocalCheckpointSyncer::from_location(&location).unwrap();
+    assert_eq!(reader.latest_index().await.unwrap(), Some(2));
+
+    std::fs::remove_dir_all(path).unwrap();
+}
+
+#[tokio::test]
+async fn test_local_layout_matches_upstream_agents() {
+    let path = temp_dir("local-layout");
+    let syncer = LocalCheckpointSyncer::new(&path).unwrap();
+
+    let signer = evm_signer(wallet(7)).await;
+    let signed = sign_checkpoint(&signer, checkpoint(2)).await.unwrap();
+    syncer.write_checkpoint(&signed).await.unwrap();
+
+    let read = |file: &str| -> serde_json::Value {
+        serde_json::from_str(&std::fs::read_to_string(path.join(file)).unwrap()).unwrap()
+    };
+    let json = read("2_with_id.json");
+    assert_eq!(json["value"]["checkpoint"]["mailbox_domain"], 1);
+    assert_eq!(json["value"]["checkpoint"]["index"], 2);
+    assert_eq!(
+        json["value"]["checkpoint"]["merkle_tree_hook_address"],
+        format!("0x{}", hex::encode([0x11; 32]))
+    );
+    assert_eq!(
+        json["value"]["message_id"],
+        format!("0x{}", hex::encode([0x22; 32]))
+    );
+    assert!(json["signature"]["v"].is_u64());
+    assert_eq!(
+        json["serialized_signature"],
+        format!("0x{}", hex::encode(&signed.signature))
+    );
+    assert_eq!(read("index.json"), 2);
+
+    let announcement = SignedAnnouncement {
+        value: Announcement {
+            validator: signer.address(),
+            mailbox_address: [0x33; 32],
+            mailbox_domain: 1,
+            storage_location: syncer.announcement_location(),
+        },
+        signature: signed.signature.clone(),
+    };
+    syncer.write_announcement(&announcement).await.unwrap();
+    let json = read("announcement.json");
+    assert_eq!(
+        json["value"]["validator"],
+        format!("{:?}", signer.address())
+    );
+    assert_eq!(
+        json["value"]["storage_location"],
+        announcement.value.storage_location
+    );
+    assert_eq!(syncer.announcement().unwrap(), Some(announcement));
+
+    std::fs::remove_dir_all(path).unwrap();
+}
+
+#[tokio::test]
+async fn test_s3_checkpoints() {
+    let (endpoint, objects) = mock_s3().await;
+    let syncer = S3CheckpointSyncer::new(
+        "bucket",
+        "us-east-1",
+        Some("validator".into()),
+        Some(&endpoint),
+    )
+    .unwrap();
+    assert_eq!(syncer.latest_index().await.unwrap(), None);
+    assert_eq!(
+        syncer.announcement_location(),
+        "s3://bucket/us-east-1/validator"
+    );
+
+    let signer = evm_signer(wallet(7)).await;
+    for index in [0, 2, 1] {
+        let signed = sign_checkpoint(&signer, checkpoint(index)).await.unwrap();
+        syncer.write_checkpoint(&signed).await.unwrap();
+        assert_eq!(syncer.fetch_checkpoint(index).await.unwrap(), Some(signed));
+    }
+    assert_eq!(syncer.latest_index().await.unwrap(), Some(2));
+    assert_eq!(syncer.fetch_checkpoint(5).await.unwrap(), None);
+
+    // objects keyed like the upstream agents do
+    let keys: Vec<_> = objects.lock().unwrap().keys().cloned().collect();
+    assert_eq!(
+        keys,
+        vec![
+            "bucket/validator/checkpoint_0_with_id.json",
+            "bucket/validator/checkpoint_1_with_id.json",
+            "bucket/validator/checkpoint_2_with_id.json",
+            "bucket/validator/checkpoint_latest_index.json",
+        ]
+    );
+
+    // relayers open the storage from its announced location
+    let reader = syncer_from_location("s3://bucket/us-east-1/validator", Some(&endpoint)).unwrap();
+    assert_eq!(reader.latest_index().await.unwrap(), Some(2));
+}
+
+#[tokio::test]
+async fn test_announced_checkpoints() {
+    let (endpoint, _) = mock_s3().await;
+    let signer = evm_signer(wallet(7)).await;
+    let syncer = S3CheckpointSyncer::new("bucket", "eu-west-1", None, Some(&endpoint)).unwrap();
+    let signed = sign_checkpoint(&signer, checkpoint(4)).await.unwrap();
+    syncer.write_checkpoint(&signed).await.unwrap();
+
+    let checkpoints =
+        AnnouncedCheckpoints::new().with_syncer(1, signer.address(), Arc::new(syncer));
+    assert_eq!(
+        checkpoints.latest_index(1, signer.address()).await.unwrap(),
+        Some(4)
+    );
+    assert_eq!(
+        checkpoints
+            .fetch_checkpoint(1, signer.address(), 4)
+            .await
+            .unwrap(),
+        Some(signed)
+    );
+
+    // origins without validator_announce cannot be resolved
+    assert!(checkpoints.latest_index(2, signer.address()).await.is_err());
+}
+
+#[tokio::test]
+async fn test_announced_checkpoints_resolve_locations() {
+    let (endpoint, _) = mock_s3().await;
+    let s3_validator = evm_signer(wallet(7)).await;
+    let s3 = S3CheckpointSyncer::new("bucket", "eu-west-1", None, Some(&endpoint)).unwrap();
+    s3.write_checkpoint(&sign_checkpoint(&s3_validator, checkpoint(4)).await.unwrap())
+        .await
+        .unwrap();
+
+    let path = temp_dir("announced-checkpoints");
+    let file_validator = evm_signer(LocalWallet::from_bytes(&[8; 32]).unwrap()).await;
+    let local = LocalCheckpointSyncer::new(&path).unwrap();
+    let signed = sign_checkpoint(&file_validator, checkpoint(2))
+        .await
+        .unwrap();
+    local.write_checkpoint(&signed).await.unwrap();
+
+    let announce = MockAnnounce {
+        locations: BTreeMap::from([
+            (
+                s3_validator.address(),
+                vec![
+                    local.announcement_location(),
+                    "s3://bucket/eu-west-1".to_string(),
+                ],
+            ),
+            // the latest location has no backend, the previous one is read
+            (
+                file_validator.address(),
+                vec![
+                    local.announcement_location(),
+                    "gs://bucket/checkpoints".to_string(),
+                ],
+            ),
+        ]),
+        ..Default::default()
+    };
+    let checkpoints = AnnouncedCheckpoints::new()
+        .with_announced_locations(1, announce.clone())
+        .with_s3_endpoint(&endpoint);
+
+    assert_eq!(
+        checkpoints
+            .latest_index(1, s3_validator.address())
+            .await
+            .unwrap(),
+        Some(4)
+    );
+    assert_eq!(
+        checkpoints
+            .latest_index(1, file_validator.address())
+            .await
+            .unwrap(),
+        Some(2)
+    );
+    assert_eq!(
+        checkpoints
+            .fetch_checkpoint(1, file_validator.address(), 2)
+            .await
+            .unwrap(),
+        Some(signed)
+    );
+    // storages are resolved once
+    assert_eq!(announce.lookups.load(Ordering::SeqCst), 2);
+
+    // validators that announced nothing have no checkpoints
+    let silent = LocalWallet::from_bytes(&[9; 32]).unwrap().address();
+    assert_eq!(checkpoints.latest_index(1, silent).await.unwrap(), None);
+
+    std::fs::remove_dir_all(path).unwrap();
+}
+
+#[test]
+fn test_storage_locations() {
+    let location = "s3://hyperlane-validator-signatures-starknet/us-east-1/folder";
+    let felts = storage_location_to_felts(location);
+    assert_eq!(felts.len(), 2);
+    assert_eq!(storage_location_from_felts(&felts), location);
+
+    assert!(syncer_from_location("gs://bucket", None).is_err());
+    assert!(syncer_from_location("s3://bucket", None).is_err());
+    assert!(syncer_from_location("file:///tmp/checkpoints", None).is_ok());
+
+    let path = std::env::temp_dir().join(format!("storage-{}", std::process::id()));
+    let syncer = syncer_from_storage(path.to_str().unwrap(), None).unwrap();
+    assert_eq!(
+        syncer.announcement_location(),
+        format!("file://{}", path.display())
+    );
+    assert!(path.is_dir());
+    std::fs::remove_dir_all(&path).unwrap();
+    assert!(syncer_from_storage("s3://bucket", None).is_err());
+}
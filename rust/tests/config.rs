@@ -0,0 +1,92 @@
+use ethers::types::Address;
+use hyperlane_starknet::config::{Config, Protocol, SignerConfig};
+use starknet::core::types::FieldElement;
+
+const CONFIG: &str = r#"{
+    "chains": {
+        "katana": {
+            "rpc_url": "http://localhost:5050",
+            "domain": 2,
+            "addresses": {
+                "mailbox": "0x064bb5e29a7e9fc67dedabb6d1c385050feef028563078daa9025dd8b218e596"
+            },
+            "account": {
+                "address": "0xb3ff441a68610b30fd5e2abbf3a1548eb6ba6f3559f2862bf2dc757e5828ca"
+            }
+        },
+        "sepolia": {
+            "rpc_url": "http://localhost:5051",
+            "domain": 3,
+            "account": {
+                "address": "0x1",
+                "legacy": true,
+                "signer": { "type": "keystore", "path": "keystore.json" }
+            }
+        },
+        "anvil": {
+            "protocol": "ethereum",
+            "rpc_url": "http://localhost:8545",
+            "domain": 1,
+            "addresses": {
+                "mailbox": "0x5FbDB2315678afecb367f032d93F642f64180aa3",
+                "invalid": "0x064bb5e29a7e9fc67dedabb6d1c385050feef028563078daa9025dd8b218e596"
+            }
+        }
+    }
+}"#;
+
+#[test]
+fn test_load_chain_config() -> eyre::Result<()> {
+    let config: Config = serde_json::from_str(CONFIG)?;
+
+    let katana = config.chain(Some("katana"))?;
+    assert_eq!(katana.domain, 2);
+    assert_eq!(
+        katana.mailbox()?,
+        FieldElement::from_hex_be(
+            "0x064bb5e29a7e9fc67dedabb6d1c385050feef028563078daa9025dd8b218e596"
+        )?
+    );
+    assert!(katana.address("validator_announce").is_err());
+
+    // env private key signer by default
+    let account = katana.account.as_ref().unwrap();
+    assert!(!account.legacy);
+    assert!(matches!(&account.signer, SignerConfig::Env { var } if var == "STARKNET_PRIVATE_KEY"));
+
+    let sepolia = config.chain_by_domain(3)?;
+    let account = sepolia.account.as_ref().unwrap();
+    assert!(account.legacy);
+    assert!(matches!(
+        &account.signer,
+        SignerConfig::Keystore { path, password_var }
+            if path == "keystore.json" && password_var == "STARKNET_KEYSTORE_PASSWORD"
+    ));
+
+    // the chain must be named when several are configured
+    assert!(config.chain(None).is_err());
+    assert!(config.chain(Some("mainnet")).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_evm_chain_config() -> eyre::Result<()> {
+    let config: Config = serde_json::from_str(CONFIG)?;
+
+    let katana = config.chain(Some("katana"))?;
+    assert_eq!(katana.protocol, Protocol::Starknet);
+    assert!(katana.evm_provider().is_err());
+
+    let anvil = config.chain_by_domain(1)?;
+    assert_eq!(anvil.protocol, Protocol::Ethereum);
+    assert!(anvil.provider().is_err());
+    assert!(anvil.evm_provider().is_ok());
+    assert_eq!(
+        anvil.evm_address("mailbox")?,
+        "0x5FbDB2315678afecb367f032d93F642f64180aa3".parse::<Address>()?
+    );
+    assert!(anvil.evm_address("invalid").is_err());
+
+    Ok(())
+}
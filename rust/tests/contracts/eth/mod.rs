@@ -1,8 +1,9 @@
-mod bind;
 mod deploy;
 mod setup;
 mod types;
 
+use hyperlane_starknet::contracts::eth::bind;
+
 pub use bind::*;
 pub use deploy::deploy;
 pub use setup::{setup_env, Env};
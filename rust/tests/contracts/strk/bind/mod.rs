@@ -1,10 +0,0 @@
-#[allow(clippy::all)]
-pub mod ism;
-#[allow(clippy::all)]
-pub mod mailbox;
-#[allow(clippy::all)]
-pub mod multisig_ism;
-#[allow(clippy::all)]
-pub mod routing;
-#[allow(clippy::all)]
-pub mod validator_announce;
@@ -1,4 +1,3 @@
-mod bind;
 mod deploy;
 mod hook;
 mod ism;
@@ -6,6 +5,8 @@ mod setup;
 mod types;
 mod utils;
 
+use hyperlane_starknet::contracts::strk::bind;
+
 pub use bind::*;
 pub use deploy::*;
 pub use setup::{setup_env, Env};
@@ -1,11 +1,8 @@
 use std::collections::BTreeMap;
 
-use starknet::{
-    accounts::SingleOwnerAccount, core::types::FieldElement, providers::AnyProvider,
-    signers::LocalWallet,
-};
+use starknet::core::types::FieldElement;
 
-pub type StarknetAccount = SingleOwnerAccount<AnyProvider, LocalWallet>;
+pub use hyperlane_starknet::contracts::strk::StarknetAccount;
 
 #[derive(serde::Serialize, serde::Deserialize)]
 pub struct CodesMap(pub BTreeMap<String, u64>);
@@ -0,0 +1,111 @@
+#[allow(dead_code)]
+mod fixtures;
+
+use ethers::{
+    core::k256::{elliptic_curve::PrimeField, Scalar},
+    signers::{LocalWallet, Signer},
+    types::H256,
+    utils::keccak256,
+};
+use hyperlane_starknet::ecdsa::{
+    is_eth_signature_valid, normalize_signature, recover_signer, sort_signatures, EcdsaError,
+};
+
+use fixtures::wallets;
+
+fn sign(wallet: &LocalWallet, digest: &[u8; 32]) -> Vec<u8> {
+    wallet.sign_hash(H256(*digest)).unwrap().to_vec()
+}
+
+#[test]
+fn test_recover_signer() {
+    let wallet = &wallets(1)[0];
+    let digest = keccak256(b"checkpoint");
+    let signature = sign(wallet, &digest);
+
+    assert_eq!(recover_signer(&digest, &signature), Ok(wallet.address()));
+    assert!(is_eth_signature_valid(
+        &digest,
+        &signature,
+        wallet.address()
+    ));
+    assert!(!is_eth_signature_valid(
+        &keccak256(b"other"),
+        &signature,
+        wallet.address()
+    ));
+
+    assert_eq!(
+        recover_signer(&digest, &signature[..64]),
+        Err(EcdsaError::InvalidLength(64))
+    );
+    assert_eq!(
+        recover_signer(&digest, &[0u8; 65]),
+        Err(EcdsaError::InvalidSignature)
+    );
+}
+
+#[test]
+fn test_normalize_v() {
+    let wallet = &wallets(1)[0];
+    let digest = keccak256(b"checkpoint");
+    let signature = sign(wallet, &digest);
+
+    // 0/1 flip the y parity read by Starknet
+    let mut raw = signature.clone();
+    raw[64] -= 27;
+    assert!(!is_eth_signature_valid(&digest, &raw, wallet.address()));
+
+    let normalized = normalize_signature(&raw).unwrap();
+    assert_eq!(normalized.to_vec(), signature);
+    assert!(is_eth_signature_valid(
+        &digest,
+        &normalized,
+        wallet.address()
+    ));
+    assert_eq!(normalize_signature(&signature).unwrap().to_vec(), signature);
+
+    raw[64] = 2;
+    assert_eq!(normalize_signature(&raw), Err(EcdsaError::InvalidV(2)));
+}
+
+#[test]
+fn test_high_s_signature() {
+    let wallet = &wallets(1)[0];
+    let digest = keccak256(b"checkpoint");
+    let mut signature = sign(wallet, &digest);
+
+    // (r, n - s) with the opposite y parity is the same signature
+    let s = Scalar::from_repr(<[u8; 32]>::try_from(&signature[32..64]).unwrap().into()).unwrap();
+    signature[32..64].copy_from_slice(&(-s).to_bytes());
+    signature[64] = if signature[64] == 27 { 28 } else { 27 };
+
+    assert_eq!(recover_signer(&digest, &signature), Ok(wallet.address()));
+}
+
+#[test]
+fn test_sort_signatures() {
+    let wallets = wallets(4);
+    let validators: Vec<_> = wallets[..3].iter().map(|w| w.address()).collect();
+    let digest = keccak256(b"checkpoint");
+
+    let mut from_two = sign(&wallets[2], &digest);
+    from_two[64] -= 27;
+    let signatures = vec![
+        from_two,
+        sign(&wallets[0], &digest),
+        sign(&wallets[2], &digest),
+    ];
+
+    let sorted = sort_signatures(&digest, &signatures, &validators).unwrap();
+    assert_eq!(sorted.len(), 2);
+    assert_eq!(recover_signer(&digest, &sorted[0]), Ok(validators[0]));
+    assert_eq!(recover_signer(&digest, &sorted[1]), Ok(validators[2]));
+    assert!(sorted.iter().all(|s| s[64] == 27 || s[64] == 28));
+
+    let signatures = vec![sign(&wallets[3], &digest)];
+    assert_eq!(
+        sort_signatures(&digest, &signatures, &validators),
+        Err(EcdsaError::UnknownSigner(wallets[3].address()))
+    );
+}
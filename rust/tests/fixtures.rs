@@ -0,0 +1,72 @@
+use std::sync::Arc;
+
+use ethers::{signers::LocalWallet, utils::keccak256};
+use hyperlane_starknet::{
+    checkpoint::{Checkpoint, CheckpointWithMessageId, TREE_DEPTH},
+    message::{HyperlaneMessage, HYPERLANE_VERSION},
+    signer::{EvmSigner, LocalSigner},
+};
+
+/// Message of `nonce` from domain 1 to domain 2, tests overriding the fields they depend on.
+pub fn message(nonce: u32) -> HyperlaneMessage {
+    HyperlaneMessage {
+        version: HYPERLANE_VERSION,
+        nonce,
+        origin: 1,
+        sender: [0xaa; 32],
+        destination: 2,
+        recipient: [0xbb; 32],
+        body: b"hello".to_vec(),
+    }
+}
+
+/// Wallet of the secp256k1 key `index`, zero but its last byte.
+pub fn wallet(index: u8) -> LocalWallet {
+    let mut key = [0u8; 32];
+    key[31] = index;
+    LocalWallet::from_bytes(&key).unwrap()
+}
+
+/// Wallets of the keys 1 to `count`.
+pub fn wallets(count: usize) -> Vec<LocalWallet> {
+    (1..=count).map(|i| wallet(i as u8)).collect()
+}
+
+/// [`EvmSigner`] of the key of `wallet`.
+pub async fn evm_signer(wallet: LocalWallet) -> EvmSigner {
+    EvmSigner::new(Arc::new(LocalSigner::Secp256k1(wallet)))
+        .await
+        .unwrap()
+}
+
+/// Checkpoint `index` of the merkle tree hook `[0x11; 32]` of domain 1, with root
+/// `[index; 32]`.
+pub fn checkpoint(index: u32) -> CheckpointWithMessageId {
+    CheckpointWithMessageId {
+        checkpoint: Checkpoint {
+            merkle_tree_hook: [0x11; 32],
+            origin: 1,
+            root: [index as u8; 32],
+            index,
+        },
+        message_id: [0x22; 32],
+    }
+}
+
+/// Proof of leaf `index` in the merkle tree holding `leaves`.
+pub fn proof(leaves: &[[u8; 32]], index: usize) -> [[u8; 32]; TREE_DEPTH] {
+    let mut proof = [[0u8; 32]; TREE_DEPTH];
+    let mut level = leaves.to_vec();
+    let mut zero = [0u8; 32];
+    let mut index = index;
+    for node in proof.iter_mut() {
+        *node = level.get(index ^ 1).copied().unwrap_or(zero);
+        level = level
+            .chunks(2)
+            .map(|pair| keccak256([pair[0], *pair.get(1).unwrap_or(&zero)].concat()))
+            .collect();
+        zero = keccak256([zero, zero].concat());
+        index >>= 1;
+    }
+    proof
+}
@@ -0,0 +1,85 @@
+use std::sync::{Arc, Mutex};
+
+use hyperlane_starknet::{
+    checkpoint::{Checkpoint, IncrementalMerkle},
+    utils::BoxFuture,
+    validator::{InsertedIntoTree, MerkleTreeHookSource},
+};
+
+/// Chain of a merkle tree hook: its latest block and the message ids inserted, along with the
+/// block of their insertion.
+#[derive(Default)]
+struct HookChain {
+    latest_block: u64,
+    inserted: Vec<(u64, [u8; 32])>,
+}
+
+/// Merkle tree hook of domain 1 at `[0x11; 32]`, its chain being replaced on reorgs.
+#[derive(Clone, Default)]
+pub struct MockHook {
+    chain: Arc<Mutex<HookChain>>,
+}
+
+impl MockHook {
+    pub fn set(&self, latest_block: u64, inserted: &[(u64, u8)]) {
+        *self.chain.lock().unwrap() = HookChain {
+            latest_block,
+            inserted: inserted
+                .iter()
+                .map(|(block, id)| (*block, [*id; 32]))
+                .collect(),
+        };
+    }
+}
+
+impl MerkleTreeHookSource for MockHook {
+    fn origin(&self) -> u32 {
+        1
+    }
+
+    fn merkle_tree_hook(&self) -> [u8; 32] {
+        [0x11; 32]
+    }
+
+    fn latest_block(&self) -> BoxFuture<'_, eyre::Result<u64>> {
+        let latest_block = self.chain.lock().unwrap().latest_block;
+        Box::pin(async move { Ok(latest_block) })
+    }
+
+    fn latest_checkpoint(&self, block: u64) -> BoxFuture<'_, eyre::Result<Option<Checkpoint>>> {
+        let mut tree = IncrementalMerkle::default();
+        for (inserted_at, id) in &self.chain.lock().unwrap().inserted {
+            if *inserted_at <= block {
+                tree.ingest(*id);
+            }
+        }
+        let checkpoint = (tree.count() > 0).then(|| Checkpoint {
+            merkle_tree_hook: self.merkle_tree_hook(),
+            origin: self.origin(),
+            root: tree.root(),
+            index: tree.count() - 1,
+        });
+        Box::pin(async move { Ok(checkpoint) })
+    }
+
+    fn inserted_ids(
+        &self,
+        from: u64,
+        to: u64,
+    ) -> BoxFuture<'_, eyre::Result<Vec<InsertedIntoTree>>> {
+        let inserted = self
+            .chain
+            .lock()
+            .unwrap()
+            .inserted
+            .iter()
+            .enumerate()
+            .filter(|(_, (block, _))| (from..=to).contains(block))
+            .map(|(index, (_, id))| InsertedIntoTree {
+                id: *id,
+                index: index as u32,
+            })
+            .collect();
+        Box::pin(async move { Ok(inserted) })
+    }
+}
@@ -0,0 +1,285 @@
+use std::collections::HashMap;
+
+use cainome::cairo_serde::U256;
+use ethers::types::U256 as EthU256;
+use hyperlane_starknet::{
+    contracts::strk::hook,
+    hook::{inspect_dispatch_hooks, inspect_hook, HookKind, HookSource, HookType},
+    message::HYPERLANE_VERSION,
+    utils::BoxFuture,
+};
+use starknet::core::types::FieldElement;
+
+const MAILBOX: u64 = 0x1000;
+const DESTINATION: u32 = 5;
+
+/// Configuration of a hook deployed on the mock chain.
+enum HookConfig {
+    /// Hooks configured by destination.
+    Routing(HashMap<u32, FieldElement>),
+    ProtocolFee {
+        protocol_fee: u64,
+        beneficiary: FieldElement,
+    },
+    Leaf,
+}
+
+struct MockHooks {
+    required: FieldElement,
+    default: FieldElement,
+    /// Type, quote and configuration of the hooks, a missing quote reverting.
+    hooks: HashMap<FieldElement, (HookType, Option<u64>, HookConfig)>,
+}
+
+impl MockHooks {
+    fn new(required: u64, default: u64) -> Self {
+        Self {
+            required: felt(required),
+            default: felt(default),
+            hooks: HashMap::new(),
+        }
+    }
+
+    fn with(
+        mut self,
+        address: u64,
+        hook_type: HookType,
+        quote: Option<u64>,
+        config: HookConfig,
+    ) -> Self {
+        self.hooks.insert(felt(address), (hook_type, quote, config));
+        self
+    }
+
+    fn get(&self, hook: FieldElement) -> eyre::Result<&(HookType, Option<u64>, HookConfig)> {
+        self.hooks
+            .get(&hook)
+            .ok_or_else(|| eyre::eyre!("no contract at {hook:#x}"))
+    }
+}
+
+fn felt(value: u64) -> FieldElement {
+    FieldElement::from(value)
+}
+
+fn ready<T: Send + 'static>(result: eyre::Result<T>) -> BoxFuture<'static, eyre::Result<T>> {
+    Box::pin(std::future::ready(result))
+}
+
+impl HookSource for MockHooks {
+    fn required_hook(&self, mailbox: FieldElement) -> BoxFuture<'_, eyre::Result<FieldElement>> {
+        assert_eq!(mailbox, felt(MAILBOX));
+        ready(Ok(self.required))
+    }
+
+    fn default_hook(&self, mailbox: FieldElement) -> BoxFuture<'_, eyre::Result<FieldElement>> {
+        assert_eq!(mailbox, felt(MAILBOX));
+        ready(Ok(self.default))
+    }
+
+    fn hook_type(&self, hook: FieldElement) -> BoxFuture<'_, eyre::Result<HookType>> {
+        ready(self.get(hook).map(|(hook_type, _, _)| *hook_type))
+    }
+
+    fn quote_dispatch<'a>(
+        &'a self,
+        hook: FieldElement,
+        _metadata: &'a hook::Bytes,
+        message: &'a hook::Message,
+    ) -> BoxFuture<'a, eyre::Result<EthU256>> {
+        assert_eq!(message.destination, DESTINATION);
+        ready(match self.get(hook) {
+            Ok((_, Some(quote), _)) => Ok(EthU256::from(*quote)),
+            Ok((_, None, _)) => Err(eyre::eyre!("execution reverted: destination not supported")),
+            Err(err) => Err(err),
+        })
+    }
+
+    fn routing_hook(
+        &self,
+        hook: FieldElement,
+        destination: u32,
+    ) -> BoxFuture<'_, eyre::Result<FieldElement>> {
+        ready(match self.get(hook) {
+            Ok((_, _, HookConfig::Routing(hooks))) => Ok(hooks
+                .get(&destination)
+                .copied()
+                .unwrap_or(FieldElement::ZERO)),
+            _ => Err(eyre::eyre!("not a routing hook")),
+        })
+    }
+
+    fn protocol_fee(&self, hook: FieldElement) -> BoxFuture<'_, eyre::Result<EthU256>> {
+        ready(match self.get(hook) {
+            Ok((_, _, HookConfig::ProtocolFee { protocol_fee, .. })) => {
+                Ok(EthU256::from(*protocol_fee))
+            }
+            _ => Err(eyre::eyre!("not a protocol fee hook")),
+        })
+    }
+
+    fn beneficiary(&self, hook: FieldElement) -> BoxFuture<'_, eyre::Result<FieldElement>> {
+        ready(match self.get(hook) {
+            Ok((_, _, HookConfig::ProtocolFee { beneficiary, .. })) => Ok(*beneficiary),
+            _ => Err(eyre::eyre!("not a protocol fee hook")),
+        })
+    }
+}
+
+fn message() -> hook::Message {
+    hook::Message {
+        version: HYPERLANE_VERSION,
+        nonce: 0,
+        origin: 1,
+        sender: U256::from_bytes_be(&[0xaa; 32]),
+        destination: DESTINATION,
+        recipient: U256::from_bytes_be(&[0xbb; 32]),
+        body: hook::Bytes {
+            size: 0,
+            data: vec![],
+        },
+    }
+}
+
+fn metadata() -> hook::Bytes {
+    hook::Bytes {
+        size: 0,
+        data: vec![],
+    }
+}
+
+/// Protocol fee hook 1 required, routing hook 2 by default routing the destination to the merkle
+/// tree hook 3, and an IGP 4 a dispatch may use as its custom hook.
+fn hooks() -> MockHooks {
+    MockHooks::new(1, 2)
+        .with(
+            1,
+            HookType::ProtocolFee,
+            Some(10),
+            HookConfig::ProtocolFee {
+                protocol_fee: 10,
+                beneficiary: felt(0xbe),
+            },
+        )
+        .with(
+            2,
+            HookType::Routing,
+            Some(0),
+            HookConfig::Routing(HashMap::from([(DESTINATION, felt(3)), (6, felt(4))])),
+        )
+        .with(3, HookType::MerkleTree, Some(0), HookConfig::Leaf)
+        .with(
+            4,
+            HookType::InterchainGasPaymaster,
+            Some(7),
+            HookConfig::Leaf,
+        )
+}
+
+#[tokio::test]
+async fn test_inspect_dispatch_hooks_sums_the_hook_quotes() {
+    let hooks = hooks();
+    let dispatch = inspect_dispatch_hooks(&hooks, felt(MAILBOX), &metadata(), &message(), None)
+        .await
+        .unwrap();
+
+    assert_eq!(dispatch.destination, DESTINATION);
+    assert_eq!(dispatch.required.address, felt(1));
+    assert_eq!(dispatch.required.hook_type, HookType::ProtocolFee);
+    assert_eq!(dispatch.required.quote, Some(EthU256::from(10)));
+    match &dispatch.required.kind {
+        HookKind::ProtocolFee {
+            protocol_fee,
+            beneficiary,
+        } => {
+            assert_eq!(*protocol_fee, EthU256::from(10));
+            assert_eq!(*beneficiary, felt(0xbe));
+        }
+        kind => panic!("unexpected hook kind: {kind:?}"),
+    }
+
+    // the default routing hook only resolves the hook of the message destination
+    assert_eq!(dispatch.default.address, felt(2));
+    match &dispatch.default.kind {
+        HookKind::Routing {
+            destination,
+            hook: Some(hook),
+        } => {
+            assert_eq!(*destination, DESTINATION);
+            assert_eq!(hook.address, felt(3));
+            assert_eq!(hook.hook_type, HookType::MerkleTree);
+            assert!(matches!(hook.kind, HookKind::Leaf));
+        }
+        kind => panic!("unexpected hook kind: {kind:?}"),
+    }
+
+    assert_eq!(dispatch.total, Some(EthU256::from(10)));
+    assert!(dispatch.to_string().ends_with("Total fee: 10\n"));
+
+    // a custom hook replaces the default hook
+    let dispatch = inspect_dispatch_hooks(
+        &hooks,
+        felt(MAILBOX),
+        &metadata(),
+        &message(),
+        Some(felt(4)),
+    )
+    .await
+    .unwrap();
+    assert_eq!(dispatch.default.address, felt(4));
+    assert_eq!(dispatch.default.hook_type, HookType::InterchainGasPaymaster);
+    assert_eq!(dispatch.total, Some(EthU256::from(17)));
+}
+
+#[tokio::test]
+async fn test_inspect_dispatch_hooks_reports_reverted_quotes() {
+    let hooks = hooks().with(4, HookType::InterchainGasPaymaster, None, HookConfig::Leaf);
+    let dispatch = inspect_dispatch_hooks(
+        &hooks,
+        felt(MAILBOX),
+        &metadata(),
+        &message(),
+        Some(felt(4)),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(dispatch.default.quote, None);
+    assert!(dispatch
+        .default
+        .quote_error
+        .as_deref()
+        .unwrap()
+        .contains("destination not supported"));
+    assert_eq!(dispatch.total, None);
+    assert!(dispatch
+        .to_string()
+        .ends_with("Total fee: unavailable, a hook quote reverted\n"));
+}
+
+#[tokio::test]
+async fn test_inspect_hook_walks_routing_hooks() {
+    // no hook configured for the destination
+    let hooks = hooks().with(
+        5,
+        HookType::FallbackRouting,
+        None,
+        HookConfig::Routing(HashMap::from([(6, felt(4))])),
+    );
+    let node = inspect_hook(&hooks, felt(5), &metadata(), &message())
+        .await
+        .unwrap();
+    assert!(matches!(node.kind, HookKind::Routing { hook: None, .. }));
+
+    // routing cycle
+    let hooks = hooks.with(
+        6,
+        HookType::Routing,
+        Some(0),
+        HookConfig::Routing(HashMap::from([(DESTINATION, felt(6))])),
+    );
+    let err = inspect_hook(&hooks, felt(6), &metadata(), &message())
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("routing cycle"), "{err}");
+}
@@ -0,0 +1,102 @@
+use std::{future::Future, sync::Arc};
+
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+};
+
+/// Request received by a [`mock_server`].
+pub struct Request {
+    pub method: String,
+    /// Request target, query string included.
+    pub target: String,
+    /// Headers, their names lowercased.
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Path of the request target, without its leading slash nor its query string.
+    pub fn path(&self) -> &str {
+        let path = self.target.split('?').next().unwrap();
+        path.trim_start_matches('/')
+    }
+}
+
+/// HTTP/1.1 server on a local port answering every request with the status and body returned by
+/// `handler`, connections being kept alive. Returns the URL of the server.
+pub async fn mock_server<H, F>(handler: H) -> String
+where
+    H: Fn(Request) -> F + Send + Sync + 'static,
+    F: Future<Output = (&'static str, Vec<u8>)> + Send + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+
+    let handler = Arc::new(handler);
+    tokio::spawn(async move {
+        loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            tokio::spawn(serve(stream, handler.clone()));
+        }
+    });
+
+    url
+}
+
+async fn serve<H, F>(stream: TcpStream, handler: Arc<H>)
+where
+    H: Fn(Request) -> F,
+    F: Future<Output = (&'static str, Vec<u8>)>,
+{
+    let mut reader = BufReader::new(stream);
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).await.unwrap_or(0) == 0 {
+            return;
+        }
+        let mut parts = line.split_whitespace();
+        let method = parts.next().unwrap_or_default().to_string();
+        let target = parts.next().unwrap_or_default().to_string();
+
+        let mut headers = vec![];
+        loop {
+            let mut header = String::new();
+            reader.read_line(&mut header).await.unwrap();
+            match header.trim_end().split_once(':') {
+                Some((name, value)) => {
+                    headers.push((name.to_ascii_lowercase(), value.trim().to_string()))
+                }
+                None => break,
+            }
+        }
+        let length = headers
+            .iter()
+            .find(|(name, _)| name == "content-length")
+            .map_or(0, |(_, value)| value.parse().unwrap());
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).await.unwrap();
+
+        let (status, body) = handler(Request {
+            method,
+            target,
+            headers,
+            body,
+        })
+        .await;
+        let head = format!(
+            "HTTP/1.1 {status}\r\nContent-Length: {}\r\n\r\n",
+            body.len()
+        );
+        let stream = reader.get_mut();
+        stream.write_all(head.as_bytes()).await.unwrap();
+        stream.write_all(&body).await.unwrap();
+    }
+}
@@ -0,0 +1,319 @@
+use std::collections::HashMap;
+
+use ethers::types::Address;
+use hyperlane_starknet::{
+    ism::{inspect_ism, IsmKind, IsmNode, IsmSource, ModuleType},
+    utils::BoxFuture,
+};
+use starknet::core::types::FieldElement;
+
+/// Configuration of an ISM deployed on the mock chain.
+enum MockIsm {
+    Routing {
+        routes: Vec<(u32, FieldElement)>,
+        fallback: Option<FieldElement>,
+    },
+    /// Routing ISM of the origins given, its routes failing to be read.
+    Unreachable(Vec<u32>),
+    Aggregation {
+        threshold: u8,
+        modules: Vec<FieldElement>,
+    },
+    Multisig {
+        module_type: ModuleType,
+        threshold: u32,
+        validators: Vec<Address>,
+    },
+    Pausable(bool),
+    TrustedRelayer(FieldElement),
+    Noop,
+    Other(ModuleType),
+}
+
+#[derive(Default)]
+struct MockIsms(HashMap<FieldElement, MockIsm>);
+
+impl MockIsms {
+    fn with(mut self, address: u64, ism: MockIsm) -> Self {
+        self.0.insert(felt(address), ism);
+        self
+    }
+
+    fn get(&self, ism: FieldElement) -> eyre::Result<&MockIsm> {
+        self.0
+            .get(&ism)
+            .ok_or_else(|| eyre::eyre!("no contract at {ism:#x}"))
+    }
+}
+
+fn felt(value: u64) -> FieldElement {
+    FieldElement::from(value)
+}
+
+fn ready<T: Send + 'static>(result: eyre::Result<T>) -> BoxFuture<'static, eyre::Result<T>> {
+    Box::pin(std::future::ready(result))
+}
+
+impl IsmSource for MockIsms {
+    fn module_type(&self, ism: FieldElement) -> BoxFuture<'_, eyre::Result<ModuleType>> {
+        ready(self.get(ism).map(|ism| match ism {
+            MockIsm::Routing { .. } | MockIsm::Unreachable(_) => ModuleType::Routing,
+            MockIsm::Aggregation { .. } => ModuleType::Aggregation,
+            MockIsm::Multisig { module_type, .. } => *module_type,
+            MockIsm::Pausable(_) | MockIsm::TrustedRelayer(_) | MockIsm::Noop => ModuleType::Null,
+            MockIsm::Other(module_type) => *module_type,
+        }))
+    }
+
+    fn routing_domains(&self, ism: FieldElement) -> BoxFuture<'_, eyre::Result<Vec<u32>>> {
+        ready(match self.get(ism) {
+            Ok(MockIsm::Routing { routes, .. }) => {
+                Ok(routes.iter().map(|(origin, _)| *origin).collect())
+            }
+            Ok(MockIsm::Unreachable(origins)) => Ok(origins.clone()),
+            _ => Err(eyre::eyre!("not a routing ISM")),
+        })
+    }
+
+    fn routing_module(
+        &self,
+        ism: FieldElement,
+        origin: u32,
+    ) -> BoxFuture<'_, eyre::Result<Option<FieldElement>>> {
+        ready(match self.get(ism) {
+            Ok(MockIsm::Routing { routes, fallback }) => Ok(routes
+                .iter()
+                .find(|(domain, _)| *domain == origin)
+                .map(|(_, module)| *module)
+                .or(*fallback)),
+            Ok(MockIsm::Unreachable(_)) => Err(eyre::eyre!("connection refused")),
+            _ => Err(eyre::eyre!("not a routing ISM")),
+        })
+    }
+
+    fn aggregation_threshold(&self, ism: FieldElement) -> BoxFuture<'_, eyre::Result<u8>> {
+        ready(match self.get(ism) {
+            Ok(MockIsm::Aggregation { threshold, .. }) => Ok(*threshold),
+            _ => Err(eyre::eyre!("not an aggregation ISM")),
+        })
+    }
+
+    fn aggregation_modules(
+        &self,
+        ism: FieldElement,
+    ) -> BoxFuture<'_, eyre::Result<Vec<FieldElement>>> {
+        ready(match self.get(ism) {
+            Ok(MockIsm::Aggregation { modules, .. }) => Ok(modules.clone()),
+            _ => Err(eyre::eyre!("not an aggregation ISM")),
+        })
+    }
+
+    fn multisig_threshold(&self, ism: FieldElement) -> BoxFuture<'_, eyre::Result<u32>> {
+        ready(match self.get(ism) {
+            Ok(MockIsm::Multisig { threshold, .. }) => Ok(*threshold),
+            _ => Err(eyre::eyre!("not a multisig ISM")),
+        })
+    }
+
+    fn multisig_validators(
+        &self,
+        ism: FieldElement,
+    ) -> BoxFuture<'_, eyre::Result<Vec<FieldElement>>> {
+        ready(match self.get(ism) {
+            // validators are stored as the felts of their Ethereum addresses
+            Ok(MockIsm::Multisig { validators, .. }) => Ok(validators
+                .iter()
+                .map(|validator| FieldElement::from_byte_slice_be(validator.as_bytes()).unwrap())
+                .collect()),
+            _ => Err(eyre::eyre!("not a multisig ISM")),
+        })
+    }
+
+    fn is_paused(&self, ism: FieldElement) -> BoxFuture<'_, eyre::Result<bool>> {
+        ready(match self.get(ism) {
+            Ok(MockIsm::Pausable(paused)) => Ok(*paused),
+            _ => Err(eyre::eyre!("entrypoint not found")),
+        })
+    }
+
+    fn trusted_relayer(&self, ism: FieldElement) -> BoxFuture<'_, eyre::Result<FieldElement>> {
+        ready(self.get(ism).map(|ism| match ism {
+            MockIsm::TrustedRelayer(relayer) => *relayer,
+            _ => FieldElement::ZERO,
+        }))
+    }
+}
+
+fn routes(node: &IsmNode) -> Vec<(u32, FieldElement)> {
+    match &node.kind {
+        IsmKind::Routing { routes, .. } => routes
+            .iter()
+            .map(|route| (route.origin, route.ism.address))
+            .collect(),
+        kind => panic!("unexpected ISM kind: {kind:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_inspect_ism_dispatches_on_the_module_type() {
+    let isms = MockIsms::default()
+        .with(
+            1,
+            MockIsm::Aggregation {
+                threshold: 2,
+                modules: (2..=7).map(felt).collect(),
+            },
+        )
+        .with(
+            2,
+            MockIsm::Multisig {
+                module_type: ModuleType::MessageIdMultisig,
+                threshold: 1,
+                validators: vec![Address::repeat_byte(0x11)],
+            },
+        )
+        .with(
+            3,
+            MockIsm::Multisig {
+                module_type: ModuleType::MerkleRootMultisig,
+                threshold: 1,
+                validators: vec![Address::repeat_byte(0x22)],
+            },
+        )
+        .with(4, MockIsm::Pausable(true))
+        .with(5, MockIsm::TrustedRelayer(felt(0xaa)))
+        .with(6, MockIsm::Noop)
+        .with(7, MockIsm::Other(ModuleType::CcipRead));
+
+    let node = inspect_ism(&isms, felt(1), None).await.unwrap();
+    assert_eq!(node.address, felt(1));
+    assert_eq!(node.module_type, ModuleType::Aggregation);
+
+    let IsmKind::Aggregation { threshold, modules } = &node.kind else {
+        panic!("unexpected ISM kind: {:?}", node.kind);
+    };
+    assert_eq!(*threshold, 2);
+    let module_types: Vec<_> = modules.iter().map(|module| module.module_type).collect();
+    assert_eq!(
+        module_types,
+        [
+            ModuleType::MessageIdMultisig,
+            ModuleType::MerkleRootMultisig,
+            ModuleType::Null,
+            ModuleType::Null,
+            ModuleType::Null,
+            ModuleType::CcipRead,
+        ]
+    );
+
+    assert!(matches!(modules[0].kind, IsmKind::Multisig { .. }));
+    assert!(matches!(modules[1].kind, IsmKind::Multisig { .. }));
+    assert!(matches!(
+        modules[2].kind,
+        IsmKind::Pausable { paused: true }
+    ));
+    assert!(
+        matches!(modules[3].kind, IsmKind::TrustedRelayer { relayer } if relayer == felt(0xaa))
+    );
+    assert!(matches!(modules[4].kind, IsmKind::Noop));
+    assert!(matches!(modules[5].kind, IsmKind::Unknown));
+
+    assert!(node
+        .to_string()
+        .starts_with(&format!("aggregation {:#064x} (2 of 6)\n", felt(1))));
+}
+
+#[tokio::test]
+async fn test_inspect_ism_decodes_multisig_validators() {
+    let validators = vec![
+        "0x15d34aaf54267db7d7c367839aaf71a00a2c6a65"
+            .parse()
+            .unwrap(),
+        "0x9965507d1a55bcc2695c58ba16fb37d819b0a4dc"
+            .parse()
+            .unwrap(),
+        Address::repeat_byte(0xff),
+    ];
+    let isms = MockIsms::default().with(
+        1,
+        MockIsm::Multisig {
+            module_type: ModuleType::MessageIdMultisig,
+            threshold: 2,
+            validators: validators.clone(),
+        },
+    );
+
+    let node = inspect_ism(&isms, felt(1), None).await.unwrap();
+    match node.kind {
+        IsmKind::Multisig {
+            threshold,
+            validators: decoded,
+        } => {
+            assert_eq!(threshold, 2);
+            assert_eq!(decoded, validators);
+        }
+        kind => panic!("unexpected ISM kind: {kind:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_inspect_ism_resolves_routes() {
+    let isms = MockIsms::default()
+        .with(
+            1,
+            MockIsm::Routing {
+                routes: vec![(10, felt(2)), (20, felt(3))],
+                fallback: None,
+            },
+        )
+        .with(
+            4,
+            MockIsm::Routing {
+                routes: vec![(10, felt(2))],
+                fallback: Some(felt(3)),
+            },
+        )
+        .with(2, MockIsm::Noop)
+        .with(3, MockIsm::Pausable(false));
+
+    let node = inspect_ism(&isms, felt(1), None).await.unwrap();
+    assert_eq!(routes(&node), [(10, felt(2)), (20, felt(3))]);
+    assert!(matches!(node.kind, IsmKind::Routing { fallback: None, .. }));
+
+    // only the route of the origin is resolved
+    let node = inspect_ism(&isms, felt(1), Some(20)).await.unwrap();
+    assert_eq!(routes(&node), [(20, felt(3))]);
+    let node = inspect_ism(&isms, felt(1), Some(30)).await.unwrap();
+    assert!(routes(&node).is_empty());
+
+    // the fallback routing ISM routes the unknown origins to its fallback
+    let node = inspect_ism(&isms, felt(4), None).await.unwrap();
+    assert_eq!(routes(&node), [(10, felt(2))]);
+    assert!(
+        matches!(node.kind, IsmKind::Routing { fallback: Some(fallback), .. } if fallback == felt(3))
+    );
+    let node = inspect_ism(&isms, felt(4), Some(30)).await.unwrap();
+    assert_eq!(routes(&node), [(30, felt(3))]);
+
+    // a failure to read the routes is not taken for a missing fallback
+    let isms = isms.with(5, MockIsm::Unreachable(vec![10]));
+    let err = inspect_ism(&isms, felt(5), Some(30)).await.unwrap_err();
+    assert!(err.to_string().contains("connection refused"), "{err}");
+}
+
+#[tokio::test]
+async fn test_inspect_ism_refuses_routing_cycles() {
+    let isms = MockIsms::default().with(
+        1,
+        MockIsm::Routing {
+            routes: vec![(10, felt(1))],
+            fallback: None,
+        },
+    );
+
+    let err = inspect_ism(&isms, felt(1), None).await.unwrap_err();
+    assert!(err.to_string().contains("routing cycle"), "{err}");
+
+    // reads of the ISMs are not swallowed
+    assert!(inspect_ism(&isms, felt(2), None).await.is_err());
+}
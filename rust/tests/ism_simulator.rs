@@ -0,0 +1,227 @@
+#[allow(dead_code)]
+mod fixtures;
+
+use ethers::{
+    signers::{LocalWallet, Signer},
+    types::H256,
+};
+use hyperlane_starknet::{
+    checkpoint::checkpoint_digest,
+    ism::{simulate_verify, IsmKind, IsmNode, IsmRoute, ModuleType, VerifyError},
+    message::HyperlaneMessage,
+};
+use starknet::{core::types::FieldElement, macros::felt};
+
+use fixtures::{message, wallets};
+
+const ORIGIN: u32 = 1;
+const MERKLE_TREE_HOOK: [u8; 32] = [0x11; 32];
+const ROOT: [u8; 32] = [0x22; 32];
+const INDEX: u32 = 4;
+
+fn multisig(address: FieldElement, threshold: u32, wallets: &[LocalWallet]) -> IsmNode {
+    IsmNode {
+        address,
+        module_type: ModuleType::MessageIdMultisig,
+        kind: IsmKind::Multisig {
+            threshold,
+            validators: wallets.iter().map(|w| w.address()).collect(),
+        },
+    }
+}
+
+fn leaf(address: FieldElement, module_type: ModuleType, kind: IsmKind) -> IsmNode {
+    IsmNode {
+        address,
+        module_type,
+        kind,
+    }
+}
+
+/// Builds the `messageid_multisig_ism` metadata of `message`, signed by `signers` in order.
+fn message_id_metadata(message: &HyperlaneMessage, signers: &[&LocalWallet]) -> Vec<u8> {
+    let digest = checkpoint_digest(ORIGIN, &MERKLE_TREE_HOOK, &ROOT, INDEX, &message.id());
+
+    let mut metadata = vec![];
+    metadata.extend_from_slice(&MERKLE_TREE_HOOK);
+    metadata.extend_from_slice(&ROOT);
+    metadata.extend_from_slice(&INDEX.to_be_bytes());
+    for signer in signers {
+        let signature = signer.sign_hash(H256(digest)).unwrap();
+        metadata.extend_from_slice(&signature.to_vec());
+    }
+    metadata
+}
+
+/// Encodes the aggregation metadata, module metadata being laid out after the ranges table.
+fn aggregation_metadata(modules: &[Option<Vec<u8>>]) -> Vec<u8> {
+    let mut ranges = vec![];
+    let mut data = vec![];
+    let mut offset = (modules.len() * 8) as u32;
+    for module in modules {
+        match module {
+            Some(metadata) => {
+                ranges.extend_from_slice(&offset.to_be_bytes());
+                offset += metadata.len() as u32;
+                ranges.extend_from_slice(&offset.to_be_bytes());
+                data.extend_from_slice(metadata);
+            }
+            None => ranges.extend_from_slice(&[0u8; 8]),
+        }
+    }
+
+    ranges.extend(data);
+    ranges
+}
+
+#[test]
+fn test_multisig_verifies_ordered_signatures() {
+    let wallets = wallets(3);
+    let ism = multisig(felt!("0x1"), 2, &wallets);
+    let message = message(INDEX);
+
+    let metadata = message_id_metadata(&message, &[&wallets[0], &wallets[2]]);
+    assert_eq!(
+        simulate_verify(&ism, &metadata, &message, felt!("0x0")),
+        Ok(())
+    );
+}
+
+#[test]
+fn test_multisig_rejects_unordered_signatures() {
+    let wallets = wallets(3);
+    let ism = multisig(felt!("0x1"), 2, &wallets);
+    let message = message(INDEX);
+
+    let metadata = message_id_metadata(&message, &[&wallets[2], &wallets[0]]);
+    let failure = simulate_verify(&ism, &metadata, &message, felt!("0x0")).unwrap_err();
+    assert_eq!(
+        failure.error,
+        VerifyError::NoMatchForSignature {
+            index: 1,
+            signer: Some(wallets[0].address()),
+        }
+    );
+    assert_eq!(failure.path.len(), 1);
+}
+
+#[test]
+fn test_multisig_rejects_unknown_signer() {
+    let wallets = wallets(4);
+    let ism = multisig(felt!("0x1"), 2, &wallets[..3]);
+    let message = message(INDEX);
+
+    let metadata = message_id_metadata(&message, &[&wallets[0], &wallets[3]]);
+    let failure = simulate_verify(&ism, &metadata, &message, felt!("0x0")).unwrap_err();
+    assert_eq!(
+        failure.error,
+        VerifyError::NoMatchForSignature {
+            index: 1,
+            signer: Some(wallets[3].address()),
+        }
+    );
+
+    let metadata = message_id_metadata(&message, &[&wallets[0]]);
+    let failure = simulate_verify(&ism, &metadata, &message, felt!("0x0")).unwrap_err();
+    assert!(matches!(
+        failure.error,
+        VerifyError::MetadataTooShort { .. }
+    ));
+}
+
+#[test]
+fn test_aggregation_threshold_and_padding() {
+    let wallets = wallets(3);
+    let message = message(INDEX);
+    let ism = leaf(
+        felt!("0x1"),
+        ModuleType::Aggregation,
+        IsmKind::Aggregation {
+            threshold: 2,
+            modules: vec![
+                multisig(felt!("0x2"), 2, &wallets),
+                leaf(felt!("0x3"), ModuleType::Null, IsmKind::Noop),
+                leaf(
+                    felt!("0x4"),
+                    ModuleType::Null,
+                    IsmKind::Pausable { paused: true },
+                ),
+            ],
+        },
+    );
+
+    let mut multisig_metadata = message_id_metadata(&message, &[&wallets[0], &wallets[1]]);
+    multisig_metadata.resize(multisig_metadata.len().next_multiple_of(16), 0);
+    let metadata =
+        aggregation_metadata(&[Some(multisig_metadata.clone()), Some(vec![0; 16]), None]);
+    assert_eq!(
+        simulate_verify(&ism, &metadata, &message, felt!("0x0")),
+        Ok(())
+    );
+
+    // the contract left pads the last chunk of the module metadata
+    let unpadded = message_id_metadata(&message, &[&wallets[0], &wallets[1]]);
+    let metadata = aggregation_metadata(&[Some(unpadded), Some(vec![0; 16]), None]);
+    let failure = simulate_verify(&ism, &metadata, &message, felt!("0x0")).unwrap_err();
+    assert!(matches!(
+        failure.error,
+        VerifyError::NoMatchForSignature { index: 1, .. }
+    ));
+    assert_eq!(failure.path.len(), 2);
+
+    // a single module with metadata does not reach the threshold
+    let metadata = aggregation_metadata(&[Some(multisig_metadata), None, None]);
+    let failure = simulate_verify(&ism, &metadata, &message, felt!("0x0")).unwrap_err();
+    assert_eq!(
+        failure.error,
+        VerifyError::ThresholdNotReached {
+            threshold: 2,
+            verified: 1,
+        }
+    );
+
+    // every module with metadata must verify
+    let metadata = aggregation_metadata(&[None, Some(vec![0; 16]), Some(vec![0; 16])]);
+    let failure = simulate_verify(&ism, &metadata, &message, felt!("0x0")).unwrap_err();
+    assert_eq!(failure.error, VerifyError::Paused);
+}
+
+#[test]
+fn test_routing_and_trusted_relayer() {
+    let relayer = felt!("0x1234");
+    let ism = leaf(
+        felt!("0x1"),
+        ModuleType::Routing,
+        IsmKind::Routing {
+            routes: vec![IsmRoute {
+                origin: ORIGIN,
+                ism: leaf(
+                    felt!("0x2"),
+                    ModuleType::Null,
+                    IsmKind::TrustedRelayer { relayer },
+                ),
+            }],
+            fallback: None,
+        },
+    );
+
+    let message = message(INDEX);
+    assert_eq!(simulate_verify(&ism, &[], &message, relayer), Ok(()));
+
+    let failure = simulate_verify(&ism, &[], &message, felt!("0x1")).unwrap_err();
+    assert_eq!(
+        failure.error,
+        VerifyError::UntrustedRelayer {
+            relayer,
+            processor: felt!("0x1"),
+        }
+    );
+
+    let message = HyperlaneMessage {
+        origin: 5,
+        ..message
+    };
+    let failure = simulate_verify(&ism, &[], &message, relayer).unwrap_err();
+    assert_eq!(failure.error, VerifyError::NoRoute { origin: 5 });
+    assert_eq!(failure.path.len(), 1);
+}
@@ -12,12 +12,12 @@ use contracts::{
 use ethers::{
     prelude::parse_log, providers::Middleware, signers::Signer, types::TransactionReceipt,
 };
+use hyperlane_starknet::{contracts::strk::to_strk_message_bytes, tx_watcher::TxWatcher};
 use starknet::{
     accounts::{Account, ConnectedAccount},
     core::types::{Event, FieldElement, MaybePendingTransactionReceipt},
     core::utils::get_selector_from_name,
     macros::felt,
-    providers::{AnyProvider, Provider},
 };
 
 use crate::{
@@ -92,33 +92,6 @@ fn eth_dispatch_event_to_strk_message(event: DispatchFilter) -> Message {
     }
 }
 
-/// Convert a byte slice to a starknet message
-/// We have to pad the bytes to 16 bytes chunks
-/// see here for more info https://github.com/keep-starknet-strange/alexandria/blob/main/src/bytes/src/bytes.cairo#L16
-fn to_strk_message_bytes(bytes: &[u8]) -> Bytes {
-    // Calculate the required padding
-    let padding = (16 - (bytes.len() % 16)) % 16;
-    let total_len = bytes.len() + padding;
-
-    // Create a new byte vector with the necessary padding
-    let mut padded_bytes = Vec::with_capacity(total_len);
-    padded_bytes.extend_from_slice(bytes);
-    padded_bytes.extend(std::iter::repeat(0).take(padding));
-
-    let mut result = Vec::with_capacity(total_len / 16);
-    for chunk in padded_bytes.chunks_exact(16) {
-        // Convert each 16-byte chunk into a u128
-        let mut array = [0u8; 16];
-        array.copy_from_slice(chunk);
-        result.push(u128::from_be_bytes(array));
-    }
-
-    Bytes {
-        size: bytes.len() as u32,
-        data: result,
-    }
-}
-
 async fn send_msg_strk_to_evm<M, S>(
     from: &strk::Env,
     to: &eth::Env<M, S>,
@@ -145,25 +118,10 @@ where
         )
         .send()
         .await?;
-    let strk_provider: &AnyProvider = from.acc_owner.provider();
-    let dispatch_receipt = strk_provider
-        .get_transaction_receipt(dispatch_res.transaction_hash)
+    let outcome = TxWatcher::new(from.acc_owner.provider())
+        .wait(dispatch_res.transaction_hash)
         .await?;
-
-    let dispatch = match dispatch_receipt {
-        MaybePendingTransactionReceipt::PendingReceipt(pending_receipt) => match pending_receipt {
-            starknet::core::types::PendingTransactionReceipt::Invoke(receipt) => {
-                parse_dispatch_from_res(&receipt.events)
-            }
-            _ => return Err(eyre::eyre!("Unexpected receipt type, check the hash")),
-        },
-        MaybePendingTransactionReceipt::Receipt(receipt) => match receipt {
-            starknet::core::types::TransactionReceipt::Invoke(receipt) => {
-                parse_dispatch_from_res(&receipt.events)
-            }
-            _ => return Err(eyre::eyre!("Unexpected receipt type, check the hash")),
-        },
-    };
+    let dispatch = parse_dispatch_from_res(outcome.raw_events());
 
     println!("\nDispatched: {:?}", dispatch);
 
@@ -218,12 +176,11 @@ where
         .send()
         .await?;
 
-    let strk_provider: &AnyProvider = to.acc_owner.provider();
-    let process_receipt = strk_provider
-        .get_transaction_receipt(process_res.transaction_hash)
+    let outcome = TxWatcher::new(to.acc_owner.provider())
+        .wait(process_res.transaction_hash)
         .await?;
 
-    Ok(process_receipt)
+    Ok(outcome.receipt)
 }
 
 #[tokio::test]
@@ -0,0 +1,243 @@
+#[allow(dead_code)]
+mod fixtures;
+
+use std::sync::{Arc, Mutex};
+
+use hyperlane_starknet::{
+    indexer::{
+        IndexedEvent, IndexerCursor, IndexerSettings, IndexerUpdate, MailboxEvent,
+        MailboxEventSource, MailboxIndexer,
+    },
+    utils::BoxFuture,
+};
+
+use fixtures::message;
+
+/// Blocks of a mock chain, as their hash and the nonces of the messages they dispatch.
+type Blocks = Arc<Mutex<Vec<([u8; 32], Vec<u32>)>>>;
+
+struct MockChain {
+    blocks: Blocks,
+}
+
+impl MailboxEventSource for MockChain {
+    fn latest_block(&self) -> BoxFuture<'_, eyre::Result<u64>> {
+        let latest = self.blocks.lock().unwrap().len() as u64 - 1;
+        Box::pin(async move { Ok(latest) })
+    }
+
+    fn block_hash(&self, block: u64) -> BoxFuture<'_, eyre::Result<Option<[u8; 32]>>> {
+        let hash = self
+            .blocks
+            .lock()
+            .unwrap()
+            .get(block as usize)
+            .map(|(hash, _)| *hash);
+        Box::pin(async move { Ok(hash) })
+    }
+
+    fn block_timestamp(&self, block_hash: [u8; 32]) -> BoxFuture<'_, eyre::Result<u64>> {
+        Box::pin(async move { Ok(block_hash[0] as u64 * 12) })
+    }
+
+    fn events(&self, from: u64, to: u64) -> BoxFuture<'_, eyre::Result<Vec<IndexedEvent>>> {
+        let blocks = self.blocks.lock().unwrap();
+        let mut events = vec![];
+        for number in from..=to {
+            let (hash, nonces) = &blocks[number as usize];
+            for nonce in nonces {
+                let message = message(*nonce);
+                for event in [
+                    MailboxEvent::Dispatch(message.clone()),
+                    MailboxEvent::DispatchId(message.id()),
+                ] {
+                    events.push(IndexedEvent {
+                        block_number: number,
+                        block_hash: *hash,
+                        block_timestamp: None,
+                        transaction_hash: [*nonce as u8; 32],
+                        event,
+                    });
+                }
+            }
+        }
+
+        Box::pin(async move { Ok(events) })
+    }
+}
+
+/// Chain of `count` blocks, block `n` dispatching the message of nonce `n`.
+fn chain(count: u32) -> Blocks {
+    Arc::new(Mutex::new(
+        (0..count).map(|n| ([n as u8; 32], vec![n])).collect(),
+    ))
+}
+
+fn settings(max_block_range: u64) -> IndexerSettings {
+    IndexerSettings {
+        max_block_range,
+        confirmations: 0,
+        reorg_window: 4,
+    }
+}
+
+fn dispatched(update: &IndexerUpdate) -> Vec<u32> {
+    match update {
+        IndexerUpdate::Indexed { events, .. } => events
+            .iter()
+            .filter_map(|indexed| match &indexed.event {
+                MailboxEvent::Dispatch(message) => Some(message.nonce),
+                _ => None,
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
+#[tokio::test]
+async fn test_indexer_pages_block_ranges() {
+    let blocks = chain(8);
+    let mut indexer = MailboxIndexer::new(
+        MockChain {
+            blocks: blocks.clone(),
+        },
+        IndexerCursor::starting_at(2),
+        IndexerSettings {
+            confirmations: 1,
+            ..settings(3)
+        },
+    );
+
+    let update = indexer.next().await.unwrap();
+    assert!(matches!(
+        update,
+        IndexerUpdate::Indexed { from: 2, to: 4, .. }
+    ));
+    assert_eq!(dispatched(&update), vec![2, 3, 4]);
+
+    // the last block waits for its confirmation
+    let update = indexer.next().await.unwrap();
+    assert!(matches!(
+        update,
+        IndexerUpdate::Indexed { from: 5, to: 6, .. }
+    ));
+    assert_eq!(dispatched(&update), vec![5, 6]);
+    assert_eq!(indexer.next().await.unwrap(), IndexerUpdate::Synced);
+    assert_eq!(indexer.cursor().next_nonce, Some(7));
+
+    // a persisted cursor resumes where the indexer stopped
+    let cursor: IndexerCursor =
+        serde_json::from_str(&serde_json::to_string(indexer.cursor()).unwrap()).unwrap();
+    blocks.lock().unwrap().push(([8; 32], vec![8]));
+    let mut indexer = MailboxIndexer::new(
+        MockChain { blocks },
+        cursor,
+        IndexerSettings {
+            confirmations: 1,
+            ..settings(3)
+        },
+    );
+    let update = indexer.next().await.unwrap();
+    assert_eq!(dispatched(&update), vec![7]);
+}
+
+#[tokio::test]
+async fn test_indexer_indexes_a_block_per_range_of_zero_blocks() {
+    let mut indexer = MailboxIndexer::new(
+        MockChain { blocks: chain(2) },
+        IndexerCursor::starting_at(0),
+        settings(0),
+    );
+
+    for block in 0..2 {
+        assert!(matches!(
+            indexer.next().await.unwrap(),
+            IndexerUpdate::Indexed { from, to, .. } if from == block && to == block
+        ));
+    }
+    assert_eq!(indexer.next().await.unwrap(), IndexerUpdate::Synced);
+}
+
+#[tokio::test]
+async fn test_indexer_rewinds_reorgs() {
+    let blocks = chain(8);
+    let mut indexer = MailboxIndexer::new(
+        MockChain {
+            blocks: blocks.clone(),
+        },
+        IndexerCursor::starting_at(0),
+        settings(3),
+    );
+    while indexer.next().await.unwrap() != IndexerUpdate::Synced {}
+    assert_eq!(indexer.cursor().next_block, 8);
+
+    // blocks 4 on are replaced, block 5 now dispatching two messages
+    {
+        let mut blocks = blocks.lock().unwrap();
+        blocks.truncate(4);
+        blocks.push(([0x44; 32], vec![4]));
+        blocks.push(([0x55; 32], vec![5, 6]));
+    }
+
+    // indexing resumes after block 2, the end of the last range still in the chain
+    assert_eq!(
+        indexer.next().await.unwrap(),
+        IndexerUpdate::Reorg { from: 3 }
+    );
+    assert_eq!(indexer.cursor().next_nonce, Some(3));
+    let update = indexer.next().await.unwrap();
+    assert!(matches!(
+        update,
+        IndexerUpdate::Indexed { from: 3, to: 5, .. }
+    ));
+    assert_eq!(dispatched(&update), vec![3, 4, 5, 6]);
+    assert_eq!(indexer.next().await.unwrap(), IndexerUpdate::Synced);
+}
+
+#[tokio::test]
+async fn test_indexer_refuses_nonce_gaps() {
+    let blocks = chain(4);
+    blocks.lock().unwrap()[2].1 = vec![];
+    let mut indexer = MailboxIndexer::new(
+        MockChain { blocks },
+        IndexerCursor::starting_at(0),
+        settings(10),
+    );
+
+    let err = indexer.next().await.unwrap_err();
+    assert!(err.to_string().contains("events are missing"));
+    assert_eq!(indexer.cursor(), &IndexerCursor::starting_at(0));
+}
+
+#[tokio::test]
+async fn test_indexer_restarts_reorgs_past_its_window() {
+    let blocks = chain(8);
+    let mut indexer = MailboxIndexer::new(
+        MockChain {
+            blocks: blocks.clone(),
+        },
+        IndexerCursor::starting_at(1),
+        settings(1),
+    );
+    while indexer.next().await.unwrap() != IndexerUpdate::Synced {}
+    assert_eq!(indexer.cursor().indexed.len(), 4);
+
+    for block in blocks.lock().unwrap().iter_mut().skip(2) {
+        block.0 = [0xff; 32];
+    }
+
+    // indexing starts over from the start block of the cursor
+    assert_eq!(
+        indexer.next().await.unwrap(),
+        IndexerUpdate::Reorg { from: 1 }
+    );
+    assert_eq!(indexer.cursor(), &IndexerCursor::starting_at(1));
+    let mut nonces = vec![];
+    loop {
+        match indexer.next().await.unwrap() {
+            IndexerUpdate::Synced => break,
+            update => nonces.extend(dispatched(&update)),
+        }
+    }
+    assert_eq!(nonces, (1..8).collect::<Vec<_>>());
+}
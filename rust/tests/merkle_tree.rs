@@ -0,0 +1,132 @@
+#[allow(dead_code)]
+mod hook;
+
+use ethers::utils::keccak256;
+use hyperlane_starknet::{
+    checkpoint::{branch_root, IncrementalMerkle},
+    indexer::{MerkleTree, MerkleTreeIndexer},
+};
+
+use hook::MockHook;
+
+/// Root of the tree holding `ids`, in their insertion order.
+fn root(ids: &[u8]) -> [u8; 32] {
+    let mut tree = IncrementalMerkle::default();
+    for id in ids {
+        tree.ingest([*id; 32]);
+    }
+    tree.root()
+}
+
+#[test]
+fn test_merkle_tree_proves_past_roots() {
+    let leaves: Vec<[u8; 32]> = (0..37u8).map(|i| keccak256([i])).collect();
+
+    let mut tree = MerkleTree::default();
+    let mut roots = vec![];
+    let mut incremental = IncrementalMerkle::default();
+    for leaf in &leaves {
+        tree.ingest(*leaf);
+        incremental.ingest(*leaf);
+        roots.push(incremental.root());
+    }
+    assert_eq!(tree.count(), 37);
+    assert_eq!(tree.root(), incremental.root());
+
+    // every leaf against the root of every size of the tree holding it
+    for (root_index, root) in roots.iter().enumerate() {
+        for (leaf_index, leaf) in leaves[..=root_index].iter().enumerate() {
+            let proof = tree.proof(leaf_index as u32, root_index as u32).unwrap();
+            assert_eq!(branch_root(leaf, &proof, leaf_index as u32), *root);
+        }
+    }
+
+    assert_eq!(tree.leaf_index(&leaves[21]), Some(21));
+    assert_eq!(tree.leaf_index(&[0u8; 32]), None);
+
+    for (index, root) in roots.iter().enumerate() {
+        assert_eq!(tree.leaf(index as u32), Some(leaves[index]));
+        assert_eq!(tree.root_at(index as u32), Some(*root));
+    }
+    assert_eq!(tree.leaf(37), None);
+    assert_eq!(tree.root_at(37), None);
+
+    tree.truncate(10);
+    assert_eq!(tree.count(), 10);
+    assert_eq!(tree.root(), roots[9]);
+    assert_eq!(tree.leaf_index(&leaves[21]), None);
+    assert!(tree.proof(3, 10).is_err());
+    for leaf in &leaves[10..] {
+        tree.ingest(*leaf);
+    }
+    assert_eq!(tree.root(), roots[36]);
+    assert_eq!(
+        branch_root(&leaves[21], &tree.proof(21, 30).unwrap(), 21),
+        roots[30]
+    );
+}
+
+#[test]
+fn test_merkle_tree_refuses_unknown_proofs() {
+    let mut tree = MerkleTree::default();
+    assert!(tree.proof(0, 0).is_err());
+
+    tree.ingest([1; 32]);
+    tree.ingest([2; 32]);
+    assert!(tree.proof(0, 1).is_ok());
+    // leaf after the root, root after the last leaf
+    assert!(tree.proof(1, 0).is_err());
+    assert!(tree.proof(0, 2).is_err());
+}
+
+#[tokio::test]
+async fn test_merkle_tree_indexer_rewinds_reorganized_ranges() {
+    let hook = MockHook::default();
+    hook.set(4, &[(1, 1), (2, 2), (3, 3), (4, 4)]);
+    let mut indexer = MerkleTreeIndexer::new(hook.clone(), 0).with_max_block_range(2);
+    assert_eq!(indexer.sync().await.unwrap(), None);
+    assert_eq!(indexer.tree().root(), root(&[1, 2, 3, 4]));
+    assert_eq!(indexer.sync().await.unwrap(), None);
+
+    // block 4 is replaced, the ranges up to block 3 being kept
+    hook.set(5, &[(1, 1), (2, 2), (3, 3), (4, 7), (5, 8)]);
+    assert_eq!(indexer.sync().await.unwrap(), Some(3));
+    assert_eq!(indexer.tree().count(), 5);
+    assert_eq!(indexer.tree().leaf(3), Some([7; 32]));
+    assert_eq!(indexer.tree().leaf_index(&[4; 32]), None);
+    assert_eq!(indexer.tree().root(), root(&[1, 2, 3, 7, 8]));
+
+    // the last message leaves the chain without another block
+    hook.set(5, &[(1, 1), (2, 2), (3, 3), (4, 7)]);
+    assert_eq!(indexer.sync().await.unwrap(), Some(3));
+    assert_eq!(indexer.tree().root(), root(&[1, 2, 3, 7]));
+}
+
+#[tokio::test]
+async fn test_merkle_tree_indexer_indexes_again_after_a_deep_reorg() {
+    let hook = MockHook::default();
+    hook.set(4, &[(1, 1), (2, 2), (3, 3), (4, 4)]);
+    let mut indexer = MerkleTreeIndexer::new(hook.clone(), 0)
+        .with_max_block_range(2)
+        .with_reorg_window(1);
+    indexer.sync().await.unwrap();
+
+    // the reorg reaches before the single range remembered
+    hook.set(4, &[(1, 5), (2, 2), (4, 6)]);
+    assert_eq!(indexer.sync().await.unwrap(), Some(0));
+    assert_eq!(indexer.tree().count(), 3);
+    assert_eq!(indexer.tree().leaf(0), Some([5; 32]));
+    assert_eq!(indexer.tree().root(), root(&[5, 2, 6]));
+}
+
+#[tokio::test]
+async fn test_merkle_tree_indexer_refuses_a_start_block_after_the_deployment() {
+    let hook = MockHook::default();
+    hook.set(4, &[(1, 1), (3, 2)]);
+    let mut indexer = MerkleTreeIndexer::new(hook, 2);
+    let err = indexer.sync().await.unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("the start block must not be after"));
+    assert_eq!(indexer.tree().count(), 0);
+}
@@ -0,0 +1,23 @@
+use hyperlane_starknet::message::{HyperlaneMessage, HEADER_LENGTH, HYPERLANE_VERSION};
+
+#[test]
+fn test_message_bytes_roundtrip() {
+    let message = HyperlaneMessage {
+        version: HYPERLANE_VERSION,
+        nonce: 7,
+        origin: 1,
+        sender: [0xaa; 32],
+        destination: 2,
+        recipient: [0xbb; 32],
+        body: b"hello".to_vec(),
+    };
+
+    let bytes = message.to_bytes();
+    assert_eq!(bytes.len(), HEADER_LENGTH + 5);
+
+    let decoded = HyperlaneMessage::from_bytes(&bytes).unwrap();
+    assert_eq!(decoded.to_bytes(), bytes);
+    assert_eq!(decoded.id(), message.id());
+
+    assert!(HyperlaneMessage::from_bytes(&bytes[..HEADER_LENGTH - 1]).is_err());
+}
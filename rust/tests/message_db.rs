@@ -0,0 +1,319 @@
+#[allow(dead_code)]
+mod fixtures;
+
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use hyperlane_starknet::{
+    indexer::{
+        IndexedEvent, IndexedMessage, IndexerCursor, IndexerSettings, IndexerUpdate, MailboxEvent,
+        MailboxEventSource, MailboxIndexer, MessageDb, MessageQuery,
+    },
+    message::HyperlaneMessage,
+    utils::BoxFuture,
+};
+
+const ORIGIN: u32 = 1;
+const DESTINATION: u32 = 2;
+
+fn message(nonce: u32, sender: u8, recipient: u8) -> HyperlaneMessage {
+    HyperlaneMessage {
+        origin: ORIGIN,
+        sender: [sender; 32],
+        destination: DESTINATION,
+        recipient: [recipient; 32],
+        ..fixtures::message(nonce)
+    }
+}
+
+fn event(block_number: u64, event: MailboxEvent) -> IndexedEvent {
+    IndexedEvent {
+        block_number,
+        block_hash: [block_number as u8; 32],
+        block_timestamp: Some(1000 + block_number * 10),
+        transaction_hash: [0xaa; 32],
+        event,
+    }
+}
+
+/// Events of the dispatch of `message` in `block`.
+fn dispatch(block: u64, message: &HyperlaneMessage) -> Vec<IndexedEvent> {
+    vec![
+        event(block, MailboxEvent::Dispatch(message.clone())),
+        event(block, MailboxEvent::DispatchId(message.id())),
+    ]
+}
+
+/// Events of the delivery of `message` in `block`.
+fn process(block: u64, message: &HyperlaneMessage) -> Vec<IndexedEvent> {
+    vec![
+        event(
+            block,
+            MailboxEvent::Process {
+                origin: message.origin,
+                sender: message.sender,
+                recipient: message.recipient,
+            },
+        ),
+        event(block, MailboxEvent::ProcessId(message.id())),
+    ]
+}
+
+/// Chain of 5 blocks dispatching `message` in block 3, the timestamp of block `n` being
+/// `1000 + n * 10`.
+struct MockChain {
+    message: HyperlaneMessage,
+    /// Timestamp lookups, failing when unset.
+    lookups: Option<Arc<AtomicUsize>>,
+}
+
+impl MailboxEventSource for MockChain {
+    fn latest_block(&self) -> BoxFuture<'_, eyre::Result<u64>> {
+        Box::pin(async { Ok(4) })
+    }
+
+    fn block_hash(&self, block: u64) -> BoxFuture<'_, eyre::Result<Option<[u8; 32]>>> {
+        Box::pin(async move { Ok((block <= 4).then_some([block as u8; 32])) })
+    }
+
+    fn block_timestamp(&self, block_hash: [u8; 32]) -> BoxFuture<'_, eyre::Result<u64>> {
+        Box::pin(async move {
+            let Some(lookups) = &self.lookups else {
+                eyre::bail!("connection refused");
+            };
+            lookups.fetch_add(1, Ordering::SeqCst);
+            Ok(1000 + block_hash[0] as u64 * 10)
+        })
+    }
+
+    fn events(&self, from: u64, to: u64) -> BoxFuture<'_, eyre::Result<Vec<IndexedEvent>>> {
+        let mut events = if (from..=to).contains(&3) {
+            dispatch(3, &self.message)
+        } else {
+            vec![]
+        };
+        for indexed in &mut events {
+            indexed.block_timestamp = None;
+        }
+        Box::pin(async move { Ok(events) })
+    }
+}
+
+fn nonces(messages: Vec<IndexedMessage>) -> Vec<u32> {
+    messages
+        .into_iter()
+        .map(|indexed| indexed.message.nonce)
+        .collect()
+}
+
+fn indexed(from: u64, to: u64, events: Vec<IndexedEvent>) -> IndexerUpdate {
+    IndexerUpdate::Indexed { from, to, events }
+}
+
+#[test]
+fn test_message_db_joins_dispatches_with_deliveries() {
+    let db = MessageDb::in_memory().unwrap();
+    let messages = [message(0, 1, 7), message(1, 1, 8), message(2, 2, 7)];
+
+    let events = [
+        dispatch(10, &messages[0]),
+        dispatch(20, &messages[1]),
+        dispatch(30, &messages[2]),
+    ]
+    .concat();
+    db.record(
+        ORIGIN,
+        &indexed(0, 30, events),
+        &IndexerCursor::starting_at(31),
+    )
+    .unwrap();
+    db.record(
+        DESTINATION,
+        &indexed(0, 5, process(5, &messages[1])),
+        &IndexerCursor::starting_at(6),
+    )
+    .unwrap();
+
+    let first = db.message(&messages[0].id()).unwrap().unwrap();
+    assert_eq!(first.message, messages[0]);
+    assert_eq!((first.block_number, first.timestamp), (10, 1100));
+    assert_eq!(first.delivery, None);
+
+    let second = db.message_by_nonce(ORIGIN, 1).unwrap().unwrap();
+    assert_eq!(second.message, messages[1]);
+    let delivery = second.delivery.unwrap();
+    assert_eq!(
+        (delivery.destination, delivery.block_number),
+        (DESTINATION, 5)
+    );
+    assert_eq!(db.delivery(&messages[1].id()).unwrap(), Some(delivery));
+    assert_eq!(db.message_by_nonce(ORIGIN, 3).unwrap(), None);
+
+    assert_eq!(nonces(db.messages_by_sender(&[1; 32]).unwrap()), vec![0, 1]);
+    assert_eq!(
+        nonces(db.messages_by_recipient(&[7; 32]).unwrap()),
+        vec![0, 2]
+    );
+    assert_eq!(nonces(db.messages_between(1200, 1300).unwrap()), vec![1]);
+    assert_eq!(
+        nonces(
+            db.query(&MessageQuery {
+                recipient: Some([7; 32]),
+                delivered: Some(false),
+                limit: Some(1),
+                ..Default::default()
+            })
+            .unwrap()
+        ),
+        vec![0]
+    );
+
+    // dispatched at 1100 and 1300, the second message being delivered
+    let now = 1300 + 5 * 60;
+    let undelivered = db
+        .undelivered_older_than(now, Duration::from_secs(5 * 60))
+        .unwrap();
+    assert_eq!(nonces(undelivered), vec![0]);
+    let undelivered = db
+        .undelivered_older_than(now + 1, Duration::from_secs(5 * 60))
+        .unwrap();
+    assert_eq!(nonces(undelivered), vec![0, 2]);
+}
+
+#[test]
+fn test_message_db_removes_reorganized_blocks() {
+    let path = std::env::temp_dir().join(format!("message-db-{}.db", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let messages = [message(0, 1, 7), message(1, 1, 7)];
+
+    {
+        let db = MessageDb::open(&path).unwrap();
+        let events = [dispatch(10, &messages[0]), dispatch(20, &messages[1])].concat();
+        db.record(
+            ORIGIN,
+            &indexed(0, 20, events),
+            &IndexerCursor::starting_at(21),
+        )
+        .unwrap();
+        db.record(
+            DESTINATION,
+            &indexed(0, 15, process(15, &messages[0])),
+            &IndexerCursor::starting_at(16),
+        )
+        .unwrap();
+    }
+
+    let db = MessageDb::open(&path).unwrap();
+    assert_eq!(
+        db.cursor(ORIGIN).unwrap(),
+        Some(IndexerCursor::starting_at(21))
+    );
+    assert_eq!(db.cursor(3).unwrap(), None);
+
+    // the dispatch of block 20 and the delivery of block 15 are reorganized
+    db.record(
+        ORIGIN,
+        &IndexerUpdate::Reorg { from: 15 },
+        &IndexerCursor::starting_at(15),
+    )
+    .unwrap();
+    db.record(
+        DESTINATION,
+        &IndexerUpdate::Reorg { from: 12 },
+        &IndexerCursor::starting_at(12),
+    )
+    .unwrap();
+    assert_eq!(
+        db.message(&messages[0].id()).unwrap().unwrap().delivery,
+        None
+    );
+    assert_eq!(db.message(&messages[1].id()).unwrap(), None);
+    assert_eq!(
+        db.cursor(ORIGIN).unwrap(),
+        Some(IndexerCursor::starting_at(15))
+    );
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_message_db_refuses_orphan_deliveries() {
+    let db = MessageDb::in_memory().unwrap();
+    let message = message(0, 1, 7);
+
+    let events = vec![
+        event(3, MailboxEvent::Dispatch(message.clone())),
+        event(4, MailboxEvent::ProcessId(message.id())),
+    ];
+    let err = db
+        .record(
+            ORIGIN,
+            &indexed(0, 4, events),
+            &IndexerCursor::starting_at(5),
+        )
+        .unwrap_err();
+    assert!(err.to_string().contains("without its Process event"));
+
+    // nothing is recorded
+    assert_eq!(db.message(&message.id()).unwrap(), None);
+    assert_eq!(db.cursor(ORIGIN).unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_message_db_index_resolves_block_timestamps() {
+    let db = MessageDb::in_memory().unwrap();
+    let message = message(0, 1, 7);
+    let settings = IndexerSettings {
+        max_block_range: 5,
+        ..Default::default()
+    };
+
+    // the timestamp of a block whose lookup fails is not recorded, the range being indexed again
+    let mut indexer = MailboxIndexer::new(
+        MockChain {
+            message: message.clone(),
+            lookups: None,
+        },
+        IndexerCursor::starting_at(0),
+        settings.clone(),
+    );
+    let err = db.index(ORIGIN, &mut indexer).await.unwrap_err();
+    assert!(err.to_string().contains("connection refused"));
+    assert_eq!(indexer.cursor(), &IndexerCursor::starting_at(0));
+    assert_eq!(db.message(&message.id()).unwrap(), None);
+
+    let lookups = Arc::new(AtomicUsize::new(0));
+    let mut indexer = MailboxIndexer::new(
+        MockChain {
+            message: message.clone(),
+            lookups: Some(lookups.clone()),
+        },
+        IndexerCursor::starting_at(0),
+        settings,
+    );
+    db.index(ORIGIN, &mut indexer).await.unwrap();
+    let stored = db.message(&message.id()).unwrap().unwrap();
+    assert_eq!((stored.block_number, stored.timestamp), (3, 1030));
+    // once for the block of both events
+    assert_eq!(lookups.load(Ordering::SeqCst), 1);
+    assert_eq!(db.cursor(ORIGIN).unwrap().unwrap().next_block, 5);
+
+    // events recorded directly must come with their timestamp
+    let mut unresolved = dispatch(6, &message);
+    unresolved[0].block_timestamp = None;
+    let err = db
+        .record(
+            ORIGIN,
+            &indexed(6, 6, unresolved),
+            &IndexerCursor::starting_at(7),
+        )
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("timestamp of block 6 not resolved"));
+}
@@ -0,0 +1,254 @@
+#[allow(dead_code)]
+mod fixtures;
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use ethers::{
+    signers::{LocalWallet, Signer},
+    types::{Address, H256},
+};
+use hyperlane_starknet::{
+    checkpoint::{branch_root, Checkpoint, CheckpointWithMessageId, SignedCheckpoint, TREE_DEPTH},
+    ism::{simulate_verify, IsmKind, IsmNode, IsmRoute, ModuleType},
+    message::HyperlaneMessage,
+    relayer::{CheckpointSource, MerkleTreeSource, MetadataBuilder, MetadataBuilders},
+    utils::BoxFuture,
+};
+use starknet::{core::types::FieldElement, macros::felt};
+
+use fixtures::{message, proof, wallets};
+
+const ORIGIN: u32 = 1;
+const MERKLE_TREE_HOOK: [u8; 32] = [0x11; 32];
+
+/// Origin whose messages are all inserted in the merkle tree hook, and whose validators sign
+/// every checkpoint up to `signed[validator]`.
+struct MockOrigin {
+    leaves: Vec<[u8; 32]>,
+    wallets: Vec<LocalWallet>,
+    signed: BTreeMap<Address, u32>,
+}
+
+impl MockOrigin {
+    fn new(messages: &[HyperlaneMessage], wallets: Vec<LocalWallet>, signed: &[u32]) -> Self {
+        Self {
+            leaves: messages.iter().map(|m| m.id()).collect(),
+            signed: wallets
+                .iter()
+                .zip(signed)
+                .map(|(w, index)| (w.address(), *index))
+                .collect(),
+            wallets,
+        }
+    }
+
+    fn checkpoint(&self, index: u32) -> CheckpointWithMessageId {
+        let leaves = &self.leaves[..=index as usize];
+        CheckpointWithMessageId {
+            checkpoint: Checkpoint {
+                merkle_tree_hook: MERKLE_TREE_HOOK,
+                origin: ORIGIN,
+                root: branch_root(
+                    &leaves[index as usize],
+                    &proof(leaves, index as usize),
+                    index,
+                ),
+                index,
+            },
+            message_id: leaves[index as usize],
+        }
+    }
+}
+
+impl CheckpointSource for MockOrigin {
+    fn latest_index(
+        &self,
+        _origin: u32,
+        validator: Address,
+    ) -> BoxFuture<'_, eyre::Result<Option<u32>>> {
+        let index = self.signed.get(&validator).copied();
+        Box::pin(async move { Ok(index) })
+    }
+
+    fn fetch_checkpoint(
+        &self,
+        _origin: u32,
+        validator: Address,
+        index: u32,
+    ) -> BoxFuture<'_, eyre::Result<Option<SignedCheckpoint>>> {
+        Box::pin(async move {
+            match self.signed.get(&validator) {
+                Some(latest) if index <= *latest => {}
+                _ => return Ok(None),
+            }
+
+            let wallet = self.wallets.iter().find(|w| w.address() == validator);
+            let value = self.checkpoint(index);
+            let signature = wallet.unwrap().sign_hash(H256(value.digest()))?;
+            Ok(Some(SignedCheckpoint {
+                value,
+                signature: signature.to_vec(),
+            }))
+        })
+    }
+}
+
+impl MerkleTreeSource for MockOrigin {
+    fn leaf_index(&self, _origin: u32, id: [u8; 32]) -> BoxFuture<'_, eyre::Result<Option<u32>>> {
+        let index = self.leaves.iter().position(|leaf| *leaf == id);
+        Box::pin(async move { Ok(index.map(|index| index as u32)) })
+    }
+
+    fn proof(
+        &self,
+        _origin: u32,
+        leaf_index: u32,
+        root_index: u32,
+    ) -> BoxFuture<'_, eyre::Result<[[u8; 32]; TREE_DEPTH]>> {
+        let proof = proof(&self.leaves[..=root_index as usize], leaf_index as usize);
+        Box::pin(async move { Ok(proof) })
+    }
+}
+
+fn multisig(address: FieldElement, module_type: ModuleType, wallets: &[LocalWallet]) -> IsmNode {
+    IsmNode {
+        address,
+        module_type,
+        kind: IsmKind::Multisig {
+            threshold: 2,
+            validators: wallets.iter().map(|w| w.address()).collect(),
+        },
+    }
+}
+
+fn builders(origin: MockOrigin) -> MetadataBuilders {
+    let origin = Arc::new(origin);
+    MetadataBuilders::new().with_multisig(origin.clone(), origin)
+}
+
+#[tokio::test]
+async fn test_multisig_metadata_verifies() {
+    let messages: Vec<_> = (0..4).map(message).collect();
+    let wallets = wallets(3);
+    let message_id = multisig(felt!("0x1"), ModuleType::MessageIdMultisig, &wallets);
+    let merkle_root = multisig(felt!("0x2"), ModuleType::MerkleRootMultisig, &wallets);
+
+    // the second validator lags behind, the first and third ones signed up to the last message
+    let builders = builders(MockOrigin::new(&messages, wallets, &[3, 0, 3]));
+
+    for ism in [&message_id, &merkle_root] {
+        let metadata = builders.build(ism, &messages[1]).await.unwrap().unwrap();
+        assert_eq!(
+            simulate_verify(ism, &metadata, &messages[1], felt!("0x0")),
+            Ok(())
+        );
+    }
+
+    // the merkle root multisig metadata proves the message against the latest quorum checkpoint
+    let metadata = builders
+        .build(&merkle_root, &messages[1])
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(&metadata[1092..1096], &3u32.to_be_bytes());
+}
+
+#[tokio::test]
+async fn test_multisig_metadata_waits_for_quorum() {
+    let messages: Vec<_> = (0..4).map(message).collect();
+    let wallets = wallets(3);
+    let ism = multisig(felt!("0x1"), ModuleType::MessageIdMultisig, &wallets);
+
+    let builders = builders(MockOrigin::new(&messages, wallets, &[3, 0, 1]));
+    assert_eq!(builders.build(&ism, &messages[2]).await.unwrap(), None);
+    assert!(builders.build(&ism, &messages[1]).await.unwrap().is_some());
+
+    // not inserted in the merkle tree hook yet
+    assert_eq!(builders.build(&ism, &message(9)).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_routing_and_aggregation_metadata_verifies() {
+    let messages: Vec<_> = (0..2).map(message).collect();
+    let wallets = wallets(3);
+    let ism = IsmNode {
+        address: felt!("0x1"),
+        module_type: ModuleType::Routing,
+        kind: IsmKind::Routing {
+            routes: vec![IsmRoute {
+                origin: ORIGIN,
+                ism: IsmNode {
+                    address: felt!("0x2"),
+                    module_type: ModuleType::Aggregation,
+                    kind: IsmKind::Aggregation {
+                        threshold: 2,
+                        modules: vec![
+                            IsmNode {
+                                address: felt!("0x3"),
+                                module_type: ModuleType::Null,
+                                kind: IsmKind::Pausable { paused: false },
+                            },
+                            multisig(felt!("0x4"), ModuleType::MessageIdMultisig, &wallets),
+                            multisig(felt!("0x5"), ModuleType::MerkleRootMultisig, &wallets),
+                        ],
+                    },
+                },
+            }],
+            fallback: None,
+        },
+    };
+
+    let builders = builders(MockOrigin::new(&messages, wallets, &[1, 1, 1]));
+    let metadata = builders.build(&ism, &messages[0]).await.unwrap().unwrap();
+    assert_eq!(
+        simulate_verify(&ism, &metadata, &messages[0], felt!("0x0")),
+        Ok(())
+    );
+    // only the first modules reaching the threshold get metadata
+    assert_eq!(&metadata[16..24], &[0u8; 8]);
+
+    let other_origin = HyperlaneMessage {
+        origin: 5,
+        ..messages[0].clone()
+    };
+    assert!(builders.build(&ism, &other_origin).await.is_err());
+}
+
+struct FixedMetadata(Vec<u8>);
+
+impl MetadataBuilder for FixedMetadata {
+    fn build<'a>(
+        &'a self,
+        _builders: &'a MetadataBuilders,
+        _ism: &'a IsmNode,
+        _message: &'a HyperlaneMessage,
+    ) -> BoxFuture<'a, eyre::Result<Option<Vec<u8>>>> {
+        Box::pin(async { Ok(Some(self.0.clone())) })
+    }
+}
+
+#[tokio::test]
+async fn test_custom_builders() {
+    let message = message(0);
+    let custom = IsmNode {
+        address: felt!("0x7"),
+        module_type: ModuleType::CcipRead,
+        kind: IsmKind::Unknown,
+    };
+
+    let mut builders = MetadataBuilders::new();
+    assert!(builders.build(&custom, &message).await.is_err());
+
+    builders.register(ModuleType::CcipRead, Arc::new(FixedMetadata(vec![1])));
+    assert_eq!(
+        builders.build(&custom, &message).await.unwrap(),
+        Some(vec![1])
+    );
+
+    // builders registered for an ISM take precedence over the ones of its module type
+    builders.register_ism(felt!("0x7"), Arc::new(FixedMetadata(vec![2])));
+    assert_eq!(
+        builders.build(&custom, &message).await.unwrap(),
+        Some(vec![2])
+    );
+}
@@ -0,0 +1,152 @@
+use hyperlane_starknet::process::{bisect_batch, parse_fee_multiplier, FeeSettings, ProcessError};
+use serde_json::json;
+
+#[test]
+fn test_fee_multiplier_and_cap() {
+    let fee = FeeSettings::default();
+    assert_eq!(fee.max_fee(1000).unwrap(), 1500);
+
+    let fee = FeeSettings {
+        multiplier: 2.0,
+        max_fee: Some(1500),
+    };
+    assert_eq!(fee.max_fee(500).unwrap(), 1000);
+    // the multiplier does not push the max fee above the cap
+    assert_eq!(fee.max_fee(1000).unwrap(), 1500);
+    assert!(matches!(
+        fee.max_fee(2000),
+        Err(ProcessError::FeeAboveCap {
+            estimated: 2000,
+            cap: 1500,
+        })
+    ));
+}
+
+#[test]
+fn test_fee_multiplier_is_positive() {
+    let fee: FeeSettings = serde_json::from_value(json!({})).unwrap();
+    assert_eq!(fee, FeeSettings::default());
+    let fee: FeeSettings = serde_json::from_value(json!({ "multiplier": 1.2 })).unwrap();
+    assert_eq!(fee.multiplier, 1.2);
+
+    for multiplier in [json!(0.0), json!(-1.5)] {
+        let err =
+            serde_json::from_value::<FeeSettings>(json!({ "multiplier": multiplier })).unwrap_err();
+        assert!(
+            err.to_string().contains("must be a positive number"),
+            "{err}"
+        );
+    }
+
+    assert_eq!(parse_fee_multiplier("2").unwrap(), 2.0);
+    for value in ["0", "-1", "NaN", "inf", "twice"] {
+        assert!(parse_fee_multiplier(value).is_err(), "{value}");
+    }
+}
+
+#[test]
+fn test_revert_classification() {
+    let error = ProcessError::from_revert("Execution failed: 'Mailbox: already delivered'");
+    assert!(matches!(error, ProcessError::AlreadyDelivered));
+    assert!(error.is_permanent());
+
+    // short strings reported hex encoded
+    let reason = format!(
+        "Failure reason: 0x{}",
+        hex::encode("No match for given signature")
+    );
+    let error = ProcessError::from_revert(&reason);
+    assert!(matches!(error, ProcessError::InvalidMetadata(_)));
+    assert!(error.is_permanent());
+
+    let error = ProcessError::from_revert("Unexpected destination");
+    assert!(matches!(error, ProcessError::InvalidMessage(_)));
+
+    let error = ProcessError::from_revert("Failure reason: 'ERC20: insufficient balance'");
+    assert!(matches!(error, ProcessError::RecipientReverted(_)));
+    assert!(error.is_permanent());
+
+    assert!(!ProcessError::FeeTooLow("insufficient max fee".into()).is_permanent());
+    assert!(!ProcessError::InvalidNonce("invalid nonce".into()).is_permanent());
+}
+
+#[tokio::test]
+async fn test_bisect_batch_isolates_failing_items() {
+    // simulations revert whenever the batch holds a multiple of 5
+    let mut simulations = 0;
+    let plan = bisect_batch((1..=12).collect(), |batch: Vec<u32>| {
+        simulations += 1;
+        async move {
+            match batch.iter().find(|item| *item % 5 == 0) {
+                Some(item) => Err(ProcessError::from_revert(&format!(
+                    "Unexpected item {item}"
+                ))),
+                None => Ok(batch.len()),
+            }
+        }
+    })
+    .await;
+
+    let batches: Vec<_> = plan
+        .batches
+        .iter()
+        .map(|(batch, _)| batch.clone())
+        .collect();
+    assert_eq!(
+        batches,
+        vec![vec![1, 2, 3], vec![4], vec![6], vec![7, 8, 9], vec![11, 12]]
+    );
+    assert!(plan.batches.iter().all(|(batch, len)| batch.len() == *len));
+
+    let failed: Vec<_> = plan.failed.iter().map(|(item, _)| *item).collect();
+    assert_eq!(failed, vec![5, 10]);
+    assert!(matches!(
+        plan.failed[0].1,
+        ProcessError::RecipientReverted(_)
+    ));
+    assert!(simulations < 2 * 12);
+
+    // a batch simulating successfully is kept whole
+    let plan = bisect_batch(vec![1, 2, 3], |batch: Vec<u32>| async move { Ok(batch) }).await;
+    assert_eq!(plan.batches.len(), 1);
+    assert!(plan.failed.is_empty());
+}
+
+#[tokio::test]
+async fn test_bisect_batch_stops_on_failures_worth_retrying() {
+    // an outage fails every item of the batch at once
+    let mut simulations = 0;
+    let plan = bisect_batch((1..=8).collect(), |_: Vec<u32>| {
+        simulations += 1;
+        async { Err::<(), _>(eyre::eyre!("connection refused").into()) }
+    })
+    .await;
+    assert_eq!(simulations, 1);
+    assert!(plan.batches.is_empty());
+    let failed: Vec<_> = plan.failed.iter().map(|(item, _)| *item).collect();
+    assert_eq!(failed, (1..=8).collect::<Vec<_>>());
+    assert!(plan.failed.iter().all(|(_, e)| !e.is_permanent()
+        && e.to_string() == "batch of 8 items failed: connection refused"));
+
+    // the items left once the outage starts are failed along with the batch simulated
+    let plan = bisect_batch((1..=8).collect(), |batch: Vec<u32>| async move {
+        if batch[0] == 7 {
+            Err(ProcessError::FeeTooLow("max fee exceeded".into()))
+        } else if batch.contains(&5) {
+            Err(ProcessError::from_revert("Unexpected item 5"))
+        } else {
+            Ok(())
+        }
+    })
+    .await;
+    let batches: Vec<_> = plan
+        .batches
+        .iter()
+        .map(|(batch, _)| batch.clone())
+        .collect();
+    assert_eq!(batches, vec![vec![1, 2, 3, 4], vec![6]]);
+    let failed: Vec<_> = plan.failed.iter().map(|(item, _)| *item).collect();
+    assert_eq!(failed, vec![5, 7, 8]);
+    assert!(plan.failed[0].1.is_permanent());
+    assert!(!plan.failed[1].1.is_permanent());
+}
@@ -0,0 +1,98 @@
+#[allow(dead_code)]
+mod fixtures;
+
+use ethers::types::Address;
+use hyperlane_starknet::{
+    message::{HyperlaneAddress, HyperlaneMessage},
+    relayer::{PolicyDecision, RelayPolicy},
+};
+use starknet::macros::felt;
+
+fn message(origin: u32, sender: [u8; 32], body: &[u8]) -> HyperlaneMessage {
+    HyperlaneMessage {
+        origin,
+        sender,
+        recipient: HyperlaneAddress::from(felt!("0x1234")).0,
+        body: body.to_vec(),
+        ..fixtures::message(0)
+    }
+}
+
+#[test]
+fn test_hyperlane_address_forms() {
+    let evm: Address = "0x00000000000000000000000000000000000000ff"
+        .parse()
+        .unwrap();
+    let address: HyperlaneAddress = "0xff".parse().unwrap();
+    assert_eq!(address, HyperlaneAddress::from(evm));
+    assert_eq!(address, HyperlaneAddress::from(felt!("0xff")));
+    assert_eq!(address.to_string(), format!("0x{}ff", "0".repeat(62)));
+    assert_eq!(
+        address.to_string().parse::<HyperlaneAddress>().unwrap(),
+        address
+    );
+
+    assert!(format!("0x{}", "11".repeat(33))
+        .parse::<HyperlaneAddress>()
+        .is_err());
+}
+
+#[test]
+fn test_policy_decisions() {
+    let policy: RelayPolicy = serde_json::from_str(
+        r#"{
+            "whitelist": [
+                { "recipients": ["0x1234"] },
+                { "origin_domains": [5] }
+            ],
+            "blacklist": [{ "origin_domains": [1], "senders": ["0xdead"] }],
+            "max_body_size": 4
+        }"#,
+    )
+    .unwrap();
+
+    let allowed = HyperlaneAddress::from(felt!("0xbeef")).0;
+    let denied = HyperlaneAddress::from(felt!("0xdead")).0;
+
+    assert_eq!(
+        policy.decide(&message(1, allowed, b"body")),
+        PolicyDecision::Relay { rule: Some(0) }
+    );
+    assert_eq!(
+        policy.decide(&message(1, denied, b"body")),
+        PolicyDecision::Blacklisted { rule: 0 }
+    );
+    // the blacklist rule only matches the messages from its origin
+    assert!(policy.decide(&message(3, denied, b"body")).is_relayed());
+    assert_eq!(
+        policy.decide(&message(1, allowed, b"hello")),
+        PolicyDecision::BodyTooLarge { size: 5, max: 4 }
+    );
+
+    let mut other_recipient = message(3, allowed, b"");
+    other_recipient.recipient = [0x11; 32];
+    assert_eq!(
+        policy.decide(&other_recipient),
+        PolicyDecision::NotWhitelisted
+    );
+    other_recipient.origin = 5;
+    assert_eq!(
+        policy.decide(&other_recipient),
+        PolicyDecision::Relay { rule: Some(1) }
+    );
+    assert_eq!(
+        PolicyDecision::NotWhitelisted.to_string(),
+        "matches no whitelist rule"
+    );
+}
+
+#[test]
+fn test_default_policy_relays_everything() {
+    let policy = RelayPolicy::default();
+    assert_eq!(
+        policy.decide(&message(1, [0; 32], &[0; 1024])),
+        PolicyDecision::Relay { rule: None }
+    );
+
+    assert!(serde_json::from_str::<RelayPolicy>(r#"{ "whitelists": [] }"#).is_err());
+}
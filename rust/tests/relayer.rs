@@ -0,0 +1,200 @@
+#[allow(dead_code)]
+mod constants;
+mod contracts;
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use ethers::{
+    middleware::SignerMiddleware,
+    prelude::parse_log,
+    providers::{Http, Middleware, Provider},
+    signers::{LocalWallet, Signer},
+    types::{Address, H256},
+};
+use hyperlane_starknet::{
+    contracts::eth::mailbox::Mailbox,
+    ism::{IsmNode, ModuleType},
+    message::HyperlaneMessage,
+    process::ProcessError,
+    relayer::{
+        batch_deliveries, Chain, Delivery, EvmChain, MessageStatus, MetadataBuilder,
+        MetadataBuilders, Relayer, RelayerSettings, RelayerStore,
+    },
+    signer::{EvmSigner, LocalSigner},
+    utils::BoxFuture,
+};
+use starknet::core::types::FieldElement;
+
+use crate::{
+    constants::DOMAIN_EVM,
+    contracts::eth::{self, mailbox::DispatchIdFilter},
+};
+
+#[tokio::test]
+async fn test_batch_deliveries_settle_already_delivered_items() {
+    // items 2 and 4 were delivered by another relayer before the batch was executed
+    let delivered = |item: &u32| {
+        let item = *item;
+        async move { Ok([2, 4].contains(&item)) }
+    };
+
+    let results = batch_deliveries(
+        &[1, 2, 3, 4],
+        Err(ProcessError::AlreadyDelivered),
+        delivered,
+    )
+    .await;
+    assert_eq!(results.len(), 4);
+    assert!(matches!(results[0], Err(ProcessError::Other(_))));
+    assert!(!results[0].as_ref().unwrap_err().is_permanent());
+    assert_eq!(results[1].as_ref().unwrap(), &Delivery::AlreadyDelivered);
+    assert!(matches!(results[2], Err(ProcessError::Other(_))));
+    assert_eq!(results[3].as_ref().unwrap(), &Delivery::AlreadyDelivered);
+
+    // a single delivery reverting as already delivered is settled without checking it
+    let results = batch_deliveries(&[1], Err(ProcessError::AlreadyDelivered), |_: &u32| async {
+        Err(eyre::eyre!("unreachable"))
+    })
+    .await;
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].as_ref().unwrap(), &Delivery::AlreadyDelivered);
+
+    // a failing delivery check retries the item
+    let results = batch_deliveries(
+        &[1, 2],
+        Err(ProcessError::AlreadyDelivered),
+        |_: &u32| async { Err(eyre::eyre!("connection refused")) },
+    )
+    .await;
+    assert!(results
+        .iter()
+        .all(|result| matches!(result, Err(ProcessError::Other(_)))));
+}
+
+#[tokio::test]
+async fn test_batch_deliveries_retry_failed_batches() {
+    let unchecked = |_: &u32| async { Ok(true) };
+
+    let results =
+        batch_deliveries(&[1, 2, 3], Ok(Delivery::Processed([0x11; 32])), unchecked).await;
+    assert!(results
+        .iter()
+        .all(|result| result.as_ref().unwrap() == &Delivery::Processed([0x11; 32])));
+
+    // the revert of a batch cannot be attributed to one of its items, which are retried
+    let reverted = || ProcessError::from_revert("Mailbox: ISM verification failed");
+    let results = batch_deliveries(&[1, 2], Err(reverted()), unchecked).await;
+    assert_eq!(results.len(), 2);
+    for result in &results {
+        let error = result.as_ref().unwrap_err();
+        assert!(!error.is_permanent());
+        assert!(error.to_string().contains("batch of 2 messages failed"));
+    }
+
+    // while the revert of a single delivery is its own
+    let results = batch_deliveries(&[1], Err(reverted()), unchecked).await;
+    assert!(matches!(results[0], Err(ProcessError::InvalidMetadata(_))));
+}
+
+/// Builder recording the ISMs it was asked the metadata of.
+#[derive(Default)]
+struct RecordingBuilder {
+    isms: Mutex<Vec<(FieldElement, ModuleType)>>,
+}
+
+impl MetadataBuilder for RecordingBuilder {
+    fn build<'a>(
+        &'a self,
+        _builders: &'a MetadataBuilders,
+        ism: &'a IsmNode,
+        _message: &'a HyperlaneMessage,
+    ) -> BoxFuture<'a, eyre::Result<Option<Vec<u8>>>> {
+        self.isms
+            .lock()
+            .unwrap()
+            .push((ism.address, ism.module_type));
+        Box::pin(async { Ok(Some(vec![0x01])) })
+    }
+}
+
+async fn evm_chain(
+    domain: u32,
+    endpoint: &str,
+    wallet: LocalWallet,
+    mailbox: Address,
+) -> eyre::Result<Chain> {
+    let signer = EvmSigner::new(Arc::new(LocalSigner::Secp256k1(wallet.clone())))
+        .await?
+        .with_chain_id(wallet.chain_id());
+    let provider = Provider::<Http>::try_from(endpoint)?.interval(Duration::from_millis(10));
+    let client = Arc::new(SignerMiddleware::new(provider, signer));
+
+    Ok(Chain::Ethereum(EvmChain {
+        domain,
+        mailbox: Mailbox::new(mailbox, client.clone()),
+        client,
+        start_block: Some(0),
+    }))
+}
+
+#[tokio::test]
+async fn test_relayer_delivers_evm_messages_past_failing_chains() -> eyre::Result<()> {
+    let anvil = eth::setup_env(DOMAIN_EVM).await?;
+    let mailbox = anvil.core.mailbox.address();
+
+    // the chain of domain 0 is indexed first and never answers
+    let chains = vec![
+        evm_chain(0, "http://127.0.0.1:1", anvil.acc_owner.clone(), mailbox).await?,
+        evm_chain(
+            DOMAIN_EVM,
+            &anvil.app.endpoint(),
+            anvil.acc_owner.clone(),
+            mailbox,
+        )
+        .await?,
+    ];
+
+    let builder = Arc::new(RecordingBuilder::default());
+    let ism = FieldElement::from_byte_slice_be(anvil.core.ism.address().as_bytes())?;
+    let mut builders = MetadataBuilders::new();
+    builders.register_ism(ism, builder.clone());
+
+    let mut relayer = Relayer::new(
+        chains,
+        RelayerStore::in_memory()?,
+        RelayerSettings::default(),
+    )
+    .with_metadata_builders(builders);
+
+    let recipient = H256::from(anvil.core.msg_receiver.address());
+    let receipt = anvil
+        .core
+        .mailbox
+        .dispatch_0(DOMAIN_EVM, recipient.0, b"hello".to_vec().into())
+        .send()
+        .await?
+        .await?
+        .unwrap();
+    let dispatch_id: DispatchIdFilter = parse_log(receipt.logs[1].clone())?;
+
+    relayer.tick().await?;
+
+    let store = relayer.store();
+    assert_eq!(store.cursor(0)?, None);
+    assert!(store.cursor(DOMAIN_EVM)?.is_some());
+
+    let stored = store.message(&dispatch_id.message_id)?.unwrap();
+    assert_eq!(stored.status, MessageStatus::Delivered, "{stored:?}");
+    assert_eq!(stored.metadata, Some(vec![0x01]));
+
+    // the metadata was built for the recipient ISM, a merkle root multisig ISM
+    assert_eq!(
+        *builder.isms.lock().unwrap(),
+        [(ism, ModuleType::MerkleRootMultisig)]
+    );
+
+    Ok(())
+}
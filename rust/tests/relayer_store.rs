@@ -0,0 +1,97 @@
+#[allow(dead_code)]
+mod fixtures;
+
+use std::time::Duration;
+
+use hyperlane_starknet::relayer::{MessageStatus, RelayerSettings, RelayerStore};
+
+use fixtures::message;
+
+#[test]
+fn test_message_lifecycle() {
+    let store = RelayerStore::in_memory().unwrap();
+    let messages = [message(0), message(1)];
+    let id = messages[0].id();
+
+    assert_eq!(store.cursor(1).unwrap(), None);
+    assert_eq!(store.record_indexed(1, &messages, 11).unwrap(), 2);
+    // indexing the same messages again does not reset their state
+    store.set_metadata(&id, &[1, 2, 3]).unwrap();
+    assert_eq!(store.record_indexed(1, &messages[..1], 12).unwrap(), 0);
+    assert_eq!(store.cursor(1).unwrap(), Some(12));
+
+    let stored = store.message(&id).unwrap().unwrap();
+    assert_eq!(stored.message, messages[0]);
+    assert_eq!(stored.status, MessageStatus::MetadataReady);
+    assert_eq!(stored.metadata, Some(vec![1, 2, 3]));
+
+    store.set_submitted(&id, &[0x11; 32]).unwrap();
+    store.schedule_retry(&id, "fee too low", 100).unwrap();
+    let stored = store.message(&id).unwrap().unwrap();
+    assert_eq!(stored.status, MessageStatus::MetadataReady);
+    assert_eq!(stored.attempts, 1);
+    assert_eq!(stored.next_attempt, 100);
+    assert_eq!(stored.tx_hash, Some([0x11; 32]));
+    assert_eq!(stored.last_error.as_deref(), Some("fee too low"));
+
+    // the retried message is not due before its next attempt
+    let due: Vec<_> = store.due_messages(50).unwrap();
+    assert_eq!(due.len(), 1);
+    assert_eq!(due[0].message, messages[1]);
+    assert_eq!(store.due_messages(100).unwrap().len(), 2);
+
+    store.set_delivered(&id, None).unwrap();
+    store
+        .set_failed(&messages[1].id(), "recipient reverted")
+        .unwrap();
+    assert!(store.due_messages(u64::MAX >> 1).unwrap().is_empty());
+    assert_eq!(
+        store.message(&id).unwrap().unwrap().tx_hash,
+        Some([0x11; 32])
+    );
+
+    let counts = store.status_counts().unwrap();
+    assert_eq!(counts.get(&MessageStatus::Delivered), Some(&1));
+    assert_eq!(counts.get(&MessageStatus::Failed), Some(&1));
+
+    assert!(store.set_delivered(&message(5).id(), None).is_err());
+}
+
+#[test]
+fn test_store_survives_restart() {
+    let path = std::env::temp_dir().join(format!("relayer-store-{}.db", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    {
+        let store = RelayerStore::open(&path).unwrap();
+        store.record_indexed(1, &[message(0)], 42).unwrap();
+        store
+            .schedule_retry(&message(0).id(), "timeout", 7)
+            .unwrap();
+    }
+
+    let store = RelayerStore::open(&path).unwrap();
+    assert_eq!(store.cursor(1).unwrap(), Some(42));
+    let due = store.due_messages(7).unwrap();
+    assert_eq!(due.len(), 1);
+    assert_eq!(due[0].status, MessageStatus::Pending);
+    assert_eq!(due[0].attempts, 1);
+
+    drop(store);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_retry_backoff() {
+    let settings = RelayerSettings {
+        retry_initial_delay: Duration::from_secs(10),
+        retry_max_delay: Duration::from_secs(60),
+        ..Default::default()
+    };
+
+    assert_eq!(settings.retry_delay(1), Duration::from_secs(10));
+    assert_eq!(settings.retry_delay(2), Duration::from_secs(20));
+    assert_eq!(settings.retry_delay(3), Duration::from_secs(40));
+    assert_eq!(settings.retry_delay(4), Duration::from_secs(60));
+    assert_eq!(settings.retry_delay(100), Duration::from_secs(60));
+}
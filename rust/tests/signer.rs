@@ -0,0 +1,264 @@
+#[allow(dead_code)]
+mod http;
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use ethers::{
+    signers::{LocalWallet, Signer as _},
+    types::{transaction::eip2718::TypedTransaction, TransactionRequest, H256},
+    utils::hash_message,
+};
+use hyperlane_starknet::{
+    config::SignerConfig,
+    signer::{
+        Curve, EvmSigner, LocalSigner, PublicKey, RemoteSigner, Signature, Signer, StarknetSigner,
+    },
+};
+use k256::elliptic_curve::rand_core::OsRng;
+use starknet::{
+    core::{crypto::ecdsa_verify, types::FieldElement},
+    signers::{Signer as _, SigningKey},
+};
+
+use http::{mock_server, Request};
+
+const KEY: &str = "0x0000000000000000000000000000000000000000000000000000000000001234";
+const TOKEN: &str = "signer-token";
+const HASH: [u8; 32] = [0x05; 32];
+
+type Keys = Arc<BTreeMap<String, LocalSigner>>;
+
+/// Remote signing service holding `stark` and `secp256k1` keys in memory, plus a `dishonest`
+/// secp256k1 key answering with the signatures of another key.
+async fn mock_signer() -> String {
+    let keys: Keys = Arc::new(BTreeMap::from([
+        (
+            "stark".to_string(),
+            LocalSigner::from_hex(Curve::Stark, KEY).unwrap(),
+        ),
+        (
+            "secp256k1".to_string(),
+            LocalSigner::from_hex(Curve::Secp256k1, KEY).unwrap(),
+        ),
+        (
+            "dishonest".to_string(),
+            LocalSigner::from_hex(Curve::Secp256k1, KEY).unwrap(),
+        ),
+    ]));
+
+    mock_server(move |request: Request| {
+        let keys = keys.clone();
+        async move {
+            let (status, body) = match request.header("authorization") {
+                Some(token) if token == format!("Bearer {TOKEN}") => respond(&keys, &request).await,
+                _ => ("401 Unauthorized", String::new()),
+            };
+            (status, body.into_bytes())
+        }
+    })
+    .await
+}
+
+async fn respond(keys: &Keys, request: &Request) -> (&'static str, String) {
+    let segments: Vec<&str> = request.path().split('/').collect();
+    let (id, sign) = match segments.as_slice() {
+        ["keys", id] => (*id, false),
+        ["keys", id, "sign"] => (*id, true),
+        _ => return ("404 Not Found", String::new()),
+    };
+    let Some(key) = keys.get(id) else {
+        return ("404 Not Found", String::new());
+    };
+
+    match (request.method.as_str(), sign) {
+        ("GET", false) => {
+            let public_key = match key.public_key().await.unwrap() {
+                PublicKey::Stark(key) => format!("{key:#064x}"),
+                PublicKey::Secp256k1(address) => format!("{address:?}"),
+            };
+            let curve = key.curve().to_string();
+            let body = serde_json::json!({ "curve": curve, "public_key": public_key });
+            ("200 OK", body.to_string())
+        }
+        ("POST", true) => {
+            let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+            let mut hash: [u8; 32] =
+                hex::decode(body["hash"].as_str().unwrap().trim_start_matches("0x"))
+                    .unwrap()
+                    .try_into()
+                    .unwrap();
+            if id == "dishonest" {
+                hash[0] ^= 1;
+            }
+            let signature = match key.sign_hash(hash).await.unwrap() {
+                Signature::Stark { r, s } => [r.to_bytes_be(), s.to_bytes_be()].concat(),
+                Signature::Secp256k1(signature) => signature.to_vec(),
+            };
+            let body = serde_json::json!({ "signature": format!("0x{}", hex::encode(signature)) });
+            ("200 OK", body.to_string())
+        }
+        _ => ("405 Method Not Allowed", String::new()),
+    }
+}
+
+fn stark_key() -> SigningKey {
+    SigningKey::from_secret_scalar(FieldElement::from_hex_be(KEY).unwrap())
+}
+
+fn evm_wallet() -> LocalWallet {
+    KEY.trim_start_matches("0x").parse().unwrap()
+}
+
+#[tokio::test]
+async fn test_local_signers() {
+    let stark = LocalSigner::from_hex(Curve::Stark, KEY).unwrap();
+    let public_key = stark_key().verifying_key().scalar();
+    assert_eq!(stark.curve(), Curve::Stark);
+    assert_eq!(
+        stark.public_key().await.unwrap(),
+        PublicKey::Stark(public_key)
+    );
+    let signature = stark.sign_hash(HASH).await.unwrap().into_stark().unwrap();
+    let hash = FieldElement::from_bytes_be(&HASH).unwrap();
+    assert!(ecdsa_verify(&public_key, &hash, &signature).unwrap());
+
+    let secp256k1 = LocalSigner::from_hex(Curve::Secp256k1, KEY).unwrap();
+    let address = evm_wallet().address();
+    assert_eq!(
+        secp256k1.public_key().await.unwrap(),
+        PublicKey::Secp256k1(address)
+    );
+    let signature = secp256k1
+        .sign_hash(HASH)
+        .await
+        .unwrap()
+        .into_secp256k1()
+        .unwrap();
+    assert_eq!(signature.recover(H256(HASH)).unwrap(), address);
+    assert!(signature.v == 27 || signature.v == 28);
+}
+
+#[tokio::test]
+async fn test_keystore_signer() {
+    let dir = std::env::temp_dir().join(format!("signer-keystore-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let key = hex::decode(KEY.trim_start_matches("0x")).unwrap();
+    LocalWallet::encrypt_keystore(&dir, &mut OsRng, &key, "password", Some("key.json")).unwrap();
+    let path = dir.join("key.json");
+
+    // the same keystore format holds the keys of both curves
+    let stark = LocalSigner::from_keystore(Curve::Stark, &path, "password").unwrap();
+    assert_eq!(
+        stark.public_key().await.unwrap(),
+        PublicKey::Stark(stark_key().verifying_key().scalar())
+    );
+    let secp256k1 = LocalSigner::from_keystore(Curve::Secp256k1, &path, "password").unwrap();
+    assert_eq!(
+        secp256k1.public_key().await.unwrap(),
+        PublicKey::Secp256k1(evm_wallet().address())
+    );
+
+    let err = LocalSigner::from_keystore(Curve::Stark, &path, "wrong")
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("failed to unlock keystore"));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_remote_signer() {
+    let url = mock_signer().await;
+
+    let stark = RemoteSigner::new(&url, "stark", Curve::Stark).with_token(TOKEN);
+    assert_eq!(
+        stark.public_key().await.unwrap(),
+        LocalSigner::from_hex(Curve::Stark, KEY)
+            .unwrap()
+            .public_key()
+            .await
+            .unwrap()
+    );
+    let signature = stark.sign_hash(HASH).await.unwrap().into_stark().unwrap();
+    assert!(ecdsa_verify(
+        &stark_key().verifying_key().scalar(),
+        &FieldElement::from_bytes_be(&HASH).unwrap(),
+        &signature
+    )
+    .unwrap());
+
+    let secp256k1 =
+        RemoteSigner::new(&format!("{url}/"), "secp256k1", Curve::Secp256k1).with_token(TOKEN);
+    let signature = secp256k1
+        .sign_hash(HASH)
+        .await
+        .unwrap()
+        .into_secp256k1()
+        .unwrap();
+    assert_eq!(signature, evm_wallet().sign_hash(H256(HASH)).unwrap());
+
+    // unauthenticated requests are rejected
+    let anonymous = RemoteSigner::new(&url, "stark", Curve::Stark);
+    assert!(anonymous.public_key().await.is_err());
+
+    // a key of another curve than expected is refused
+    let wrong_curve = RemoteSigner::new(&url, "stark", Curve::Secp256k1).with_token(TOKEN);
+    let err = wrong_curve.public_key().await.unwrap_err();
+    assert!(err.to_string().contains("is a stark key"));
+
+    // signatures of another key than announced are refused
+    let dishonest = RemoteSigner::new(&url, "dishonest", Curve::Secp256k1).with_token(TOKEN);
+    let err = dishonest.sign_hash(HASH).await.unwrap_err();
+    assert!(err.to_string().contains("another key"));
+}
+
+#[tokio::test]
+async fn test_account_signers() {
+    let url = mock_signer().await;
+    std::env::set_var("TEST_REMOTE_SIGNER_TOKEN", TOKEN);
+    let config = |key_id: &str| SignerConfig::Remote {
+        url: url.clone(),
+        key_id: key_id.to_string(),
+        token_var: Some("TEST_REMOTE_SIGNER_TOKEN".to_string()),
+    };
+
+    let stark = StarknetSigner::new(config("stark").signer(Curve::Stark).unwrap()).unwrap();
+    assert_eq!(
+        stark.get_public_key().await.unwrap().scalar(),
+        stark_key().verifying_key().scalar()
+    );
+    let hash = FieldElement::from_bytes_be(&HASH).unwrap();
+    let signature = starknet::signers::Signer::sign_hash(&stark, &hash)
+        .await
+        .unwrap();
+    let expected = stark_key().sign(&hash).unwrap();
+    assert_eq!((signature.r, signature.s), (expected.r, expected.s));
+    assert!(StarknetSigner::new(config("secp256k1").signer(Curve::Secp256k1).unwrap()).is_err());
+
+    let wallet = evm_wallet().with_chain_id(5u64);
+    let evm = EvmSigner::new(config("secp256k1").signer(Curve::Secp256k1).unwrap())
+        .await
+        .unwrap()
+        .with_chain_id(5u64);
+    assert_eq!(evm.address(), wallet.address());
+    assert_eq!(
+        evm.sign_message("hello").await.unwrap(),
+        wallet.sign_message("hello").await.unwrap()
+    );
+    assert_eq!(
+        evm.sign_hash(hash_message("hello")).await.unwrap(),
+        wallet.sign_hash(hash_message("hello")).unwrap()
+    );
+
+    // EIP-155 `v` of the signer chain id
+    let tx: TypedTransaction = TransactionRequest::new()
+        .to(wallet.address())
+        .value(1)
+        .nonce(0)
+        .gas(21000)
+        .gas_price(1)
+        .into();
+    let signature = evm.sign_transaction(&tx).await.unwrap();
+    assert_eq!(signature, wallet.sign_transaction(&tx).await.unwrap());
+    assert!(signature.v == 5 * 2 + 35 || signature.v == 5 * 2 + 36);
+}
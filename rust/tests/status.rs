@@ -0,0 +1,136 @@
+#[allow(dead_code)]
+mod constants;
+mod contracts;
+
+use std::process::Output;
+
+use ethers::{prelude::parse_log, signers::Signer, types::H256};
+use tokio::process::Command;
+
+use crate::{
+    constants::DOMAIN_EVM,
+    contracts::eth::{
+        self,
+        mailbox::{DispatchFilter, DispatchIdFilter},
+    },
+};
+
+async fn status(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_hyperlane-starknet"))
+        .args(args)
+        .output()
+        .await
+        .unwrap()
+}
+
+fn format_bytes(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+#[tokio::test]
+async fn test_status_requires_the_origin_along_with_its_transaction() {
+    let message_id = format_bytes(&[0x11; 32]);
+    let origin_tx = format_bytes(&[0x22; 32]);
+
+    for args in [
+        ["status", message_id.as_str(), "--origin", "evm"],
+        [
+            "status",
+            message_id.as_str(),
+            "--origin-tx",
+            origin_tx.as_str(),
+        ],
+    ] {
+        let output = status(&args).await;
+        let stderr = String::from_utf8_lossy(&output.stderr);
+
+        // rejected by the argument parser, before the configuration is loaded
+        assert_eq!(output.status.code(), Some(2), "{stderr}");
+        assert!(
+            stderr.contains("required arguments were not provided"),
+            "{stderr}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_status_of_an_evm_message() -> eyre::Result<()> {
+    let anvil = eth::setup_env(DOMAIN_EVM).await?;
+    let mailbox = &anvil.core.mailbox;
+
+    let config = std::env::temp_dir().join(format!("status-{}.json", std::process::id()));
+    std::fs::write(
+        &config,
+        serde_json::json!({
+            "chains": {
+                "evm": {
+                    "protocol": "ethereum",
+                    "rpc_url": anvil.app.endpoint(),
+                    "domain": DOMAIN_EVM,
+                    "addresses": { "mailbox": format!("{:#x}", mailbox.address()) }
+                }
+            }
+        })
+        .to_string(),
+    )?;
+
+    // the message is sent to the mailbox itself, to be delivered without relaying it
+    let recipient = H256::from(anvil.core.msg_receiver.address());
+    let receipt = mailbox
+        .dispatch_0(DOMAIN_EVM, recipient.0, b"hello".to_vec().into())
+        .send()
+        .await?
+        .await?
+        .unwrap();
+    let dispatch: DispatchFilter = parse_log(receipt.logs[0].clone())?;
+    let dispatch_id: DispatchIdFilter = parse_log(receipt.logs[1].clone())?;
+
+    let message_id = format_bytes(&dispatch_id.message_id);
+    let origin_tx = format_bytes(receipt.transaction_hash.as_bytes());
+    let args = [
+        "--config",
+        config.to_str().unwrap(),
+        "status",
+        message_id.as_str(),
+        "--origin",
+        "evm",
+        "--origin-tx",
+        origin_tx.as_str(),
+    ];
+
+    let output = status(&args).await;
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8(output.stdout)?;
+    assert!(stdout.contains(&format!(
+        "Dispatched on domain {DOMAIN_EVM} to domain {DOMAIN_EVM}, recipient {}",
+        format_bytes(recipient.as_bytes())
+    )));
+    assert!(stdout.contains(&format!(
+        "Recipient ISM on domain {DOMAIN_EVM}: {}",
+        format_bytes(H256::from(anvil.core.ism.address()).as_bytes())
+    )));
+    assert!(stdout.contains(&format!("Not delivered on domain {DOMAIN_EVM}")));
+
+    let receipt = mailbox
+        .process(vec![].into(), dispatch.message)
+        .send()
+        .await?
+        .await?
+        .unwrap();
+
+    let output = status(&args).await;
+    assert!(output.status.success(), "{output:?}");
+    let stdout = String::from_utf8(output.stdout)?;
+    assert!(stdout.contains(&format!("Delivered on domain {DOMAIN_EVM}")));
+    assert!(stdout.contains(&format!(
+        "Processor: {}",
+        format_bytes(H256::from(anvil.acc_owner.address()).as_bytes())
+    )));
+    assert!(stdout.contains(&format!(
+        "Processed at block: {}",
+        receipt.block_number.unwrap()
+    )));
+
+    std::fs::remove_file(&config)?;
+    Ok(())
+}
@@ -0,0 +1,214 @@
+use std::{sync::Mutex, time::Duration};
+
+use hyperlane_starknet::{
+    tx_watcher::{Finality, TxError, TxStatusSource, TxWatcher},
+    utils::BoxFuture,
+};
+use serde_json::json;
+use starknet::{
+    core::types::{FieldElement, MaybePendingTransactionReceipt, StarknetError, TransactionStatus},
+    providers::ProviderError,
+};
+use tokio::time::Instant;
+
+fn tx_hash() -> FieldElement {
+    FieldElement::from(0xabcdu64)
+}
+
+/// Replies of a mock node, the last one being repeated once the others are consumed.
+struct MockNode {
+    replies: Mutex<Vec<Option<serde_json::Value>>>,
+    rejected: bool,
+    polls: Mutex<Vec<Instant>>,
+}
+
+impl MockNode {
+    fn new(replies: Vec<Option<serde_json::Value>>) -> Self {
+        Self {
+            replies: Mutex::new(replies),
+            rejected: false,
+            polls: Mutex::new(vec![]),
+        }
+    }
+
+    /// Offsets of the polls from the first one.
+    fn poll_offsets(&self) -> Vec<Duration> {
+        let polls = self.polls.lock().unwrap();
+        polls.iter().map(|poll| *poll - polls[0]).collect()
+    }
+}
+
+impl TxStatusSource for MockNode {
+    fn transaction_receipt(
+        &self,
+        _hash: FieldElement,
+    ) -> BoxFuture<'_, Result<MaybePendingTransactionReceipt, ProviderError>> {
+        self.polls.lock().unwrap().push(Instant::now());
+        let mut replies = self.replies.lock().unwrap();
+        let reply = if replies.len() > 1 {
+            replies.remove(0)
+        } else {
+            replies[0].clone()
+        };
+
+        let result = match reply {
+            Some(receipt) => Ok(serde_json::from_value(receipt).unwrap()),
+            None => Err(ProviderError::StarknetError(
+                StarknetError::TransactionHashNotFound,
+            )),
+        };
+        Box::pin(std::future::ready(result))
+    }
+
+    fn transaction_status(
+        &self,
+        _hash: FieldElement,
+    ) -> BoxFuture<'_, Result<TransactionStatus, ProviderError>> {
+        let result = if self.rejected {
+            Ok(TransactionStatus::Rejected)
+        } else {
+            Err(ProviderError::StarknetError(
+                StarknetError::TransactionHashNotFound,
+            ))
+        };
+        Box::pin(std::future::ready(result))
+    }
+}
+
+/// Invoke receipt as returned by the JSON-RPC API, `finality` being `None` while pending.
+fn receipt(finality: Option<&str>, revert_reason: Option<&str>) -> serde_json::Value {
+    let mut receipt = json!({
+        "type": "INVOKE",
+        "transaction_hash": format!("{:#x}", tx_hash()),
+        "actual_fee": { "amount": "0x1", "unit": "WEI" },
+        "finality_status": finality.unwrap_or("ACCEPTED_ON_L2"),
+        "messages_sent": [],
+        "events": [],
+        "execution_resources": {
+            "steps": 1,
+            "data_availability": { "l1_gas": 0, "l1_data_gas": 0 }
+        },
+        "execution_status": "SUCCEEDED",
+    });
+    if finality.is_some() {
+        receipt["block_hash"] = json!("0x2");
+        receipt["block_number"] = json!(3);
+    }
+    if let Some(reason) = revert_reason {
+        receipt["execution_status"] = json!("REVERTED");
+        receipt["revert_reason"] = json!(reason);
+    }
+    receipt
+}
+
+fn replies() -> Vec<Option<serde_json::Value>> {
+    vec![
+        None,
+        Some(receipt(None, None)),
+        Some(receipt(Some("ACCEPTED_ON_L2"), None)),
+        Some(receipt(Some("ACCEPTED_ON_L1"), None)),
+    ]
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_tx_watcher_waits_for_the_finality_depth() {
+    for (finality, polls) in [
+        (Finality::Pending, 2),
+        (Finality::AcceptedOnL2, 3),
+        (Finality::AcceptedOnL1, 4),
+    ] {
+        let node = MockNode::new(replies());
+        let outcome = TxWatcher::new(&node)
+            .with_finality(finality)
+            .wait(tx_hash())
+            .await
+            .unwrap();
+
+        assert_eq!(outcome.hash, tx_hash());
+        assert_eq!(outcome.finality, finality);
+        assert_eq!(node.polls.lock().unwrap().len(), polls);
+    }
+
+    // a deeper finality than the requested one is accepted as well
+    let node = MockNode::new(vec![Some(receipt(Some("ACCEPTED_ON_L1"), None))]);
+    let outcome = TxWatcher::new(&node).wait(tx_hash()).await.unwrap();
+    assert_eq!(outcome.finality, Finality::AcceptedOnL1);
+    assert_eq!(node.polls.lock().unwrap().len(), 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_tx_watcher_backs_off_until_the_timeout() {
+    let node = MockNode::new(vec![Some(receipt(None, None))]);
+    let err = TxWatcher::new(&node)
+        .with_timeout(Duration::from_secs(1))
+        .with_backoff(Duration::from_millis(100), Duration::from_millis(400), 2)
+        .wait(tx_hash())
+        .await
+        .unwrap_err();
+
+    match err {
+        TxError::Timeout {
+            hash,
+            finality,
+            timeout,
+        } => {
+            assert_eq!(hash, tx_hash());
+            assert_eq!(finality, Finality::AcceptedOnL2);
+            assert_eq!(timeout, Duration::from_secs(1));
+        }
+        err => panic!("unexpected error: {err}"),
+    }
+
+    // the interval doubles up to its maximum and the last poll happens at the deadline
+    let offsets: Vec<_> = [0, 100, 300, 700, 1000]
+        .into_iter()
+        .map(Duration::from_millis)
+        .collect();
+    assert_eq!(node.poll_offsets(), offsets);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_tx_watcher_keeps_polling_unknown_transactions() {
+    let node = MockNode::new(vec![None]);
+    let err = TxWatcher::new(&node)
+        .with_timeout(Duration::from_millis(500))
+        .with_backoff(Duration::from_millis(100), Duration::from_millis(100), 1)
+        .wait(tx_hash())
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err, TxError::Timeout { .. }));
+    assert_eq!(node.polls.lock().unwrap().len(), 6);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_tx_watcher_reports_rejected_transactions() {
+    let node = MockNode {
+        rejected: true,
+        ..MockNode::new(vec![None])
+    };
+    let err = TxWatcher::new(&node).wait(tx_hash()).await.unwrap_err();
+
+    assert!(matches!(err, TxError::Rejected(hash) if hash == tx_hash()));
+    assert_eq!(node.polls.lock().unwrap().len(), 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_tx_watcher_reports_reverted_transactions() {
+    // reverted transactions fail without waiting for the finality
+    let node = MockNode::new(vec![Some(receipt(None, Some("out of gas")))]);
+    let err = TxWatcher::new(&node)
+        .with_finality(Finality::AcceptedOnL1)
+        .wait(tx_hash())
+        .await
+        .unwrap_err();
+
+    match err {
+        TxError::Reverted { hash, reason, .. } => {
+            assert_eq!(hash, tx_hash());
+            assert_eq!(reason, "out of gas");
+        }
+        err => panic!("unexpected error: {err}"),
+    }
+    assert_eq!(node.polls.lock().unwrap().len(), 1);
+}
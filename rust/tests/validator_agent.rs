@@ -0,0 +1,409 @@
+#[allow(dead_code)]
+mod fixtures;
+#[allow(dead_code)]
+mod hook;
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use ethers::{
+    signers::Signer,
+    types::{Address, U256},
+    utils::keccak256,
+};
+use hyperlane_starknet::{
+    checkpoint::{branch_root, Announcement, IncrementalMerkle, SignedAnnouncement},
+    checkpoint_syncer::{storage_location_to_felts, CheckpointSyncer, LocalCheckpointSyncer},
+    ecdsa::recover_signer,
+    utils::BoxFuture,
+    validator::{
+        announce, sign_announcement, sign_checkpoint, Announced, Equivocation, Halted,
+        SigningLedger, Validator, ValidatorAnnounceContract, ValidatorSettings,
+    },
+};
+use starknet::core::types::FieldElement;
+
+use fixtures::{checkpoint, evm_signer, proof, wallet};
+use hook::MockHook;
+
+/// `validator_announce` of domain 1 with mailbox `[0x44; 32]`, counting the announcements
+/// submitted.
+struct MockAnnounce {
+    announced: Vec<String>,
+    submitted: Arc<AtomicUsize>,
+}
+
+impl ValidatorAnnounceContract for MockAnnounce {
+    fn announced_locations(&self, _validator: Address) -> BoxFuture<'_, eyre::Result<Vec<String>>> {
+        Box::pin(async move { Ok(self.announced.clone()) })
+    }
+
+    fn announcement_digest<'a>(
+        &'a self,
+        storage_location: &'a str,
+    ) -> BoxFuture<'a, eyre::Result<[u8; 32]>> {
+        let announcement = Announcement {
+            validator: Address::zero(),
+            mailbox_address: [0x44; 32],
+            mailbox_domain: 1,
+            storage_location: storage_location.to_string(),
+        };
+        Box::pin(async move { Ok(announcement.digest()) })
+    }
+
+    fn submit<'a>(
+        &'a self,
+        _signed: &'a SignedAnnouncement,
+    ) -> BoxFuture<'a, eyre::Result<FieldElement>> {
+        self.submitted.fetch_add(1, Ordering::SeqCst);
+        Box::pin(async { Ok(FieldElement::ONE) })
+    }
+}
+
+/// Announcement of `storage_location` signed for the mailbox of `domain`.
+async fn signed_announcement(domain: u32, storage_location: &str) -> SignedAnnouncement {
+    let signer = evm_signer(wallet(7)).await;
+    sign_announcement(
+        &signer,
+        Announcement {
+            validator: signer.address(),
+            mailbox_address: [0x44; 32],
+            mailbox_domain: domain,
+            storage_location: storage_location.to_string(),
+        },
+    )
+    .await
+    .unwrap()
+}
+
+/// Validator of `hook` writing its checkpoints to a fresh directory named after `name`.
+async fn validator(hook: &MockHook, name: &str) -> (Validator, Arc<LocalCheckpointSyncer>) {
+    let path = std::env::temp_dir().join(format!("validator-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&path);
+    let syncer = Arc::new(LocalCheckpointSyncer::new(path).unwrap());
+    let signer = evm_signer(wallet(7)).await;
+    let settings = ValidatorSettings {
+        max_block_range: 2,
+        ..Default::default()
+    };
+    let validator = Validator::new(
+        hook.clone(),
+        signer,
+        syncer.clone(),
+        SigningLedger::in_memory().unwrap(),
+        settings,
+    );
+
+    (validator, syncer)
+}
+
+#[test]
+fn test_incremental_merkle_matches_proofs() {
+    let mut tree = IncrementalMerkle::default();
+    let empty = branch_root(&[0u8; 32], &proof(&[], 0), 0);
+    assert_eq!(tree.root(), empty);
+
+    let leaves: Vec<[u8; 32]> = (0..9u8).map(|i| keccak256([i])).collect();
+    for (i, leaf) in leaves.iter().enumerate() {
+        tree.ingest(*leaf);
+        assert_eq!(tree.count(), i as u32 + 1);
+        assert_eq!(
+            tree.root(),
+            branch_root(leaf, &proof(&leaves[..=i], i), i as u32)
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_signed_checkpoint_recovers_validator() {
+    let signer = evm_signer(wallet(7)).await;
+    let signed = sign_checkpoint(&signer, checkpoint(3)).await.unwrap();
+    assert_eq!(
+        recover_signer(&signed.value.digest(), &signed.signature).unwrap(),
+        signer.address()
+    );
+}
+
+#[tokio::test]
+async fn test_ledger_refuses_equivocation() {
+    let signer = evm_signer(wallet(7)).await;
+    let ledger = SigningLedger::in_memory().unwrap();
+    assert_eq!(ledger.check(&checkpoint(3)).unwrap(), None);
+
+    let signed = sign_checkpoint(&signer, checkpoint(3)).await.unwrap();
+    ledger.record(&signed).unwrap();
+    // signing the same checkpoint again reuses the recorded signature
+    assert_eq!(ledger.check(&checkpoint(3)).unwrap(), Some(signed.clone()));
+    ledger.record(&signed).unwrap();
+
+    let mut conflicting = checkpoint(3);
+    conflicting.checkpoint.root = [0xff; 32];
+    let err = ledger.check(&conflicting).unwrap_err();
+    let equivocation = err.downcast_ref::<Equivocation>().unwrap();
+    assert_eq!(equivocation.signed, checkpoint(3));
+    assert_eq!(equivocation.requested, conflicting);
+
+    let mut other_message = checkpoint(3);
+    other_message.message_id = [0x33; 32];
+    let conflicting = sign_checkpoint(&signer, other_message).await.unwrap();
+    let err = ledger.record(&conflicting).unwrap_err();
+    assert!(err.downcast_ref::<Equivocation>().is_some());
+
+    // the same index of another hook is a different checkpoint
+    let mut other_hook = checkpoint(3);
+    other_hook.checkpoint.merkle_tree_hook = [0x12; 32];
+    assert_eq!(ledger.check(&other_hook).unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_ledger_persists_history_and_halt() {
+    let path = std::env::temp_dir().join(format!("ledger-{}.db", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let signer = evm_signer(wallet(7)).await;
+
+    {
+        let ledger = SigningLedger::open(&path).unwrap();
+        for index in [0, 2, 1] {
+            ledger
+                .record(&sign_checkpoint(&signer, checkpoint(index)).await.unwrap())
+                .unwrap();
+        }
+        assert_eq!(ledger.halted().unwrap(), None);
+        let halted = ledger.halt("origin reorg").unwrap();
+        assert_eq!(halted.reason, "origin reorg");
+        // the first reason is kept
+        assert_eq!(ledger.halt("another").unwrap(), halted);
+    }
+
+    let ledger = SigningLedger::open(&path).unwrap();
+    assert_eq!(ledger.halted().unwrap().unwrap().reason, "origin reorg");
+    let entries = ledger.entries(1, &[0x11; 32], 1, 10).unwrap();
+    let indexes: Vec<u32> = entries
+        .iter()
+        .map(|entry| entry.checkpoint.value.checkpoint.index)
+        .collect();
+    assert_eq!(indexes, vec![1, 2]);
+    assert_eq!(entries[1].checkpoint.value, checkpoint(2));
+    assert!(ledger.entries(2, &[0x11; 32], 0, 10).unwrap().is_empty());
+
+    let mut conflicting = checkpoint(1);
+    conflicting.checkpoint.root = [0xff; 32];
+    assert!(ledger.check(&conflicting).is_err());
+
+    ledger.resume().unwrap();
+    assert_eq!(ledger.halted().unwrap(), None);
+
+    drop(ledger);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_announcement_digest_matches_validator_announce() {
+    // vector of `test_digest_computation` in the cairo contracts tests
+    let mut mailbox_address = [0u8; 32];
+    hex::decode_to_slice(
+        "0228c4f640b613dba2107cabf930564bbdb1b4e2d283ba1843b91e6327f09f8e",
+        &mut mailbox_address,
+    )
+    .unwrap();
+    let announcement = Announcement {
+        validator: wallet(7).address(),
+        mailbox_address,
+        mailbox_domain: 23448593,
+        storage_location:
+            "file:///var/folders/kr/z3l_6qyn3znb6gbnddtvgsn40000gn/T/.tmpdY51LU/checkpoint"
+                .to_string(),
+    };
+
+    let felts: Vec<FieldElement> = [
+        "180946006308525359965345158532346553211983108462325076142963585023296502126",
+        "90954189295124463684969781689350429239725285131197301894846683156275291225",
+        "276191619276790668637754154763775604",
+    ]
+    .iter()
+    .map(|felt| FieldElement::from_dec_str(felt).unwrap())
+    .collect();
+    assert_eq!(
+        storage_location_to_felts(&announcement.storage_location),
+        felts
+    );
+
+    let mut digest = [0u8; 32];
+    U256::from_dec_str(
+        "68490098148397702232337918459455233145663417151157276422147736490102791983827",
+    )
+    .unwrap()
+    .to_big_endian(&mut digest);
+    assert_eq!(announcement.digest(), digest);
+
+    let mut other_mailbox = announcement.clone();
+    other_mailbox.mailbox_address = [0x45; 32];
+    assert_ne!(other_mailbox.digest(), announcement.digest());
+}
+
+#[tokio::test]
+async fn test_signed_announcement_recovers_validator() {
+    let signer = evm_signer(wallet(7)).await;
+    let signed = sign_announcement(
+        &signer,
+        Announcement {
+            validator: signer.address(),
+            mailbox_address: [0x44; 32],
+            mailbox_domain: 1,
+            storage_location: "file:///checkpoints".to_string(),
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(signed.signature.len(), 65);
+    assert_eq!(
+        recover_signer(&signed.value.digest(), &signed.signature).unwrap(),
+        signer.address()
+    );
+}
+
+#[tokio::test]
+async fn test_validator_signs_the_new_checkpoints() {
+    let hook = MockHook::default();
+    let (mut validator, syncer) = validator(&hook, "tick").await;
+    assert!(validator.tick().await.unwrap().is_empty());
+    assert_eq!(syncer.latest_index().await.unwrap(), None);
+
+    // more blocks than the block range of a single query
+    hook.set(5, &[(1, 1), (3, 2), (5, 3)]);
+    let signed = validator.tick().await.unwrap();
+    let mut tree = IncrementalMerkle::default();
+    for (index, checkpoint) in signed.iter().enumerate() {
+        tree.ingest([index as u8 + 1; 32]);
+        assert_eq!(checkpoint.value.checkpoint.index, index as u32);
+        assert_eq!(checkpoint.value.checkpoint.root, tree.root());
+        assert_eq!(checkpoint.value.message_id, [index as u8 + 1; 32]);
+        assert_eq!(
+            recover_signer(&checkpoint.value.digest(), &checkpoint.signature).unwrap(),
+            validator.address()
+        );
+        assert_eq!(
+            syncer
+                .fetch_checkpoint(index as u32)
+                .await
+                .unwrap()
+                .as_ref(),
+            Some(checkpoint)
+        );
+    }
+    assert_eq!(signed.len(), 3);
+    assert_eq!(syncer.latest_index().await.unwrap(), Some(2));
+
+    // only the checkpoints of the messages inserted since are signed
+    assert!(validator.tick().await.unwrap().is_empty());
+    hook.set(6, &[(1, 1), (3, 2), (5, 3), (6, 4)]);
+    let signed = validator.tick().await.unwrap();
+    assert_eq!(signed.len(), 1);
+    assert_eq!(signed[0].value.checkpoint.index, 3);
+    assert_eq!(syncer.latest_index().await.unwrap(), Some(3));
+    assert_eq!(
+        validator
+            .ledger()
+            .entries(1, &[0x11; 32], 0, 10)
+            .unwrap()
+            .len(),
+        4
+    );
+}
+
+#[tokio::test]
+async fn test_validator_halts_when_a_reorg_replaces_a_signed_leaf() {
+    let hook = MockHook::default();
+    hook.set(2, &[(1, 1), (2, 2)]);
+    let (mut validator, syncer) = validator(&hook, "reorg-halt").await;
+    assert_eq!(validator.tick().await.unwrap().len(), 2);
+
+    // message 2 is replaced by message 3, another message being inserted after it, so that the
+    // latest index of the hook was never signed
+    hook.set(3, &[(1, 1), (2, 3), (3, 4)]);
+    let err = validator.tick().await.unwrap_err();
+    let halted = err.downcast_ref::<Halted>().unwrap();
+    assert!(halted.reason.contains("at index 1 replaced by message"));
+    assert_eq!(validator.ledger().halted().unwrap().as_ref(), Some(halted));
+
+    // nothing was signed on the new chain
+    assert_eq!(syncer.latest_index().await.unwrap(), Some(1));
+    assert_eq!(
+        validator
+            .ledger()
+            .entries(1, &[0x11; 32], 0, 10)
+            .unwrap()
+            .len(),
+        2
+    );
+    let err = validator.tick().await.unwrap_err();
+    assert!(err.downcast_ref::<Halted>().is_some());
+}
+
+#[tokio::test]
+async fn test_validator_indexes_again_after_a_reorg_keeping_signed_leaves() {
+    let hook = MockHook::default();
+    hook.set(2, &[(1, 1), (2, 2)]);
+    let (mut validator, syncer) = validator(&hook, "reorg-rewind").await;
+    assert_eq!(validator.tick().await.unwrap().len(), 2);
+
+    // message 2 leaves the chain, the local tree being ahead of the hook
+    hook.set(2, &[(1, 1)]);
+    assert!(validator.tick().await.unwrap().is_empty());
+
+    // then is inserted again in a later block, before another message
+    hook.set(5, &[(1, 1), (4, 2), (5, 5)]);
+    let signed = validator.tick().await.unwrap();
+    assert_eq!(signed.len(), 1);
+    assert_eq!(signed[0].value.checkpoint.index, 2);
+    assert_eq!(signed[0].value.message_id, [5; 32]);
+    assert_eq!(syncer.latest_index().await.unwrap(), Some(2));
+    assert_eq!(validator.ledger().halted().unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_announce_is_idempotent() {
+    let path = std::env::temp_dir().join(format!("announce-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&path);
+    let syncer = LocalCheckpointSyncer::new(&path).unwrap();
+    let location = syncer.announcement_location();
+    let submitted = Arc::new(AtomicUsize::new(0));
+
+    // already announced: nothing is written nor submitted
+    let contract = MockAnnounce {
+        announced: vec!["s3://bucket/us-east-1".to_string(), location.clone()],
+        submitted: submitted.clone(),
+    };
+    let signed = signed_announcement(1, &location).await;
+    assert_eq!(
+        announce(&contract, &signed, &syncer).await.unwrap(),
+        Announced::Already
+    );
+    assert_eq!(submitted.load(Ordering::SeqCst), 0);
+    assert_eq!(syncer.announcement().unwrap(), None);
+
+    // signed for the mailbox of another domain
+    let contract = MockAnnounce {
+        announced: vec![],
+        submitted: submitted.clone(),
+    };
+    let err = announce(&contract, &signed_announcement(2, &location).await, &syncer)
+        .await
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("does not match the validator_announce digest"));
+    assert_eq!(submitted.load(Ordering::SeqCst), 0);
+    assert_eq!(syncer.announcement().unwrap(), None);
+
+    assert_eq!(
+        announce(&contract, &signed, &syncer).await.unwrap(),
+        Announced::Submitted(FieldElement::ONE)
+    );
+    assert_eq!(submitted.load(Ordering::SeqCst), 1);
+    assert_eq!(syncer.announcement().unwrap(), Some(signed));
+
+    std::fs::remove_dir_all(&path).unwrap();
+}
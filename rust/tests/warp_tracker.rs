@@ -0,0 +1,328 @@
+#[allow(dead_code)]
+mod fixtures;
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use ethers::types::U256;
+use hyperlane_starknet::{
+    indexer::{IndexedEvent, MailboxEvent},
+    message::HyperlaneMessage,
+    utils::BoxFuture,
+    warp::{
+        match_transfers, match_transfers_with_timestamps, CollateralAlert, IndexedTransfer,
+        RouteBalance, RouteTotals, TransferEvent, TransferStatus, WarpRouteSource, WarpTracker,
+    },
+};
+
+const COLLATERAL: u32 = 1;
+const SYNTHETIC: u32 = 2;
+const ROUTER: [u8; 32] = [0x33; 32];
+
+struct MockRoute {
+    latest_block: u64,
+    transfers: Vec<IndexedTransfer>,
+    balance: Arc<Mutex<RouteBalance>>,
+}
+
+impl WarpRouteSource for MockRoute {
+    fn latest_block(&self) -> BoxFuture<'_, eyre::Result<u64>> {
+        Box::pin(async move { Ok(self.latest_block) })
+    }
+
+    fn transfers(&self, from: u64, to: u64) -> BoxFuture<'_, eyre::Result<Vec<IndexedTransfer>>> {
+        assert!(to <= self.latest_block);
+        let transfers = self
+            .transfers
+            .iter()
+            .filter(|transfer| (from..=to).contains(&transfer.block_number))
+            .copied()
+            .collect();
+        Box::pin(async move { Ok(transfers) })
+    }
+
+    fn balance(&self, _block: u64) -> BoxFuture<'_, eyre::Result<RouteBalance>> {
+        let balance = *self.balance.lock().unwrap();
+        Box::pin(async move { Ok(balance) })
+    }
+}
+
+fn message(nonce: u32, sender: [u8; 32], recipient: [u8; 32]) -> HyperlaneMessage {
+    HyperlaneMessage {
+        origin: COLLATERAL,
+        sender,
+        destination: SYNTHETIC,
+        recipient,
+        body: vec![],
+        ..fixtures::message(nonce)
+    }
+}
+
+fn mailbox_event(transaction: u8, block_number: u64, event: MailboxEvent) -> IndexedEvent {
+    IndexedEvent {
+        block_number,
+        block_hash: [block_number as u8; 32],
+        block_timestamp: Some(block_number * 12),
+        transaction_hash: [transaction; 32],
+        event,
+    }
+}
+
+fn sent(amount: u64) -> TransferEvent {
+    TransferEvent::Sent {
+        destination: SYNTHETIC,
+        recipient: [0x44; 32],
+        amount: amount.into(),
+    }
+}
+
+fn received(origin: u32, amount: u64) -> TransferEvent {
+    TransferEvent::Received {
+        origin,
+        recipient: [0x44; 32],
+        amount: amount.into(),
+    }
+}
+
+fn transfer(id: u8, block_number: u64, event: TransferEvent) -> IndexedTransfer {
+    IndexedTransfer {
+        message_id: [id; 32],
+        block_number,
+        block_timestamp: block_number * 12,
+        transaction_hash: [id; 32],
+        event,
+    }
+}
+
+#[test]
+fn test_match_transfers_pairs_messages_of_the_transaction() {
+    let ours = message(0, ROUTER, [0x55; 32]);
+    let theirs = message(1, [0x66; 32], [0x55; 32]);
+    let mailbox_events = vec![
+        // transaction 1 dispatches a message of another sender, then one of the router
+        mailbox_event(1, 10, MailboxEvent::Dispatch(theirs.clone())),
+        mailbox_event(1, 10, MailboxEvent::DispatchId(theirs.id())),
+        mailbox_event(1, 10, MailboxEvent::Dispatch(ours.clone())),
+        mailbox_event(1, 10, MailboxEvent::DispatchId(ours.id())),
+        // transaction 2 delivers a message to another recipient, then one to the router
+        mailbox_event(
+            2,
+            11,
+            MailboxEvent::Process {
+                origin: SYNTHETIC,
+                sender: [0x77; 32],
+                recipient: [0x88; 32],
+            },
+        ),
+        mailbox_event(2, 11, MailboxEvent::ProcessId([0xee; 32])),
+        mailbox_event(
+            2,
+            11,
+            MailboxEvent::Process {
+                origin: SYNTHETIC,
+                sender: [0x77; 32],
+                recipient: ROUTER,
+            },
+        ),
+        mailbox_event(2, 11, MailboxEvent::ProcessId([0xff; 32])),
+    ];
+
+    let transfers = match_transfers(
+        ROUTER,
+        vec![([1; 32], sent(100)), ([2; 32], received(SYNTHETIC, 30))],
+        &mailbox_events,
+    )
+    .unwrap();
+    assert_eq!(
+        transfers,
+        vec![
+            IndexedTransfer {
+                message_id: ours.id(),
+                block_number: 10,
+                block_timestamp: 120,
+                transaction_hash: [1; 32],
+                event: sent(100),
+            },
+            IndexedTransfer {
+                message_id: [0xff; 32],
+                block_number: 11,
+                block_timestamp: 132,
+                transaction_hash: [2; 32],
+                event: received(SYNTHETIC, 30),
+            },
+        ]
+    );
+
+    // a single message of the router was dispatched by transaction 1
+    let err = match_transfers(
+        ROUTER,
+        vec![([1; 32], sent(100)), ([1; 32], sent(100))],
+        &mailbox_events,
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("no dispatch of a message"));
+}
+
+#[tokio::test]
+async fn test_match_transfers_looks_up_the_blocks_of_transfers_only() {
+    let ours = message(0, ROUTER, [0x55; 32]);
+    let theirs = message(1, [0x66; 32], [0x55; 32]);
+    let mut mailbox_events = vec![
+        mailbox_event(1, 10, MailboxEvent::Dispatch(ours.clone())),
+        mailbox_event(1, 10, MailboxEvent::DispatchId(ours.id())),
+        // a transaction without transfer of the router, in another block
+        mailbox_event(9, 12, MailboxEvent::Dispatch(theirs.clone())),
+        mailbox_event(9, 12, MailboxEvent::DispatchId(theirs.id())),
+    ];
+    for indexed in &mut mailbox_events {
+        indexed.block_timestamp = None;
+    }
+
+    let lookups = Arc::new(Mutex::new(vec![]));
+    let block_timestamp = |block_hash: [u8; 32]| {
+        lookups.lock().unwrap().push(block_hash);
+        async move { Ok(block_hash[0] as u64 * 12) }
+    };
+    let transfers = match_transfers_with_timestamps(
+        ROUTER,
+        vec![([1; 32], sent(100))],
+        mailbox_events,
+        block_timestamp,
+    )
+    .await
+    .unwrap();
+    assert_eq!(transfers.len(), 1);
+    assert_eq!(transfers[0].message_id, ours.id());
+    assert_eq!(transfers[0].block_timestamp, 120);
+    assert_eq!(*lookups.lock().unwrap(), vec![[10; 32]]);
+}
+
+#[tokio::test]
+async fn test_warp_tracker_joins_both_sides_of_transfers() {
+    let locked = Arc::new(Mutex::new(RouteBalance::Collateral(120.into())));
+    let collateral = MockRoute {
+        latest_block: 14,
+        transfers: vec![
+            transfer(1, 10, sent(100)),
+            transfer(2, 11, sent(50)),
+            // sent before the start block of the synthetic route
+            transfer(3, 12, received(SYNTHETIC, 30)),
+            // not confirmed yet
+            transfer(4, 13, sent(1000)),
+        ],
+        balance: locked.clone(),
+    };
+    let synthetic = MockRoute {
+        latest_block: 7,
+        transfers: vec![transfer(1, 5, received(COLLATERAL, 100))],
+        balance: Arc::new(Mutex::new(RouteBalance::Synthetic(70.into()))),
+    };
+
+    let mut tracker = WarpTracker::new()
+        .with_route(COLLATERAL, collateral, 0)
+        .with_route(SYNTHETIC, synthetic, 0)
+        .with_max_block_range(4)
+        .with_confirmations(2);
+    tracker.sync().await.unwrap();
+
+    let delivered = tracker.transfer(&[1; 32]).unwrap();
+    assert_eq!(delivered.status(), TransferStatus::Delivered);
+    assert_eq!(
+        (delivered.origin, delivered.destination),
+        (COLLATERAL, SYNTHETIC)
+    );
+    // sent at 120, received at 60 on a chain with another clock
+    assert_eq!(delivered.latency(), Some(Duration::ZERO));
+    assert_eq!(
+        tracker.transfer(&[2; 32]).unwrap().status(),
+        TransferStatus::InFlight
+    );
+    assert_eq!(tracker.transfer(&[2; 32]).unwrap().latency(), None);
+    assert_eq!(
+        tracker.transfer(&[3; 32]).unwrap().status(),
+        TransferStatus::ReceivedOnly
+    );
+    assert!(tracker.transfer(&[4; 32]).is_none());
+
+    assert_eq!(
+        tracker.totals().get(&(COLLATERAL, SYNTHETIC)),
+        Some(&RouteTotals {
+            sent: 150.into(),
+            received: 100.into(),
+            in_flight: 50.into(),
+        })
+    );
+    assert_eq!(
+        tracker.totals().get(&(SYNTHETIC, COLLATERAL)),
+        Some(&RouteTotals {
+            sent: U256::zero(),
+            received: 30.into(),
+            in_flight: U256::zero(),
+        })
+    );
+
+    // transfer 2 was sent at 132
+    assert!(tracker
+        .in_flight_older_than(132 + 60, Duration::from_secs(60))
+        .is_empty());
+    let in_flight = tracker.in_flight_older_than(133 + 60, Duration::from_secs(60));
+    assert_eq!(in_flight.len(), 1);
+    assert_eq!(in_flight[0].message_id, [2; 32]);
+
+    // 120 locked for 70 minted and 50 in flight
+    assert_eq!(tracker.check_collateral().await.unwrap(), None);
+
+    *locked.lock().unwrap() = RouteBalance::Collateral(110.into());
+    let alert = CollateralAlert {
+        collateral: COLLATERAL,
+        locked: 110.into(),
+        synthetic_supply: 70.into(),
+        in_flight: 50.into(),
+    };
+    assert_eq!(tracker.check_collateral().await.unwrap(), Some(alert));
+
+    let tracker = tracker.with_tolerance(10.into());
+    assert_eq!(tracker.check_collateral().await.unwrap(), None);
+}
+
+#[test]
+fn test_warp_tracker_receives_before_sending() {
+    let mut tracker = WarpTracker::new();
+    tracker.ingest(SYNTHETIC, &transfer(1, 5, received(COLLATERAL, 100)));
+    assert_eq!(
+        tracker.transfer(&[1; 32]).unwrap().status(),
+        TransferStatus::ReceivedOnly
+    );
+
+    tracker.ingest(COLLATERAL, &transfer(1, 3, sent(100)));
+    let transfer = tracker.transfer(&[1; 32]).unwrap();
+    assert_eq!(transfer.status(), TransferStatus::Delivered);
+    assert_eq!(transfer.latency(), Some(Duration::from_secs(24)));
+    assert_eq!(
+        tracker.totals().get(&(COLLATERAL, SYNTHETIC)),
+        Some(&RouteTotals {
+            sent: 100.into(),
+            received: 100.into(),
+            in_flight: U256::zero(),
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_warp_tracker_indexes_at_least_one_block_per_range() {
+    let route = MockRoute {
+        latest_block: 3,
+        transfers: vec![transfer(1, 2, sent(100))],
+        balance: Arc::new(Mutex::new(RouteBalance::Collateral(100.into()))),
+    };
+    let mut tracker = WarpTracker::new()
+        .with_route(COLLATERAL, route, 0)
+        .with_max_block_range(0);
+    tracker.sync().await.unwrap();
+    assert_eq!(
+        tracker.transfer(&[1; 32]).unwrap().status(),
+        TransferStatus::InFlight
+    );
+}
@@ -0,0 +1,193 @@
+#[allow(dead_code)]
+mod constants;
+mod contracts;
+mod validator;
+
+use cainome::cairo_serde::{ContractAddress, U256};
+use hyperlane_starknet::contracts::strk::{
+    declare_contract,
+    hyp_xerc20::HypXERC20,
+    hyp_xerc20_lockbox::HypXERC20Lockbox,
+    mailbox::{mailbox, Bytes, Event as MailboxEvent},
+    test_erc20::TestERC20,
+    xerc20::{
+        declare_xerc20_classes, deploy_xerc20_factory, deploy_xerc20_warp_route, BridgeLimits,
+        XERC20RouteConfig,
+    },
+    xerc20_token::XERC20,
+};
+use hyperlane_starknet::tx_watcher::TxWatcher;
+use starknet::{
+    accounts::{Account, ConnectedAccount},
+    core::types::FieldElement,
+};
+
+use crate::{constants::DOMAIN_STRK, contracts::strk};
+
+const LIMIT: u128 = 1_000_000_000;
+const AMOUNT: u128 = 250_000;
+
+fn u256(value: u128) -> U256 {
+    U256 {
+        low: value,
+        high: 0,
+    }
+}
+
+fn felt_to_u256(value: FieldElement) -> U256 {
+    U256::from_bytes_be(&value.to_bytes_be())
+}
+
+#[tokio::test]
+async fn test_xerc20_lockbox_to_xerc20_route() -> eyre::Result<()> {
+    let strk = strk::setup_env(DOMAIN_STRK, &[]).await?;
+    let owner = &strk.acc_owner;
+    let tester = &strk.acc_tester;
+
+    // declare and deploy the factory
+    let codes = declare_xerc20_classes(&strk.acc_deployer).await?;
+    let factory = deploy_xerc20_factory(&codes, owner, &strk.acc_deployer).await?;
+
+    // base token locked by the lockbox (total_supply: u256, decimals: u8)
+    let erc20_class = declare_contract(&strk.acc_deployer, "TestERC20").await?;
+    let (base_token, _) = strk::deploy_contract(
+        erc20_class,
+        vec![
+            FieldElement::ZERO,
+            FieldElement::ZERO,
+            FieldElement::from(18u8),
+        ],
+        &strk.acc_deployer,
+    )
+    .await;
+
+    let limits = BridgeLimits {
+        minting: u256(LIMIT),
+        burning: u256(LIMIT),
+    };
+    let route = deploy_xerc20_warp_route(
+        &codes,
+        factory,
+        XERC20RouteConfig {
+            name: "Hyperlane xERC20",
+            symbol: "HXERC20",
+            base_token: Some(base_token),
+            limits: limits.clone(),
+            mailbox: strk.core.mailbox,
+            hook: strk.core.default_hook,
+            ism: strk.core.default_ism,
+        },
+        owner,
+        &strk.acc_deployer,
+    )
+    .await?;
+    let lockbox = route.lockbox.expect("lockbox deployed");
+    let hyp_xerc20_lockbox = route.hyp_xerc20_lockbox.expect("lockbox route deployed");
+
+    // both routes are bridges of the xERC20
+    let xerc20 = XERC20::new(route.xerc20, owner);
+    for bridge in [route.hyp_xerc20, hyp_xerc20_lockbox] {
+        let bridge = ContractAddress(bridge);
+        assert_eq!(
+            xerc20.minting_max_limit_of(&bridge).call().await?,
+            limits.minting
+        );
+        assert_eq!(
+            xerc20.burning_max_limit_of(&bridge).call().await?,
+            limits.burning
+        );
+    }
+
+    // enroll the routes with each other, looping back on the local domain
+    let res = HypXERC20::new(route.hyp_xerc20, owner)
+        .enroll_remote_router(&DOMAIN_STRK, &felt_to_u256(hyp_xerc20_lockbox))
+        .send()
+        .await?;
+    TxWatcher::new(owner.provider())
+        .wait(res.transaction_hash)
+        .await?;
+    let res = HypXERC20Lockbox::new(hyp_xerc20_lockbox, owner)
+        .enroll_remote_router(&DOMAIN_STRK, &felt_to_u256(route.hyp_xerc20))
+        .send()
+        .await?;
+    TxWatcher::new(owner.provider())
+        .wait(res.transaction_hash)
+        .await?;
+
+    // fund the tester and bridge the base token through the lockbox route
+    let base = TestERC20::new(base_token, tester);
+    let res = base
+        .mint(&ContractAddress(tester.address()), &u256(AMOUNT))
+        .send()
+        .await?;
+    TxWatcher::new(tester.provider())
+        .wait(res.transaction_hash)
+        .await?;
+    let res = base
+        .approve(&ContractAddress(hyp_xerc20_lockbox), &u256(AMOUNT))
+        .send()
+        .await?;
+    TxWatcher::new(tester.provider())
+        .wait(res.transaction_hash)
+        .await?;
+
+    let res = HypXERC20Lockbox::new(hyp_xerc20_lockbox, tester)
+        .transfer_remote(
+            &DOMAIN_STRK,
+            &felt_to_u256(tester.address()),
+            &u256(AMOUNT),
+            &u256(0),
+            &None,
+            &None,
+        )
+        .send()
+        .await?;
+    let outcome = TxWatcher::new(tester.provider())
+        .wait(res.transaction_hash)
+        .await?;
+
+    let message = outcome
+        .events_from::<MailboxEvent>(strk.core.mailbox)
+        .into_iter()
+        .find_map(|e| match e {
+            MailboxEvent::Dispatch(dispatch) => Some(dispatch.message),
+            _ => None,
+        })
+        .expect("Dispatch event not found");
+
+    // deliver the transfer to the xERC20 route
+    let res = mailbox::new(strk.core.mailbox, tester)
+        .process(
+            &Bytes {
+                size: 0,
+                data: vec![],
+            },
+            &message,
+        )
+        .send()
+        .await?;
+    TxWatcher::new(tester.provider())
+        .wait(res.transaction_hash)
+        .await?;
+
+    assert_eq!(
+        base.balance_of(&ContractAddress(lockbox)).call().await?,
+        u256(AMOUNT)
+    );
+    assert_eq!(
+        xerc20
+            .balance_of(&ContractAddress(tester.address()))
+            .call()
+            .await?,
+        u256(AMOUNT)
+    );
+
+    // the limit replenishes over time, it can only have grown back since the mint
+    let current_limit = xerc20
+        .minting_current_limit_of(&ContractAddress(route.hyp_xerc20))
+        .call()
+        .await?;
+    assert!(current_limit.low >= LIMIT - AMOUNT && current_limit.low < LIMIT);
+
+    Ok(())
+}